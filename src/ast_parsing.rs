@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use crate::diagnostics::{Rule, RuleSelector, Spans};
 use crate::parsing::extract_docstring;
 use tree_sitter::Node;
 
@@ -9,6 +10,12 @@ pub(crate) enum FunctionLocation<'a> {
     Row(usize),
 }
 
+impl Default for FunctionLocation<'_> {
+    fn default() -> Self {
+        FunctionLocation::Name("")
+    }
+}
+
 impl<'a> Display for FunctionLocation<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -18,14 +25,379 @@ impl<'a> Display for FunctionLocation<'a> {
     }
 }
 
+/// A parameter of a function's signature.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct Param<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) typ: Option<&'a str>,
+    /// Whether the parameter has a default value.
+    pub(crate) has_default: bool,
+}
+
+impl<'a> Param<'a> {
+    pub(crate) fn new(name: &'a str, typ: Option<&'a str>, has_default: bool) -> Self {
+        Self {
+            name,
+            typ,
+            has_default,
+        }
+    }
+}
+
 /// Information about a function's signature and docstring.
+#[derive(Default)]
 pub(crate) struct FunctionInfo<'a, 'b> {
-    pub(crate) params: &'b [(&'a str, Option<&'a str>)],
+    pub(crate) params: &'b [Param<'a>],
     pub(crate) docstring: Option<&'a str>,
     pub(crate) function_name: FunctionLocation<'a>,
+    /// Row (0-based) in which the function is defined, if known.
+    pub(crate) row: Option<usize>,
+    /// Row (0-based) in which the body of the function ends, if known. Only found by the
+    /// tree-sitter parser.
+    pub(crate) end_row: Option<usize>,
+    /// Column (0-based) in which the function is defined, if known.
+    pub(crate) column: Option<usize>,
+    /// Row and column (0-based) in which the docstring starts, if there is one.
+    pub(crate) docstring_start: Option<(usize, usize)>,
+    /// Byte ranges of the signature, params and docstring in the source code.
+    pub(crate) spans: Spans,
+    pub(crate) name: &'a str,
+    pub(crate) return_type: Option<&'a str>,
+    /// Exceptions raised in the body of the function, if they were collected.
+    pub(crate) raises: Option<Vec<&'a str>>,
+    /// Whether the function is a generator, if it was determined.
+    pub(crate) is_generator: Option<bool>,
+    /// Docstring of the class enclosing the function, if it was looked up.
+    pub(crate) class_docstring: Option<&'a str>,
+    /// Dotted names of the decorators of the function, without their arguments. Only
+    /// collected by the tree-sitter parser.
+    pub(crate) decorators: Vec<&'a str>,
+    /// Number of types in the type comment of the signature and number of params, in case
+    /// they differ.
+    pub(crate) type_comment_mismatch: Option<(usize, usize)>,
+    /// Whether the signature has a `**` param, even if it was left out of the params.
+    pub(crate) has_var_kwargs: bool,
+    /// Whether the body of the function is just `...` or `pass`, possibly after its
+    /// docstring.
+    pub(crate) is_stub: bool,
+    /// Whether the function is defined directly in the body of a class.
+    pub(crate) is_method: bool,
+    /// Checks suppressed by `# pystaleds: ignore` comments on its `def` line or the line
+    /// directly above it, if any.
+    pub(crate) suppression: Option<Suppression>,
+}
+
+impl FunctionInfo<'_, '_> {
+    /// Whether the diagnostics of the rule are suppressed by a comment.
+    pub(crate) fn is_suppressed(&self, rule: Rule) -> bool {
+        self.suppression
+            .as_ref()
+            .is_some_and(|suppression| suppression.suppresses(rule))
+    }
+}
+
+/// Checks of a function suppressed by comments.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Suppression {
+    /// Every check, as in `# pystaleds: ignore`.
+    All,
+    /// The checks of the rules whose codes start with the selectors, as in
+    /// `# pystaleds: ignore[PSD203, PSD4]`.
+    Rules {
+        selectors: Vec<RuleSelector>,
+        /// Codes in the brackets that do not match any rule.
+        unknown: Vec<String>,
+    },
+}
+
+impl Suppression {
+    /// Whether the diagnostics of the rule are suppressed.
+    pub(crate) fn suppresses(&self, rule: Rule) -> bool {
+        match self {
+            Suppression::All => true,
+            Suppression::Rules { selectors, .. } => {
+                selectors.iter().any(|selector| selector.matches(rule))
+            }
+        }
+    }
+
+    /// Combines the suppressions of two comments of the same function.
+    pub(crate) fn merge(self, other: Suppression) -> Suppression {
+        match (self, other) {
+            (
+                Suppression::Rules {
+                    mut selectors,
+                    mut unknown,
+                },
+                Suppression::Rules {
+                    selectors: other_selectors,
+                    unknown: other_unknown,
+                },
+            ) => {
+                selectors.extend(other_selectors);
+                unknown.extend(other_unknown);
+
+                Suppression::Rules { selectors, unknown }
+            }
+            _ => Suppression::All,
+        }
+    }
+}
+
+/// Marker of the comments with directives for pystaleds, such as `# pystaleds: ignore`.
+pub(crate) const DIRECTIVE_MARKER: &str = "pystaleds:";
+
+/// Number of lines at the start of a file in which a `# pystaleds: skip-file` comment and
+/// the markers of generated files are looked for.
+pub(crate) const HEADER_LINES: usize = 10;
+
+/// Directives for pystaleds in a comment, which may follow other comments in the same
+/// line, as in `# noqa # pystaleds: ignore[PSD203]`.
+fn directives(comment: &str) -> impl Iterator<Item = &str> {
+    comment
+        .split('#')
+        .skip(1)
+        .filter_map(|part| Some(part.trim().strip_prefix(DIRECTIVE_MARKER)?.trim()))
+}
+
+/// Determines if there is a `# pystaleds: skip-file` comment in the first
+/// [`HEADER_LINES`] lines of the source code, without parsing it.
+pub(crate) fn has_skip_file_comment(source_code: &str) -> bool {
+    source_code
+        .lines()
+        .take(HEADER_LINES)
+        .map(str::trim)
+        .filter(|line| line.starts_with('#'))
+        .any(|comment| directives(comment).any(|directive| directive == "skip-file"))
+}
+
+/// Finds the first of the markers of generated files, such as `@generated`, that is in
+/// the first [`HEADER_LINES`] lines of the source code, without parsing it.
+pub(crate) fn generated_marker<'a>(source_code: &str, markers: &'a [String]) -> Option<&'a str> {
+    source_code.lines().take(HEADER_LINES).find_map(|line| {
+        markers
+            .iter()
+            .find(|marker| !marker.is_empty() && line.contains(marker.as_str()))
+            .map(String::as_str)
+    })
+}
+
+/// Parses the suppression of a comment. Returns `None` if it is not a suppression comment.
+pub(crate) fn parse_suppression(comment: &str) -> Option<Suppression> {
+    directives(comment)
+        .filter_map(|directive| {
+            if directive == "ignore" {
+                return Some(Suppression::All);
+            }
+
+            let codes = directive.strip_prefix("ignore")?.trim_start();
+            let codes = codes.strip_prefix('[')?.strip_suffix(']')?;
+
+            let (selectors, unknown) = codes
+                .split(',')
+                .map(str::trim)
+                .filter(|code| !code.is_empty())
+                .map(|code| code.parse::<RuleSelector>().map_err(|_| code.to_string()))
+                .partition::<Vec<_>, _>(Result::is_ok);
+
+            Some(Suppression::Rules {
+                selectors: selectors.into_iter().filter_map(Result::ok).collect(),
+                unknown: unknown.into_iter().filter_map(Result::err).collect(),
+            })
+        })
+        .reduce(Suppression::merge)
+}
+
+/// Parses the suppression of the line directly above the one of `offset`, if it is a
+/// comment.
+pub(crate) fn suppression_above(source_code: &str, offset: usize) -> Option<Suppression> {
+    let previous_end = source_code[..offset].rfind('\n')?;
+    let previous_start = source_code[..previous_end].rfind('\n').map_or(0, |i| i + 1);
+    let previous = source_code[previous_start..previous_end].trim();
+
+    previous
+        .starts_with('#')
+        .then(|| parse_suppression(previous))?
+}
+
+/// Parses a PEP 484 type comment of a signature, e.g. `# type: (int, str) -> bool`, into
+/// the types of its params and its return type.
+///
+/// The types of the params are `None` if they are omitted with `...`. Returns `None` if
+/// the comment is not the type comment of a signature.
+pub(crate) fn parse_type_comment(comment: &str) -> Option<(Option<Vec<&str>>, &str)> {
+    let signature = comment
+        .strip_prefix('#')?
+        .trim_start()
+        .strip_prefix("type:")?
+        .trim();
+
+    let (params, return_type) = signature.split_once("->")?;
+
+    let params = params.trim().strip_prefix('(')?.strip_suffix(')')?.trim();
+
+    if params == "..." {
+        return Some((None, return_type.trim()));
+    }
+
+    let mut types = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, c) in params.char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                types.push(params[start..i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+
+    let last = params[start..].trim();
+
+    if !last.is_empty() {
+        types.push(last);
+    }
+
+    Some((Some(types), return_type.trim()))
+}
+
+/// Merges the types of a type comment into the params and return type that are not
+/// annotated. Stars in the types of `*args` and `**kwargs` (e.g. `*str`) are dropped.
+///
+/// Returns the number of types in the comment and the number of params in case they
+/// differ, without merging the types of the params.
+pub(crate) fn apply_type_comment<'a>(
+    comment: &'a str,
+    params: &mut [Param<'a>],
+    return_type: &mut Option<&'a str>,
+) -> Option<(usize, usize)> {
+    let (types, comment_return_type) = parse_type_comment(comment)?;
+
+    return_type.get_or_insert(comment_return_type);
+
+    let types = types?;
+
+    if types.len() != params.len() {
+        return Some((types.len(), params.len()));
+    }
+
+    for (param, typ) in params.iter_mut().zip(types) {
+        param.typ.get_or_insert(typ.trim_start_matches('*'));
+    }
+
+    None
+}
+
+/// Information about a class's annotated attributes and docstring.
+pub(crate) struct ClassInfo<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) row: usize,
+    pub(crate) column: usize,
+    pub(crate) docstring: Option<&'a str>,
+    /// Row and column (0-based) in which the docstring starts, if there is one.
+    pub(crate) docstring_start: Option<(usize, usize)>,
+    /// Names and types of the annotated assignments in the body of the class.
+    pub(crate) attributes: Vec<(&'a str, Option<&'a str>)>,
+    /// Checks suppressed by `# pystaleds: ignore` comments on its `class` line or the line
+    /// directly above it, if any.
+    pub(crate) suppression: Option<Suppression>,
+}
+
+impl ClassInfo<'_> {
+    /// Whether the diagnostics of the rule are suppressed by a comment.
+    pub(crate) fn is_suppressed(&self, rule: Rule) -> bool {
+        self.suppression
+            .as_ref()
+            .is_some_and(|suppression| suppression.suppresses(rule))
+    }
+}
+
+/// Extracts class information from a node if it is a class definition.
+///
+/// Attributes whose names start with `_` are left out.
+pub(crate) fn get_class_info<'a>(node: &Node, source_code: &'a str) -> Option<ClassInfo<'a>> {
+    if node.kind() != "class_definition" {
+        return None;
+    }
+
+    let name = node
+        .child_by_field_name("name")?
+        .utf8_text(source_code.as_bytes())
+        .ok()?;
+
+    let block = node.child_by_field_name("body")?;
+    let docstring = extract_docstring(block.utf8_text(source_code.as_bytes()).ok()?);
+
+    let mut attributes = Vec::new();
+    let mut cursor = block.walk();
+
+    for statement in block.named_children(&mut cursor) {
+        let Some(assignment) = statement
+            .named_child(0)
+            .filter(|_| statement.kind() == "expression_statement")
+            .filter(|assignment| assignment.kind() == "assignment")
+        else {
+            continue;
+        };
+
+        let (Some(left), Some(typ)) = (
+            assignment.child_by_field_name("left"),
+            assignment.child_by_field_name("type"),
+        ) else {
+            continue;
+        };
+
+        if left.kind() != "identifier" {
+            continue;
+        }
+
+        let (Ok(attribute), Ok(typ)) = (
+            left.utf8_text(source_code.as_bytes()),
+            typ.utf8_text(source_code.as_bytes()),
+        ) else {
+            continue;
+        };
+
+        if !attribute.starts_with('_') {
+            attributes.push((attribute, Some(typ)));
+        }
+    }
+
+    // A comment after the colon is placed in the body of the class.
+    let row = node.start_position().row;
+    let suppression = node
+        .children(&mut node.walk())
+        .chain(block.children(&mut block.walk()))
+        .filter(|child| child.kind() == "comment" && child.start_position().row == row)
+        .filter_map(|comment| comment.utf8_text(source_code.as_bytes()).ok())
+        .filter_map(parse_suppression)
+        .chain(suppression_above(source_code, node.start_byte()))
+        .reduce(Suppression::merge);
+
+    Some(ClassInfo {
+        name,
+        row,
+        column: node.start_position().column,
+        docstring,
+        docstring_start: docstring.map(|_| {
+            let start = block.start_position();
+            (start.row, start.column)
+        }),
+        attributes,
+        suppression,
+    })
 }
 
-/// Extracts function information from a node if it is a function definition.
+/// Extracts function information from a node if it is a function definition, leaving
+/// the receiver (i.e., `self` or `cls`), the ones in `ignored_params` and, if
+/// `skip_args_and_kwargs`, `*args` and `**kwargs` out of its params.
+///
+/// The receiver is the first param of functions defined directly in the body of a class,
+/// unless they are decorated with `staticmethod`.
 ///
 /// Uses a buffered params vector for performance, instead of allocating a new one
 /// every time.
@@ -33,7 +405,9 @@ pub(crate) struct FunctionInfo<'a, 'b> {
 pub(crate) fn get_function_signature<'a, 'b>(
     node: &Node,
     source_code: &'a str,
-    params: &'b mut Vec<(&'a str, Option<&'a str>)>,
+    params: &'b mut Vec<Param<'a>>,
+    skip_args_and_kwargs: bool,
+    ignored_params: &[String],
 ) -> Option<FunctionInfo<'a, 'b>> {
     if !node.kind().eq("function_definition") {
         return None;
@@ -41,20 +415,38 @@ pub(crate) fn get_function_signature<'a, 'b>(
 
     let function_name = FunctionLocation::Row(node.start_position().row);
 
+    let name = node
+        .child_by_field_name("name")
+        .and_then(|name| name.utf8_text(source_code.as_bytes()).ok())
+        .unwrap_or_default();
+
+    let mut return_type = node
+        .child_by_field_name("return_type")
+        .and_then(|typ| typ.utf8_text(source_code.as_bytes()).ok());
+
+    let decorators = get_decorators(node, source_code);
+
+    let is_method = get_enclosing_class(node).is_some();
+    let mut has_receiver = is_method && !decorators.contains(&"staticmethod");
+
     let params_node = node.child_by_field_name("parameters")?;
     params.clear();
 
     let mut cursor = params_node.walk();
 
-    for child in params_node.children(&mut cursor) {
-        let text = child
-            .utf8_text(source_code.as_bytes())
-            .expect("should be valid utf-8");
+    for child in params_node.named_children(&mut cursor) {
+        if child.kind() == "comment" {
+            continue;
+        }
 
-        if text == "self" {
+        if std::mem::take(&mut has_receiver) {
             continue;
         }
 
+        let text = child
+            .utf8_text(source_code.as_bytes())
+            .expect("should be valid utf-8");
+
         if child.kind() == "typed_parameter" || child.kind() == "typed_default_parameter" {
             let mut identifier = None;
             let mut typ = None;
@@ -66,7 +458,10 @@ pub(crate) fn get_function_signature<'a, 'b>(
                     .utf8_text(source_code.as_bytes())
                     .expect("should be valid utf-8");
 
-                if inner_child.kind() == "identifier" {
+                if matches!(
+                    inner_child.kind(),
+                    "identifier" | "list_splat_pattern" | "dictionary_splat_pattern"
+                ) {
                     identifier = Some(text_of_inner_child);
                 } else if inner_child.kind() == "type" {
                     typ = Some(text_of_inner_child);
@@ -74,19 +469,56 @@ pub(crate) fn get_function_signature<'a, 'b>(
             }
 
             if let (Some(identifier), Some(typ)) = (identifier, typ) {
-                params.push((identifier, Some(typ)));
+                let has_default = child.kind() == "typed_default_parameter";
+
+                params.push(Param::new(identifier, Some(typ), has_default));
             }
-        } else if child.kind() == "identifier" {
-            params.push((text, None));
+        } else if matches!(
+            child.kind(),
+            "identifier" | "list_splat_pattern" | "dictionary_splat_pattern"
+        ) {
+            params.push(Param::new(text, None, false));
         } else if child.kind() == "default_parameter" {
             let (name, _) = text
                 .split_once('=')
                 .expect("parameter with default value should have '=' in the text");
 
-            params.push((name, None));
+            params.push(Param::new(name.trim(), None, true));
         }
     }
 
+    let colon = node.children(&mut cursor).find(|child| child.kind() == ":");
+    let signature_end = colon.map(|colon| colon.end_position().row);
+
+    let type_comment = node
+        .children(&mut cursor)
+        .filter(|child| child.kind() == "comment")
+        .find(|comment| Some(comment.start_position().row) == signature_end)
+        .and_then(|comment| comment.utf8_text(source_code.as_bytes()).ok());
+
+    let type_comment_mismatch =
+        type_comment.and_then(|comment| apply_type_comment(comment, params, &mut return_type));
+
+    // Comments on the `def` line may be placed inside the parentheses of the params when
+    // the signature spans multiple lines.
+    let signature_rows = node.start_position().row..=signature_end.unwrap_or_default();
+    let suppression = node
+        .children(&mut cursor)
+        .chain(params_node.children(&mut params_node.walk()))
+        .filter(|child| child.kind() == "comment")
+        .filter(|comment| signature_rows.contains(&comment.start_position().row))
+        .filter_map(|comment| comment.utf8_text(source_code.as_bytes()).ok())
+        .filter_map(parse_suppression)
+        .chain(suppression_above(source_code, node.start_byte()))
+        .reduce(Suppression::merge);
+
+    let has_var_kwargs = params.iter().any(|param| param.name.starts_with("**"));
+
+    params.retain(|param| {
+        !(ignored_params.iter().any(|ignored| ignored == param.name)
+            || (skip_args_and_kwargs && param.name.starts_with('*')))
+    });
+
     let block = node.children(&mut cursor).find(|c| c.kind() == "block")?;
 
     let content = block.utf8_text(source_code.as_bytes()).ok()?;
@@ -96,5 +528,223 @@ pub(crate) fn get_function_signature<'a, 'b>(
         params,
         docstring,
         function_name,
+        row: Some(node.start_position().row),
+        end_row: Some(node.end_position().row),
+        column: Some(node.start_position().column),
+        docstring_start: docstring.map(|_| {
+            let start = block.start_position();
+            (start.row, start.column)
+        }),
+        spans: Spans {
+            signature: colon.map(|colon| node.start_byte()..colon.end_byte()),
+            parameters: Some(params_node.byte_range()),
+            docstring: docstring
+                .map(|docstring| block.start_byte()..block.start_byte() + docstring.len()),
+        },
+        name,
+        return_type,
+        raises: None,
+        is_generator: None,
+        class_docstring: None,
+        decorators,
+        type_comment_mismatch,
+        has_var_kwargs,
+        is_stub: is_stub(&block),
+        is_method,
+        suppression,
     })
 }
+
+/// Whether a block is just `...` or `pass`, possibly after a docstring.
+fn is_stub(block: &Node) -> bool {
+    let mut cursor = block.walk();
+
+    let mut statements = block
+        .named_children(&mut cursor)
+        .filter(|statement| statement.kind() != "comment")
+        .peekable();
+
+    let is_expression_of_kind = |statement: &Node, kind: &str| {
+        statement.kind() == "expression_statement"
+            && statement
+                .named_child(0)
+                .is_some_and(|expression| expression.kind() == kind)
+    };
+
+    if statements
+        .peek()
+        .is_some_and(|statement| is_expression_of_kind(statement, "string"))
+    {
+        statements.next();
+    }
+
+    let Some(statement) = statements.next() else {
+        return false;
+    };
+
+    statements.next().is_none()
+        && (statement.kind() == "pass_statement" || is_expression_of_kind(&statement, "ellipsis"))
+}
+
+/// Extracts the dotted names of the decorators of a definition, e.g. `cli.command` for
+/// `@cli.command("x")`.
+pub(crate) fn get_decorators<'a>(node: &Node, source_code: &'a str) -> Vec<&'a str> {
+    let Some(parent) = node
+        .parent()
+        .filter(|parent| parent.kind() == "decorated_definition")
+    else {
+        return Vec::new();
+    };
+
+    let mut cursor = parent.walk();
+
+    parent
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "decorator")
+        .filter_map(|decorator| {
+            let mut expression = decorator.named_child(0)?;
+
+            if expression.kind() == "call" {
+                expression = expression.child_by_field_name("function")?;
+            }
+
+            expression.utf8_text(source_code.as_bytes()).ok()
+        })
+        .collect()
+}
+
+/// Counts the lines spanned by the body of a function definition.
+pub(crate) fn body_line_count(node: &Node) -> Option<usize> {
+    let body = node.child_by_field_name("body")?;
+
+    Some(body.end_position().row - body.start_position().row + 1)
+}
+
+/// Determines if a module node starts with a docstring, skipping comments (including a
+/// shebang) and `from __future__` imports. Returns `None` if there is nothing else in the
+/// module.
+pub(crate) fn has_module_docstring(module: &Node) -> Option<bool> {
+    let mut cursor = module.walk();
+
+    let first_statement = module
+        .named_children(&mut cursor)
+        .find(|statement| !matches!(statement.kind(), "comment" | "future_import_statement"))?;
+
+    Some(
+        first_statement.kind() == "expression_statement"
+            && first_statement.named_child(0).is_some_and(|expression| {
+                matches!(expression.kind(), "string" | "concatenated_string")
+            }),
+    )
+}
+
+/// Finds the class in which a definition is directly defined, if any.
+fn get_enclosing_class<'t>(node: &Node<'t>) -> Option<Node<'t>> {
+    let mut parent = node.parent()?;
+
+    if parent.kind() == "decorated_definition" {
+        parent = parent.parent()?;
+    }
+
+    parent
+        .parent()
+        .filter(|class| parent.kind() == "block" && class.kind() == "class_definition")
+}
+
+/// Extracts the docstring of the class in which a function is directly defined, if any.
+pub(crate) fn get_enclosing_class_docstring<'a>(
+    node: &Node,
+    source_code: &'a str,
+) -> Option<&'a str> {
+    let class = get_enclosing_class(node)?;
+
+    let block = class.child_by_field_name("body")?;
+
+    extract_docstring(block.utf8_text(source_code.as_bytes()).ok()?)
+}
+
+/// Visits the named nodes in the body of a function definition, in the order they
+/// appear, without entering nested functions, classes and lambdas.
+fn visit_own_body<'t>(node: &Node<'t>, mut visit: impl FnMut(Node<'t>)) {
+    let Some(body) = node.child_by_field_name("body") else {
+        return;
+    };
+
+    let mut stack = vec![body];
+
+    while let Some(node) = stack.pop() {
+        if matches!(
+            node.kind(),
+            "function_definition" | "class_definition" | "lambda"
+        ) {
+            continue;
+        }
+
+        visit(node);
+
+        let mut cursor = node.walk();
+
+        // Reversed so that the nodes are visited in the order they appear.
+        let children: Vec<Node> = node.named_children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+}
+
+/// Collects the names of the exceptions raised in the body of a function definition,
+/// without duplicates.
+///
+/// Raises in nested functions and classes are not considered, and neither are re-raises
+/// (i.e., a bare `raise`) and exceptions raised through variables, which are told apart
+/// from exception classes by not starting with an uppercase letter.
+pub(crate) fn get_raised_exceptions<'a>(node: &Node, source_code: &'a str) -> Vec<&'a str> {
+    let mut raises = Vec::new();
+
+    visit_own_body(node, |node| {
+        if node.kind() != "raise_statement" {
+            return;
+        }
+
+        let exception = node.named_child(0).map(|exception| {
+            if exception.kind() == "call" {
+                exception
+                    .child_by_field_name("function")
+                    .unwrap_or(exception)
+            } else {
+                exception
+            }
+        });
+
+        let name = exception
+            .filter(|exception| matches!(exception.kind(), "identifier" | "attribute"))
+            .and_then(|exception| exception.utf8_text(source_code.as_bytes()).ok());
+
+        let is_class = name.is_some_and(|name| {
+            name.rsplit('.')
+                .next()
+                .and_then(|last| last.chars().next())
+                .is_some_and(char::is_uppercase)
+        });
+
+        if let (Some(name), true) = (name, is_class) {
+            if !raises.contains(&name) {
+                raises.push(name);
+            }
+        }
+    });
+
+    raises
+}
+
+/// Determines if a function definition is a generator, i.e. if its body has a `yield`
+/// or `yield from` expression outside of nested functions.
+pub(crate) fn is_generator(node: &Node) -> bool {
+    let mut is_generator = false;
+
+    visit_own_body(node, |node| {
+        if node.kind() == "yield" {
+            is_generator = true;
+        }
+    });
+
+    is_generator
+}
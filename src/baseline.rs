@@ -0,0 +1,369 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::diagnostics::Diagnostic;
+use crate::rules_checking::SourceReport;
+
+/// Error of a baseline file that could not be read or written.
+#[derive(Debug, Error)]
+pub enum BaselineError {
+    #[error("could not read {}", path.display())]
+    Read {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not write {}", path.display())]
+    Write {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("could not parse {}", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// Known violations of a rule in a function, which are not reported.
+///
+/// Entries are keyed by the file, the function and the rule instead of rows, so that they
+/// keep matching after unrelated edits move the function around.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// Path of the file, as reported, with `/` as the separator.
+    pub path: String,
+    /// Name of the function (or class) in which the violations are.
+    pub function: String,
+    /// Code of the rule, e.g. `PSD201`.
+    pub rule: String,
+    /// Number of violations of the rule in the function.
+    pub count: usize,
+}
+
+/// Known violations, usually those found when a codebase adopted pystaleds, so that only
+/// new ones fail the check.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "BaselineFile", into = "BaselineFile")]
+pub struct Baseline {
+    /// Entries by path, so that each file only looks up its own.
+    by_path: BTreeMap<String, Vec<BaselineEntry>>,
+}
+
+/// Layout of the baseline file.
+#[derive(Serialize, Deserialize)]
+struct BaselineFile {
+    entries: Vec<BaselineEntry>,
+}
+
+impl From<BaselineFile> for Baseline {
+    fn from(file: BaselineFile) -> Self {
+        Baseline::new(file.entries)
+    }
+}
+
+impl From<Baseline> for BaselineFile {
+    fn from(baseline: Baseline) -> Self {
+        BaselineFile {
+            entries: baseline.entries().cloned().collect(),
+        }
+    }
+}
+
+/// Violations left out of a report by a baseline.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Filtered {
+    /// Number of violations that were in the baseline.
+    pub known: usize,
+    /// Entries of the baseline for the file of the report that matched fewer violations
+    /// than they count, with the count of the unmatched ones.
+    pub stale: Vec<BaselineEntry>,
+}
+
+impl Baseline {
+    /// Creates a baseline from its entries, merging the ones with the same key.
+    pub fn new(entries: impl IntoIterator<Item = BaselineEntry>) -> Self {
+        let mut by_path: BTreeMap<String, Vec<BaselineEntry>> = BTreeMap::new();
+
+        for entry in entries {
+            let entries = by_path.entry(entry.path.clone()).or_default();
+
+            match entries
+                .iter_mut()
+                .find(|known| known.function == entry.function && known.rule == entry.rule)
+            {
+                Some(known) => known.count += entry.count,
+                None => entries.push(entry),
+            }
+        }
+
+        by_path.values_mut().for_each(|entries| entries.sort());
+
+        Baseline { by_path }
+    }
+
+    /// Records every violation of the reports.
+    pub fn from_reports(reports: &[SourceReport]) -> Self {
+        Baseline::new(reports.iter().flat_map(|report| {
+            report.diagnostics.iter().map(|diagnostic| BaselineEntry {
+                path: key_path(report, diagnostic),
+                function: function_name(report, diagnostic).to_string(),
+                rule: diagnostic.rule.code().to_string(),
+                count: 1,
+            })
+        }))
+    }
+
+    /// Reads a baseline written with [`Baseline::write`].
+    pub fn load(path: &Path) -> Result<Self, BaselineError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| BaselineError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        serde_json::from_str(&contents).map_err(|source| BaselineError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Writes the baseline as JSON, with its entries sorted so that it diffs well.
+    pub fn write(&self, path: &Path) -> Result<(), BaselineError> {
+        let contents = serde_json::to_string_pretty(self).expect("baseline should be serializable");
+
+        std::fs::write(path, contents + "\n").map_err(|source| BaselineError::Write {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Entries of the baseline, sorted by path, function and rule.
+    pub fn entries(&self) -> impl Iterator<Item = &BaselineEntry> {
+        self.by_path.values().flatten()
+    }
+
+    /// Number of violations in the baseline.
+    pub fn len(&self) -> usize {
+        self.entries().map(|entry| entry.count).sum()
+    }
+
+    /// Whether there are no violations in the baseline.
+    pub fn is_empty(&self) -> bool {
+        self.by_path.is_empty()
+    }
+
+    /// Removes the known violations from a report, returning how many were removed and
+    /// which entries of its file are stale.
+    pub fn filter(&self, report: &mut SourceReport) -> Filtered {
+        let Some(path) = report.path.as_deref().map(normalize) else {
+            return Filtered::default();
+        };
+
+        let Some(entries) = self.by_path.get(&path) else {
+            return Filtered::default();
+        };
+
+        let mut remaining: Vec<_> = entries.iter().map(|entry| entry.count).collect();
+        let diagnostics = std::mem::take(&mut report.diagnostics);
+        let mut known = 0;
+
+        for diagnostic in diagnostics {
+            let function = function_name(report, &diagnostic);
+            let index = entries.iter().position(|entry| {
+                entry.function == function && entry.rule == diagnostic.rule.code()
+            });
+
+            match index.filter(|&index| remaining[index] > 0) {
+                Some(index) => {
+                    remaining[index] -= 1;
+                    known += 1;
+                }
+                None => report.diagnostics.push(diagnostic),
+            }
+        }
+
+        let stale = entries
+            .iter()
+            .zip(remaining)
+            .filter(|(_, remaining)| *remaining > 0)
+            .map(|(entry, remaining)| BaselineEntry {
+                count: remaining,
+                ..entry.clone()
+            })
+            .collect();
+
+        Filtered { known, stale }
+    }
+
+    /// Entries of the files other than the given ones, e.g. those of files deleted or
+    /// renamed since the baseline was written, which no report could match.
+    pub fn unvisited<P: AsRef<Path>>(&self, paths: &[P]) -> Vec<&BaselineEntry> {
+        let visited: BTreeSet<_> = paths.iter().map(|path| normalize(path.as_ref())).collect();
+
+        self.by_path
+            .iter()
+            .filter(|(path, _)| !visited.contains(*path))
+            .flat_map(|(_, entries)| entries)
+            .collect()
+    }
+}
+
+/// Path of the file of a diagnostic, as it is keyed in the baseline.
+fn key_path(report: &SourceReport, diagnostic: &Diagnostic) -> String {
+    diagnostic
+        .path
+        .as_deref()
+        .or(report.path.as_deref())
+        .map(normalize)
+        .unwrap_or_default()
+}
+
+/// Uses `/` as the separator and drops a leading `./`, so that baselines can be shared
+/// across platforms and ways of passing the same path.
+fn normalize(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    match path.strip_prefix("./") {
+        Some(path) => path.to_string(),
+        None => path,
+    }
+}
+
+/// Name of the function of a diagnostic. Diagnostics of the tree-sitter parser refer to
+/// their function by row, so the name is taken from the checked functions.
+fn function_name<'a>(report: &'a SourceReport, diagnostic: &'a Diagnostic) -> &'a str {
+    report
+        .functions
+        .iter()
+        .find(|function| function.matches(diagnostic))
+        .map_or(&diagnostic.function, |function| &function.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules_checking::{check_source_report, CheckOptions, CompliancyChecker};
+
+    use super::*;
+
+    fn report(source_code: &str, checker: CompliancyChecker) -> SourceReport {
+        check_source_report(
+            source_code,
+            Some(Path::new("./src/module.py")),
+            checker,
+            &CheckOptions::default(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn filter() {
+        let source_code = r#"def f(x, y):
+    """Hey.
+
+    Args:
+        z: Something.
+    """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let baseline = Baseline::from_reports(&[report(source_code, checker)]);
+
+            assert_eq!(
+                baseline.entries().cloned().collect::<Vec<_>>(),
+                [
+                    BaselineEntry {
+                        path: "src/module.py".to_string(),
+                        function: "f".to_string(),
+                        rule: "PSD201".to_string(),
+                        count: 2,
+                    },
+                    BaselineEntry {
+                        path: "src/module.py".to_string(),
+                        function: "f".to_string(),
+                        rule: "PSD202".to_string(),
+                        count: 1,
+                    },
+                ]
+            );
+
+            // Unrelated edits move the function, while the new violation is reported.
+            let mut moved = report(
+                &format!("import os\n\n{}", source_code.replace("y)", "y, w)")),
+                checker,
+            );
+            let filtered = baseline.filter(&mut moved);
+
+            assert_eq!(filtered.known, 3);
+            assert!(filtered.stale.is_empty());
+            assert_eq!(moved.diagnostics.len(), 1);
+            assert!(moved.diagnostics[0].message.contains("`w`"));
+
+            // Fixed violations leave their entries stale.
+            let mut fixed = report(&source_code.replace("z:", "x:"), checker);
+            let filtered = baseline.filter(&mut fixed);
+
+            assert_eq!(filtered.known, 1);
+            assert!(fixed.diagnostics.is_empty());
+            assert_eq!(
+                filtered.stale,
+                [
+                    BaselineEntry {
+                        path: "src/module.py".to_string(),
+                        function: "f".to_string(),
+                        rule: "PSD201".to_string(),
+                        count: 1,
+                    },
+                    BaselineEntry {
+                        path: "src/module.py".to_string(),
+                        function: "f".to_string(),
+                        rule: "PSD202".to_string(),
+                        count: 1,
+                    },
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn unvisited() {
+        let entry = |path: &str| BaselineEntry {
+            path: path.to_string(),
+            function: "f".to_string(),
+            rule: "PSD201".to_string(),
+            count: 1,
+        };
+        let baseline = Baseline::new([entry("src/module.py"), entry("src/deleted.py")]);
+
+        assert_eq!(
+            baseline.unvisited(&[Path::new("./src/module.py")]),
+            [&entry("src/deleted.py")]
+        );
+        assert!(baseline
+            .unvisited(&["src/module.py", "src/deleted.py"])
+            .is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        let baseline = Baseline::new([BaselineEntry {
+            path: "src/module.py".to_string(),
+            function: "f".to_string(),
+            rule: "PSD201".to_string(),
+            count: 2,
+        }]);
+
+        let json = serde_json::to_string(&baseline).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"entries":[{"path":"src/module.py","function":"f","rule":"PSD201","count":2}]}"#
+        );
+        assert_eq!(serde_json::from_str::<Baseline>(&json).unwrap(), baseline);
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Token that allows a running check to be cancelled, possibly from another thread.
+///
+/// Clones share the same state, so cancelling any of them cancels all of them.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::cancellation::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let clone = token.clone();
+///
+/// assert!(!token.is_cancelled());
+///
+/// clone.cancel();
+///
+/// assert!(token.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests the cancellation of the checks using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the cancellation was requested, through this token or any of its clones.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
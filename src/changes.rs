@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::rules_checking::{CheckedFunction, SourceReport};
+
+/// Rows changed in each file, such as since a git ref, so that the violations of the
+/// functions that were not touched can be told apart from the ones of the functions that
+/// were.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangedLines {
+    /// Ranges of the changed rows (0-based) of each file, by its path joined to the root
+    /// of the diff.
+    by_path: HashMap<PathBuf, Vec<RangeInclusive<usize>>>,
+}
+
+impl ChangedLines {
+    /// Parses the changed rows of each file from a unified diff without context lines,
+    /// such as the output of `git diff -U0`, whose paths (after their `b/` prefix) are
+    /// relative to the root. Only the rows of the new version of the files are kept, so
+    /// that a hunk that only removes lines changes the row before them instead, which is
+    /// in the function they were removed from. Deleted files are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::path::Path;
+    ///
+    /// use pystaleds::changes::ChangedLines;
+    ///
+    /// let diff = "\
+    /// diff --git a/src/module.py b/src/module.py
+    /// --- a/src/module.py
+    /// +++ b/src/module.py
+    /// @@ -3 +3,2 @@ def f(x):
+    /// -    return x
+    /// +    y = x
+    /// +    return y
+    /// @@ -10,2 +10,0 @@ def g(x):
+    /// -    z = x
+    /// -    return z
+    /// ";
+    ///
+    /// let changes = ChangedLines::parse(diff, Path::new("/repo"));
+    ///
+    /// assert_eq!(
+    ///     changes.rows(Path::new("/repo/src/module.py")),
+    ///     [2..=3, 9..=9]
+    /// );
+    /// assert!(changes.rows(Path::new("/repo/src/other.py")).is_empty());
+    /// ```
+    pub fn parse(diff: &str, root: &Path) -> Self {
+        let mut by_path: HashMap<PathBuf, Vec<RangeInclusive<usize>>> = HashMap::new();
+        let mut path = None;
+        // Lines of the current hunk, which are skipped since their contents may look like
+        // headers, e.g. a removed line starting with `-- `.
+        let mut hunk_lines = 0;
+
+        for line in diff.lines() {
+            if hunk_lines > 0 {
+                hunk_lines -= 1;
+                continue;
+            }
+
+            if let Some(new_path) = line.strip_prefix("+++ ") {
+                path = new_path
+                    .strip_prefix("b/")
+                    .map(|new_path| root.join(new_path));
+                continue;
+            }
+
+            let Some(hunk) = line.strip_prefix("@@ ") else {
+                continue;
+            };
+
+            let mut ranges = hunk.split(' ');
+            let (Some(old), Some(new)) = (
+                ranges.next().and_then(|range| range.strip_prefix('-')),
+                ranges.next().and_then(|range| range.strip_prefix('+')),
+            ) else {
+                continue;
+            };
+
+            let (Some((_, old_count)), Some((start, new_count))) =
+                (parse_range(old), parse_range(new))
+            else {
+                continue;
+            };
+
+            hunk_lines = old_count + new_count;
+
+            let Some(path) = &path else {
+                continue;
+            };
+
+            let rows = if new_count == 0 {
+                let row = start.saturating_sub(1);
+                row..=row
+            } else {
+                start - 1..=start + new_count - 2
+            };
+
+            by_path.entry(path.clone()).or_default().push(rows);
+        }
+
+        ChangedLines { by_path }
+    }
+
+    /// Marks every row of a file as changed, as for a file that is new.
+    pub fn add_file(&mut self, path: PathBuf) {
+        self.by_path.insert(path, vec![0..=usize::MAX]);
+    }
+
+    /// Ranges of the rows (0-based) of the file at the path that changed, in the order
+    /// they were found.
+    pub fn rows(&self, path: &Path) -> &[RangeInclusive<usize>] {
+        self.by_path.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Narrows the diagnostics of a report down to the ones of the functions touched by
+    /// the changes of its file, found at the path, returning how many were not.
+    ///
+    /// The diagnostics of the functions that were not touched are removed, or kept as
+    /// warnings if `keep_untouched` is set, so that they do not fail the check. When the
+    /// end of a function is unknown, as with the lexer, it is taken to end right before
+    /// the next checked function, so that no touched function is missed. Diagnostics of
+    /// no checked function are touched if their own row changed.
+    pub fn filter(&self, report: &mut SourceReport, path: &Path, keep_untouched: bool) -> usize {
+        let changed = self.rows(path);
+        let mut starts: Vec<usize> = report.functions.iter().filter_map(|f| f.row).collect();
+        starts.sort_unstable();
+
+        let is_touched = |diagnostic: &Diagnostic| {
+            let rows = match report.functions.iter().find(|f| f.matches(diagnostic)) {
+                Some(function) => function_rows(function, &starts),
+                None => diagnostic.row.map(|row| row..=row),
+            };
+
+            rows.is_some_and(|rows| {
+                changed
+                    .iter()
+                    .any(|changed| changed.start() <= rows.end() && rows.start() <= changed.end())
+            })
+        };
+
+        let (touched, untouched): (Vec<_>, Vec<_>) = std::mem::take(&mut report.diagnostics)
+            .into_iter()
+            .partition(is_touched);
+        let count = untouched.iter().filter(|d| d.is_error()).count();
+
+        report.diagnostics = touched;
+
+        if keep_untouched {
+            report
+                .diagnostics
+                .extend(untouched.into_iter().map(|diagnostic| Diagnostic {
+                    severity: Severity::Warning,
+                    ..diagnostic
+                }));
+        }
+
+        count
+    }
+}
+
+/// Parses the start and the number of lines of a range of a hunk header, such as `3,2`,
+/// or `3` for a single line.
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Rows of a function, up to the row before the next function (among the starts of the
+/// checked functions) if its end is unknown.
+fn function_rows(function: &CheckedFunction, starts: &[usize]) -> Option<RangeInclusive<usize>> {
+    let start = function.row?;
+    let end = function.end_row.unwrap_or_else(|| {
+        starts
+            .iter()
+            .find(|&&next| next > start)
+            .map_or(usize::MAX, |next| next - 1)
+    });
+
+    Some(start..=end)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules_checking::{check_source_report, CheckOptions, CompliancyChecker};
+
+    use super::*;
+
+    const SOURCE: &str = r#"def f(x):
+    """Does.
+
+    Args:
+        y: Thing.
+    """
+    return x
+
+
+def g(x):
+    """Does.
+
+    Args:
+        z: Thing.
+    """
+    return x
+"#;
+
+    #[test]
+    fn parsing() {
+        let diff = "\
+diff --git a/a.py b/a.py
+--- a/a.py
++++ b/a.py
+@@ -1,0 +2,3 @@ import os
++import sys
+++++ not a header
++import re
+@@ -0,0 +1 @@
++#!/usr/bin/env python
+diff --git a/gone.py b/gone.py
+deleted file mode 100644
+--- a/gone.py
++++ /dev/null
+@@ -1 +0,0 @@
+-import os
+diff --git a/b.py b/b.py
+--- a/b.py
++++ b/b.py
+@@ -4,2 +3,0 @@ def f():
+-    x = 1
+-    y = 2
+";
+
+        let changes = ChangedLines::parse(diff, Path::new("repo"));
+
+        assert_eq!(changes.rows(Path::new("repo/a.py")), [1..=3, 0..=0]);
+        assert_eq!(changes.rows(Path::new("repo/b.py")), [2..=2]);
+        assert!(changes.rows(Path::new("repo/gone.py")).is_empty());
+        assert!(changes.rows(Path::new("repo/not a header")).is_empty());
+    }
+
+    #[test]
+    fn filtering() {
+        let path = Path::new("module.py");
+        let mut changes = ChangedLines::default();
+        changes.by_path.insert(path.to_path_buf(), vec![6..=6]);
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let report =
+                check_source_report(SOURCE, Some(path), checker, &CheckOptions::default(), None)
+                    .unwrap();
+            assert_eq!(report.diagnostics.len(), 4);
+
+            let mut filtered = report.clone();
+            assert_eq!(changes.filter(&mut filtered, path, false), 2);
+            assert_eq!(filtered.diagnostics.len(), 2);
+            assert!(filtered
+                .diagnostics
+                .iter()
+                .all(|d| d.function_row == Some(0)));
+
+            let mut kept = report.clone();
+            assert_eq!(changes.filter(&mut kept, path, true), 2);
+            assert_eq!(kept.diagnostics.len(), 4);
+            assert_eq!(kept.diagnostics.iter().filter(|d| d.is_error()).count(), 2);
+
+            // Nothing is touched in files without changes.
+            let mut untouched = report.clone();
+            assert_eq!(
+                changes.filter(&mut untouched, Path::new("other.py"), false),
+                4
+            );
+            assert!(untouched.diagnostics.is_empty());
+        }
+    }
+
+    #[test]
+    fn function_ends() {
+        let report = check_source_report(
+            SOURCE,
+            None,
+            CompliancyChecker::TreeSitter,
+            &CheckOptions::default(),
+            None,
+        )
+        .unwrap();
+
+        let rows: Vec<_> = report
+            .functions
+            .iter()
+            .map(|function| (function.row, function.end_row))
+            .collect();
+
+        assert_eq!(rows, [(Some(0), Some(6)), (Some(9), Some(15))]);
+    }
+}
@@ -0,0 +1,18 @@
+pub(crate) mod args;
+pub(crate) mod check;
+pub(crate) mod convert;
+pub(crate) mod files;
+pub(crate) mod fix;
+pub(crate) mod pipeline;
+pub(crate) mod progress;
+pub(crate) mod rules;
+pub(crate) mod watch;
+
+/// Outcome of a completed run.
+pub(crate) enum Outcome {
+    /// No errors were found.
+    Clean,
+    /// Errors were found in the checked files, described by the message unless nothing is
+    /// to be printed.
+    Errors(Option<String>),
+}
@@ -0,0 +1,769 @@
+use std::path::PathBuf;
+
+use clap::builder::BoolishValueParser;
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, Args};
+use pystaleds::config::Config;
+use pystaleds::diagnostics::{RuleSelection, RuleSelector};
+use pystaleds::human::ColorChoice;
+use pystaleds::output::OutputFormat;
+use pystaleds::rules_checking::{
+    CheckOptions, CompliancyChecker, DocstringStyle, FunctionSelection, DEFAULT_GENERATED_MARKERS,
+    DEFAULT_MENTION_PATTERN, DEFAULT_PLACEHOLDERS, DEFAULT_SECTION_ORDER,
+};
+use pystaleds::stats::{StatisticsGrouping, StatsGrouping};
+use regex::Regex;
+
+use crate::cli::files::DEFAULT_MAX_FILE_SIZE;
+
+// The structs are described with plain comments, since clap would take their doc comments
+// as the about of the commands they are flattened into.
+
+// Options that choose the files to check.
+#[derive(Args)]
+pub(crate) struct FileArgs {
+    #[arg(required_unless_present = "files_from")]
+    /// File or folder to check.
+    pub(crate) path: Option<String>,
+
+    #[arg(long, value_name = "PATH", conflicts_with = "path")]
+    /// Will check the files listed in the given file, one per line, or in the standard
+    /// input with `-`, as in `git diff --name-only | pystaleds --files-from -`. Listed
+    /// files that do not exist are warned about and left out, and so are the ones
+    /// excluded by `--glob`, hidden or in a folder excluded by default, as when walking
+    /// through the current folder.
+    pub(crate) files_from: Option<PathBuf>,
+
+    #[arg(long, default_value_t = false, alias = "ah")]
+    /// Will allow hidden files, both when walking through the path and in the matches of
+    /// `--glob`.
+    pub(crate) allow_hidden: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Will walk through the folders that are left out by default, such as `venv`,
+    /// `build` and `__pycache__`, both when walking through the path and in the matches
+    /// of `--glob`.
+    pub(crate) no_default_excludes: bool,
+
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_FILE_SIZE)]
+    /// Will skip the files larger than this, both when walking through the path and in
+    /// the matches of `--glob`, counting them in the summary. Defaults to 1 MiB, and 0
+    /// checks files of any size.
+    pub(crate) max_file_size: u64,
+
+    #[arg(short, long, value_delimiter = ',')]
+    /// Runs over glob matches considering root to be the path specified in the command.
+    /// Matches inside hidden files or folders are left out unless `--allow-hidden`. Can
+    /// be repeated or given a comma-separated list, checking each file once even if it
+    /// matches several globs. Globs starting with `!` exclude the files matched by the
+    /// globs before them. With `--files-from`, filters the listed files instead.
+    pub(crate) glob: Vec<String>,
+
+    #[arg(long, value_name = "PATTERN", value_parser = glob::Pattern::new)]
+    /// Will only check the files matching some of the patterns, which are relative to
+    /// the path (e.g. `src/**`). Can be repeated. Also filters the matches of `--glob`.
+    pub(crate) include: Vec<glob::Pattern>,
+}
+
+// Options to check the source code read from the standard input.
+#[derive(Args, Default)]
+pub(crate) struct StdinArgs {
+    #[arg(long, conflicts_with_all = [
+            "path", "glob", "files_from", "fix", "diff", "fix_output", "diff_against"
+        ])]
+    /// Will check the source code read from the standard input instead of files, as
+    /// editors do with unsaved buffers. See `--stdin-filename`.
+    pub(crate) stdin: bool,
+
+    #[arg(long, value_name = "PATH")]
+    /// Path of the source code read with `--stdin`, with which it is reported and with
+    /// which its configuration (including `per_file` patterns) is resolved. It does not
+    /// need to exist.
+    pub(crate) stdin_filename: Option<PathBuf>,
+}
+
+// Options that configure the rules, shared by every command that checks or lists them.
+#[derive(Args)]
+pub(crate) struct RuleArgs {
+    #[arg(long, default_value_t = false, alias = "be")]
+    /// Will consider that an "Args" section breaks on an empty line.
+    pub(crate) break_on_empty_line: bool,
+
+    #[arg(long, default_value_t = false, alias = "nd")]
+    /// Will consider an error for a docstring to be absent.
+    pub(crate) forbid_no_docstring: bool,
+
+    #[arg(long, default_value_t = false, alias = "na")]
+    /// Will consider an error for an "Args" or "Parameters" section to be absent.
+    pub(crate) forbid_no_args_in_docstring: bool,
+
+    #[arg(long, default_value_t = false, alias = "nu")]
+    /// Will consider an error for an arg in docstring to be untyped. Otherwise, only
+    /// raises an error if the docstring's type and the signature's type are mismatched.
+    pub(crate) forbid_untyped_docstrings: bool,
+
+    #[arg(long, default_value_t = false, alias = "ak")]
+    /// Will consider *args and **kwargs when checking the docstrings. If this flag is
+    /// not set, they are just completely ignored.
+    pub(crate) include_args_and_kwargs: bool,
+
+    #[arg(short, long, default_value_t, value_enum)]
+    /// Which parsing to use. Defaults to simple lexer, which is faster. Select
+    /// `tree-sitter` in case you might be getting false positives/negatives.
+    pub(crate) parser: CompliancyChecker,
+
+    #[arg(short, long, default_value_t, value_enum)]
+    /// Determines the docstring style to consider for parsing.
+    pub(crate) docstyle: DocstringStyle,
+
+    #[arg(long, default_value_t = false, alias = "sm")]
+    /// Will consider an error for a docstring to be written in a style other than the
+    /// one selected with `--docstyle`. Has no effect when auto-detecting.
+    pub(crate) forbid_style_mismatch: bool,
+
+    #[arg(long, default_value_t = false, alias = "ce")]
+    /// Will consider an error for the doctest statements (i.e., lines starting with
+    /// `>>>`) in an "Examples" section to not be valid Python. Statements marked with
+    /// `# doctest: +SKIP` are ignored.
+    pub(crate) check_examples_style: bool,
+
+    #[arg(long, default_value_t = false, alias = "nr")]
+    /// Will consider an error for a function with a return annotation other than
+    /// `None` to have no "Returns" section in its docstring. `__init__` is exempt.
+    pub(crate) forbid_missing_returns: bool,
+
+    #[arg(long, default_value_t = false, alias = "rt")]
+    /// Will consider an error for the type documented in the "Returns" section to
+    /// differ from the return annotation of the function.
+    pub(crate) check_return_type: bool,
+
+    #[arg(long, default_value_t = false, alias = "sa")]
+    /// Will consider an error for a name referenced in a "See Also" section to not be
+    /// defined in any of the checked files. Dotted names from packages that are not
+    /// checked (e.g., the standard library) are skipped.
+    pub(crate) check_see_also: bool,
+
+    #[arg(long, default_value_t = false, alias = "cr")]
+    /// Will consider an error for an exception raised in a function to not be in the
+    /// "Raises" section of its docstring, and vice versa. Requires the tree-sitter parser.
+    pub(crate) check_raises: bool,
+
+    #[arg(long, default_value_t = false, alias = "ny")]
+    /// Will consider an error for a generator to have no "Yields" section in its
+    /// docstring, or for a function that is not a generator to have one. Requires the
+    /// tree-sitter parser.
+    pub(crate) forbid_missing_yields: bool,
+
+    #[arg(long, default_value_t = false, alias = "yt")]
+    /// Will consider an error for the type documented in the "Yields" section to differ
+    /// from the one in the return annotation of a generator (e.g., `Foo` in
+    /// `Iterator[Foo]` or `Generator[Foo, None, None]`).
+    pub(crate) check_yield_type: bool,
+
+    #[arg(long, default_value_t = false, alias = "ca")]
+    /// Will consider an error for the "Attributes" section of a class docstring to not
+    /// match the annotated attributes of the class, in the same way as the args of a
+    /// function. Private attributes are ignored. Requires the tree-sitter parser.
+    pub(crate) check_attributes: bool,
+
+    #[arg(long, default_value_t = false, alias = "ic")]
+    /// Will validate the args of an `__init__` whose docstring has no args section (or
+    /// that has no docstring at all) against the docstring of its class. Requires the
+    /// tree-sitter parser.
+    pub(crate) init_args_in_class_docstring: bool,
+
+    #[arg(long, default_value_t = false, alias = "nm")]
+    /// Will consider an error for a file to not start with a docstring. Comments and
+    /// `from __future__` imports before it are allowed.
+    pub(crate) forbid_no_module_docstring: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Will also require a module docstring in empty files.
+    pub(crate) forbid_empty_modules: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Will also require a module docstring in `__init__.py` files whose only statements
+    /// are imports and assignments (such as `__all__`).
+    pub(crate) include_trivial_init: bool,
+
+    #[arg(long, default_value_t = false, alias = "fp")]
+    /// Will consider an error for the description of a docstring, or the type or
+    /// description of any of its entries, to contain a placeholder (see `--placeholders`).
+    pub(crate) forbid_placeholders: bool,
+
+    #[arg(long, value_delimiter = ',', default_values = DEFAULT_PLACEHOLDERS)]
+    /// Comma-separated tokens considered placeholders by `--forbid-placeholders`.
+    pub(crate) placeholders: Vec<String>,
+
+    #[arg(long, default_value_t = false, alias = "ed")]
+    /// Will consider an error for an arg in the docstring to have an empty description.
+    /// Descriptions starting in a continuation line are not considered empty.
+    pub(crate) forbid_empty_descriptions: bool,
+
+    #[arg(long, default_value_t = false, alias = "dm")]
+    /// Will consider an error for the description in the docstring of a parameter with a
+    /// default value to not mention it (see `--default-mention-pattern`).
+    pub(crate) check_default_mentions: bool,
+
+    #[arg(long, default_value = DEFAULT_MENTION_PATTERN, value_parser = Regex::new)]
+    /// Regex that the description of a parameter with a default value should match for
+    /// `--check-default-mentions`.
+    pub(crate) default_mention_pattern: Regex,
+
+    #[arg(long, default_value_t = false, alias = "oc")]
+    /// Will consider an error for an arg to be documented as optional (e.g., with
+    /// `int, optional`) but have no default value, or vice versa.
+    pub(crate) check_optional_consistency: bool,
+
+    #[arg(long, default_value_t = false, alias = "oo")]
+    /// Will allow the args in the docstring to be in a different order than in the
+    /// signature, as long as each one is documented exactly once.
+    pub(crate) allow_out_of_order_args: bool,
+
+    #[arg(long, default_value_t = false, alias = "so")]
+    /// Will consider an error for the sections of a docstring to not follow the order of
+    /// `--section-order`.
+    pub(crate) check_section_order: bool,
+
+    #[arg(long, value_delimiter = ',', default_values = DEFAULT_SECTION_ORDER)]
+    /// Comma-separated order of the sections for `--check-section-order`, by their google
+    /// names (e.g., "Args" also stands for numpy's "Parameters"). Sections not listed can
+    /// be anywhere.
+    pub(crate) section_order: Vec<String>,
+
+    #[arg(long, default_value_t = false, alias = "ms")]
+    /// Will consider an error for a docstring to be empty or to start with a section
+    /// (such as "Args:") instead of a summary.
+    pub(crate) forbid_missing_summary: bool,
+
+    #[arg(long, default_value_t = false, alias = "ds")]
+    /// Will allow docstrings to document `self` and `cls`, which are ignored when comparing
+    /// the args.
+    pub(crate) allow_documented_self: bool,
+
+    #[arg(long, default_value_t = false, alias = "ft")]
+    /// Will consider an error for a parameter to have no type annotation, or for an arg in
+    /// the docstring to have no type, in addition to the types being compared.
+    pub(crate) require_full_typing: bool,
+
+    #[arg(long, default_value_t = false, alias = "od")]
+    /// Will allow the params with default values to be left out of the docstring. The
+    /// documented ones are still compared, in the order of the signature.
+    pub(crate) allow_omitting_defaulted_args: bool,
+
+    #[arg(long, default_value_t = false, alias = "kd")]
+    /// Will allow the docstring to document args that are not in the signature if it has
+    /// a `**` param, such as `**kwargs`, whose accepted keys may be documented as args.
+    pub(crate) allow_kwargs_docs: bool,
+
+    #[arg(long, default_value_t = false, alias = "sb")]
+    /// Will not require a docstring, or args in it, for functions whose body is just `...`
+    /// or `pass`, such as the methods of protocols. Docstrings with args are still checked.
+    pub(crate) skip_stub_bodies: bool,
+
+    #[arg(
+        long,
+        default_value_t = true,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true",
+        value_parser = BoolishValueParser::new()
+    )]
+    /// Will skip the files with any of `--generated-markers` in their first lines, as
+    /// written by code generators. On by default, and turned off with
+    /// `--skip-generated=false`.
+    pub(crate) skip_generated: bool,
+
+    #[arg(long, value_delimiter = ',', default_values = DEFAULT_GENERATED_MARKERS)]
+    /// Comma-separated markers of generated files for `--skip-generated`.
+    pub(crate) generated_markers: Vec<String>,
+
+    #[arg(long, default_value_t = false, alias = "pc")]
+    /// Will accept args in the docstring whose names differ from the ones in the signature
+    /// only by case, which are otherwise reported on their own.
+    pub(crate) ignore_param_case: bool,
+
+    #[arg(long, default_value_t = 0)]
+    /// Will skip the functions whose body spans fewer lines than this. Requires the
+    /// tree-sitter parser.
+    pub(crate) min_function_lines: usize,
+
+    #[arg(long, default_value_t = false, alias = "ip")]
+    /// Will skip the functions whose names start with an underscore, such as `_helper`.
+    /// Dunders, such as `__init__`, are not considered private.
+    pub(crate) ignore_private: bool,
+
+    #[arg(long, default_value_t = false, alias = "id")]
+    /// Will skip the functions whose names start and end with two underscores, such as
+    /// `__init__` and `__repr__`.
+    pub(crate) ignore_dunder: bool,
+
+    #[arg(long = "skip-decorated", value_name = "NAME")]
+    /// Will skip the functions with a decorator whose dotted name ends with NAME, such as
+    /// `command` or `cli.command` for `@cli.command("x")`. Can be repeated. Requires the
+    /// tree-sitter parser.
+    pub(crate) skip_decorated: Vec<String>,
+
+    #[arg(long = "exclude-functions", value_name = "REGEX", value_parser = Regex::new)]
+    /// Will skip the functions whose names match REGEX, such as `^test_`. Can be repeated,
+    /// in which case matching any of them is enough.
+    pub(crate) exclude_functions: Vec<Regex>,
+
+    #[arg(long = "ignore-params", value_name = "NAME")]
+    /// Will ignore the params named NAME in both the signatures and the docstrings, as is
+    /// done with the receivers of methods. Can be repeated.
+    pub(crate) ignore_params: Vec<String>,
+
+    #[arg(long, default_value_t, value_enum)]
+    /// Which functions to check: the ones outside classes, the ones defined directly in
+    /// the body of a class, or all of them.
+    pub(crate) only: FunctionSelection,
+
+    #[arg(long, value_name = "CODE", value_delimiter = ',')]
+    /// Will only report the rules whose codes start with CODE, such as `PSD2` for the
+    /// args, among the ones enabled by the other flags. Can be repeated or separated by
+    /// commas.
+    pub(crate) select: Vec<RuleSelector>,
+
+    #[arg(long, value_name = "CODE", value_delimiter = ',')]
+    /// Will not report the rules whose codes start with CODE, such as `PSD101`. Overrides
+    /// `--select` when more specific than it. Can be repeated or separated by commas.
+    pub(crate) ignore: Vec<RuleSelector>,
+
+    #[arg(long, value_name = "CODE", value_delimiter = ',')]
+    /// Will report the rules whose codes start with CODE, such as `PSD3`, as warnings,
+    /// which are shown but do not make the run fail. Can be repeated or separated by
+    /// commas.
+    pub(crate) warn: Vec<RuleSelector>,
+
+    #[arg(long, value_name = "PATH")]
+    /// Will read the configuration from PATH (a `pystaleds.toml`, or a `pyproject.toml`
+    /// with a `[tool.pystaleds]` table) instead of discovering it next to the checked path.
+    /// The flags given in the command line take precedence over it.
+    pub(crate) config: Option<PathBuf>,
+}
+
+// Options that configure how the results of a check are reported.
+#[derive(Args)]
+pub(crate) struct ReportArgs {
+    #[arg(long, default_value_t = false)]
+    /// Will count the files without any functions in the summary of the run. They are
+    /// left out by default, since there is nothing to check in them.
+    pub(crate) count_trivial_files: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "rule"
+    )]
+    /// Will print the number of errors of each rule instead of the errors, sorted by
+    /// count. With `=directory`, they are counted separately for each top-level directory.
+    pub(crate) statistics: Option<StatisticsGrouping>,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "GROUPING",
+        conflicts_with = "statistics"
+    )]
+    /// Will print, instead of the errors, the number of files and functions checked and
+    /// of errors of each rule in each directory, sorted by errors. Up to `--depth` levels
+    /// of directories are told apart. Prints a table, or JSON with `--format json`.
+    pub(crate) stats_by: Option<StatsGrouping>,
+
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "stats_by")]
+    /// Number of levels of directories told apart by `--stats-by dir`, such as 2 for
+    /// `src/pkg`.
+    pub(crate) depth: usize,
+
+    #[arg(long, default_value_t, value_enum)]
+    /// Format in which the errors are reported, to stdout once all files are checked,
+    /// sorted by path and line.
+    pub(crate) format: OutputFormat,
+
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    /// Whether the errors of the text format are colored. With `auto`, they are colored
+    /// if stdout is a terminal and the `NO_COLOR` environment variable is not set.
+    pub(crate) color: ColorChoice,
+
+    #[arg(short, long, default_value_t = false)]
+    /// Will not print anything but, on failure, a final line with the number of errors.
+    /// The errors are still written with `--output`.
+    pub(crate) quiet: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Will not show the bar with the number of files checked so far, which is otherwise
+    /// shown in stderr while checking a folder if the errors are printed to a terminal in
+    /// the text format.
+    pub(crate) no_progress: bool,
+
+    #[arg(long, value_name = "N")]
+    /// Will exit with 0 as long as at most N errors are found, counting each error rather
+    /// than the files with them. Meant to be lowered over time.
+    pub(crate) max_errors: Option<usize>,
+
+    #[arg(long, value_name = "PATH")]
+    /// Will leave out the violations recorded in the baseline at PATH, so that only new
+    /// ones are reported and fail the check. Entries of the baseline that no longer match
+    /// any violation are reported as stale, so that it can be pruned.
+    pub(crate) baseline: Option<PathBuf>,
+
+    #[arg(long, default_value_t = false, requires = "baseline")]
+    /// Will record the violations found in the baseline given with `--baseline`, replacing
+    /// it, instead of reporting them.
+    pub(crate) write_baseline: bool,
+
+    #[arg(long, value_name = "REF")]
+    /// Will only report the violations in the functions touched since the git ref REF,
+    /// such as `origin/main`, as found by `git diff REF`, so that CI only fails because of
+    /// the code changed. Files that git does not track count as touched whole. See
+    /// `--report-untouched`.
+    pub(crate) diff_against: Option<String>,
+
+    #[arg(long, default_value_t = false, requires = "diff_against")]
+    /// Will still report the violations in the functions not touched since the ref given
+    /// with `--diff-against`, as warnings, so that they do not fail the check.
+    pub(crate) report_untouched: bool,
+
+    #[arg(long, default_value_t = false)]
+    /// Will exit with 0 even if errors are found, still reporting them. Runs that could
+    /// not be completed still exit with 2.
+    pub(crate) exit_zero: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["quiet", "statistics", "stats_by"]
+    )]
+    /// Will only print the number of errors found, as a single integer.
+    pub(crate) count: bool,
+
+    #[arg(short, long, action = ArgAction::Count, conflicts_with = "quiet")]
+    /// Will log each file as it is checked and each function with its outcome (ok,
+    /// skipped and why, or failed) to stderr. When repeated, will also log the params
+    /// parsed from the signature and from the docstring of each function.
+    pub(crate) verbose: u8,
+
+    #[arg(short, long, value_name = "FILE")]
+    /// Will write the errors to FILE instead of stdout, in the chosen format, replacing
+    /// its contents. The summary of the run is printed to stdout instead.
+    pub(crate) output: Option<PathBuf>,
+}
+
+// Options that configure how the files are fixed.
+#[derive(Args)]
+pub(crate) struct FixArgs {
+    #[arg(long, value_name = "CODE", value_delimiter = ',')]
+    /// Will only fix the violations of the rules whose codes start with CODE, such as
+    /// `PSD202` to remove the args that are no longer in the signature without adding the
+    /// new ones, whether they are reported or not. By default, the reported rules are
+    /// fixed. Can be repeated or separated by commas.
+    pub(crate) fix_only: Vec<RuleSelector>,
+
+    #[arg(long, default_value_t = false)]
+    /// Will print the changes that fixing the files would make as a unified diff instead
+    /// of checking them, without modifying them. Exits with 1 if there is any change.
+    pub(crate) diff: bool,
+
+    #[arg(long, value_name = "PATH")]
+    /// Will write the changes that fixing the files would make as a single patch at
+    /// PATH instead of checking them, without modifying them, so that it can be applied
+    /// with `git apply` from the current directory. The patch is empty if there is nothing
+    /// to fix. Exits with 1 if there is any change.
+    pub(crate) fix_output: Option<PathBuf>,
+}
+
+// Checks the files, which is also what the program does without a command.
+#[derive(Args)]
+#[command(mut_arg("path", |arg| {
+    arg.required_unless_present_any(["list_rules", "show_config", "stdin"])
+}))]
+pub(crate) struct CheckCommand {
+    #[command(flatten)]
+    pub(crate) files: FileArgs,
+
+    #[command(flatten)]
+    pub(crate) input: StdinArgs,
+
+    #[command(flatten)]
+    pub(crate) rules: RuleArgs,
+
+    #[command(flatten)]
+    pub(crate) report: ReportArgs,
+
+    #[arg(long, default_value_t = false)]
+    /// Will rewrite the "Args" section of the google docstrings whose args differ from
+    /// the signature (PSD201, PSD202 and PSD204) to match it, keeping the descriptions of
+    /// the params that still exist and adding `_description_` for the new ones. The
+    /// docstrings that cannot be fixed safely are left untouched and still reported.
+    /// Same as the `fix` command.
+    pub(crate) fix: bool,
+
+    #[command(flatten)]
+    pub(crate) fixes: FixArgs,
+
+    #[arg(long, default_value_t = false)]
+    /// Will list every rule with its code, whether it is enforced with the other flags and
+    /// its default severity, instead of checking any files. Supports `--format json`. Same
+    /// as the `rules` command.
+    pub(crate) list_rules: bool,
+
+    #[arg(long, default_value_t = false, conflicts_with = "list_rules")]
+    /// Will print the configuration with which the files in the path (or the current
+    /// directory) would be checked, along with where each value comes from, instead of
+    /// checking any files. Prints TOML, or JSON with `--format json`. Same as the
+    /// `show-config` command.
+    pub(crate) show_config: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = [
+            "list_rules", "show_config", "stdin", "fix", "diff", "fix_output", "watch"
+        ]
+    )]
+    /// Will print the files that would be checked, one per line, instead of checking
+    /// them. With `--verbose`, the files that were left out are logged along with why.
+    pub(crate) list_files: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = [
+            "stdin", "fix", "diff", "fix_output", "write_baseline", "output", "count", "quiet",
+            "stats_by"
+        ]
+    )]
+    /// Will keep watching the checked files after checking them, checking again the ones
+    /// that are changed or created and printing their errors along with the updated
+    /// summary, until interrupted with Ctrl-C. Only supports the text format.
+    pub(crate) watch: bool,
+}
+
+// Fixes the files, then checks them for what is left to fix by hand.
+#[derive(Args)]
+pub(crate) struct FixCommand {
+    #[command(flatten)]
+    pub(crate) files: FileArgs,
+
+    #[command(flatten)]
+    pub(crate) rules: RuleArgs,
+
+    #[command(flatten)]
+    pub(crate) report: ReportArgs,
+
+    #[command(flatten)]
+    pub(crate) fixes: FixArgs,
+}
+
+// Lists every rule with its code, whether it is enforced and its default severity.
+#[derive(Args)]
+pub(crate) struct RulesCommand {
+    /// File or folder whose configuration decides which rules are enforced, the current
+    /// directory by default.
+    pub(crate) path: Option<String>,
+
+    #[command(flatten)]
+    pub(crate) rules: RuleArgs,
+
+    #[arg(long, default_value_t, value_enum)]
+    /// Format in which the rules are listed.
+    pub(crate) format: OutputFormat,
+}
+
+// Prints the configuration with which the files would be checked, along with where each
+// value comes from.
+#[derive(Args)]
+pub(crate) struct ShowConfigCommand {
+    /// File or folder whose configuration is printed, the current directory by default.
+    pub(crate) path: Option<String>,
+
+    #[command(flatten)]
+    pub(crate) rules: RuleArgs,
+
+    #[arg(long, default_value_t, value_enum)]
+    /// Format in which the configuration is printed: TOML with `text`, or JSON with
+    /// `json`.
+    pub(crate) format: OutputFormat,
+}
+
+impl From<FixCommand> for CheckCommand {
+    /// Checks the files once fixed, or previews the fixes if asked to.
+    fn from(command: FixCommand) -> Self {
+        CheckCommand {
+            files: command.files,
+            input: StdinArgs::default(),
+            rules: command.rules,
+            report: command.report,
+            fix: !command.fixes.diff && command.fixes.fix_output.is_none(),
+            fixes: command.fixes,
+            list_rules: false,
+            show_config: false,
+            list_files: false,
+            watch: false,
+        }
+    }
+}
+
+impl CheckCommand {
+    /// Gathers the options that determine which rules are enforced and how they are fixed.
+    pub(crate) fn check_options(&self) -> CheckOptions {
+        CheckOptions {
+            attach_fixes: matches!(
+                self.report.format,
+                OutputFormat::Json | OutputFormat::Rdjson
+            ),
+            fix_only: self.fixes.fix_only.clone(),
+            ..self.rules.check_options()
+        }
+    }
+}
+
+impl RuleArgs {
+    /// Gathers the options given in the command line or in the environment, leaving out
+    /// the defaults, so that they can be layered on top of the configuration files.
+    pub(crate) fn cli_config(&self, matches: &ArgMatches) -> Config {
+        self.config_where(|id| {
+            matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        })
+    }
+
+    /// Gathers the options whose ids satisfy `given` into a configuration.
+    pub(crate) fn config_where(&self, given: impl Fn(&str) -> bool) -> Config {
+        Config {
+            parser: given("parser").then_some(self.parser),
+            docstyle: given("docstyle").then_some(self.docstyle),
+            break_on_empty_line: given("break_on_empty_line").then_some(self.break_on_empty_line),
+            forbid_no_docstring: given("forbid_no_docstring").then_some(self.forbid_no_docstring),
+            forbid_no_args_in_docstring: given("forbid_no_args_in_docstring")
+                .then_some(self.forbid_no_args_in_docstring),
+            forbid_untyped_docstrings: given("forbid_untyped_docstrings")
+                .then_some(self.forbid_untyped_docstrings),
+            include_args_and_kwargs: given("include_args_and_kwargs")
+                .then_some(self.include_args_and_kwargs),
+            forbid_style_mismatch: given("forbid_style_mismatch")
+                .then_some(self.forbid_style_mismatch),
+            check_examples_style: given("check_examples_style")
+                .then_some(self.check_examples_style),
+            forbid_missing_returns: given("forbid_missing_returns")
+                .then_some(self.forbid_missing_returns),
+            check_return_type: given("check_return_type").then_some(self.check_return_type),
+            check_see_also: given("check_see_also").then_some(self.check_see_also),
+            check_raises: given("check_raises").then_some(self.check_raises),
+            forbid_missing_yields: given("forbid_missing_yields")
+                .then_some(self.forbid_missing_yields),
+            check_yield_type: given("check_yield_type").then_some(self.check_yield_type),
+            check_attributes: given("check_attributes").then_some(self.check_attributes),
+            init_args_in_class_docstring: given("init_args_in_class_docstring")
+                .then_some(self.init_args_in_class_docstring),
+            forbid_no_module_docstring: given("forbid_no_module_docstring")
+                .then_some(self.forbid_no_module_docstring),
+            forbid_empty_modules: given("forbid_empty_modules")
+                .then_some(self.forbid_empty_modules),
+            include_trivial_init: given("include_trivial_init")
+                .then_some(self.include_trivial_init),
+            forbid_placeholders: given("forbid_placeholders").then_some(self.forbid_placeholders),
+            placeholders: given("placeholders").then(|| self.placeholders.clone()),
+            forbid_empty_descriptions: given("forbid_empty_descriptions")
+                .then_some(self.forbid_empty_descriptions),
+            check_default_mentions: given("check_default_mentions")
+                .then_some(self.check_default_mentions),
+            default_mention_pattern: given("default_mention_pattern")
+                .then(|| self.default_mention_pattern.clone()),
+            check_optional_consistency: given("check_optional_consistency")
+                .then_some(self.check_optional_consistency),
+            allow_out_of_order_args: given("allow_out_of_order_args")
+                .then_some(self.allow_out_of_order_args),
+            check_section_order: given("check_section_order").then_some(self.check_section_order),
+            section_order: given("section_order").then(|| self.section_order.clone()),
+            forbid_missing_summary: given("forbid_missing_summary")
+                .then_some(self.forbid_missing_summary),
+            allow_documented_self: given("allow_documented_self")
+                .then_some(self.allow_documented_self),
+            require_full_typing: given("require_full_typing").then_some(self.require_full_typing),
+            allow_omitting_defaulted_args: given("allow_omitting_defaulted_args")
+                .then_some(self.allow_omitting_defaulted_args),
+            allow_kwargs_docs: given("allow_kwargs_docs").then_some(self.allow_kwargs_docs),
+            skip_stub_bodies: given("skip_stub_bodies").then_some(self.skip_stub_bodies),
+            skip_generated: given("skip_generated").then_some(self.skip_generated),
+            generated_markers: given("generated_markers").then(|| self.generated_markers.clone()),
+            ignore_param_case: given("ignore_param_case").then_some(self.ignore_param_case),
+            min_function_lines: given("min_function_lines").then_some(self.min_function_lines),
+            ignore_private: given("ignore_private").then_some(self.ignore_private),
+            ignore_dunder: given("ignore_dunder").then_some(self.ignore_dunder),
+            skip_decorated: given("skip_decorated").then(|| self.skip_decorated.clone()),
+            exclude_functions: given("exclude_functions").then(|| self.exclude_functions.clone()),
+            ignore_params: given("ignore_params").then(|| self.ignore_params.clone()),
+            only: given("only").then_some(self.only),
+            select: given("select").then(|| self.select.clone()),
+            ignore: given("ignore").then(|| self.ignore.clone()),
+            warn: given("warn").then(|| self.warn.clone()),
+            skip: None,
+            per_file: None,
+        }
+    }
+
+    /// Gathers the options that determine which rules are enforced, without fixing any.
+    pub(crate) fn check_options(&self) -> CheckOptions {
+        CheckOptions {
+            break_on_empty_line: self.break_on_empty_line,
+            succeed_if_no_docstring: !self.forbid_no_docstring,
+            succeed_if_no_args_in_docstring: !self.forbid_no_args_in_docstring,
+            succeed_if_docstrings_are_not_typed: !self.forbid_untyped_docstrings,
+            skip_args_and_kwargs: !self.include_args_and_kwargs,
+            docstyle: self.docstyle,
+            forbid_style_mismatch: self.forbid_style_mismatch,
+            check_examples_style: self.check_examples_style,
+            forbid_missing_returns: self.forbid_missing_returns,
+            check_return_type: self.check_return_type,
+            check_see_also: self.check_see_also,
+            check_raises: self.check_raises,
+            forbid_missing_yields: self.forbid_missing_yields,
+            check_yield_type: self.check_yield_type,
+            check_attributes: self.check_attributes,
+            init_args_in_class_docstring: self.init_args_in_class_docstring,
+            forbid_no_module_docstring: self.forbid_no_module_docstring,
+            succeed_if_empty_module: !self.forbid_empty_modules,
+            skip_trivial_init: !self.include_trivial_init,
+            forbid_placeholders: self.forbid_placeholders,
+            placeholders: self.placeholders.clone(),
+            forbid_empty_descriptions: self.forbid_empty_descriptions,
+            check_default_mentions: self.check_default_mentions,
+            default_mention_pattern: self.default_mention_pattern.clone(),
+            check_optional_consistency: self.check_optional_consistency,
+            allow_out_of_order_args: self.allow_out_of_order_args,
+            check_section_order: self.check_section_order,
+            section_order: self.section_order.clone(),
+            forbid_missing_summary: self.forbid_missing_summary,
+            allow_documented_self: self.allow_documented_self,
+            require_full_typing: self.require_full_typing,
+            allow_omitting_defaulted_args: self.allow_omitting_defaulted_args,
+            allow_kwargs_docs: self.allow_kwargs_docs,
+            skip_stub_bodies: self.skip_stub_bodies,
+            skip_generated: self.skip_generated,
+            generated_markers: self.generated_markers.clone(),
+            ignore_param_case: self.ignore_param_case,
+            min_function_lines: self.min_function_lines,
+            ignore_private: self.ignore_private,
+            ignore_dunder: self.ignore_dunder,
+            skip_decorated: self.skip_decorated.clone(),
+            exclude_functions: self.exclude_functions.clone(),
+            ignore_params: self.ignore_params.clone(),
+            only: self.only,
+            rules: RuleSelection {
+                select: self.select.clone(),
+                ignore: self.ignore.clone(),
+                warn: self.warn.clone(),
+            },
+            // Set by the commands that render or apply the fixes.
+            attach_fixes: false,
+            fix_only: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,455 @@
+use std::{
+    env::current_dir,
+    fs::File,
+    io::{IsTerminal, Write},
+    path::Path,
+    time::Instant,
+};
+
+use anyhow::{anyhow, Context, Result};
+use clap::ArgMatches;
+use pystaleds::baseline::Baseline;
+use pystaleds::cancellation::CancellationToken;
+use pystaleds::human::HumanRenderer;
+use pystaleds::output::OutputFormat;
+use pystaleds::rules_checking::{CheckError, CompliancyChecker, SourceReport};
+use pystaleds::stats::{
+    directory_stats, pluralize, rule_counts, statistics_table, Stats, StatsGrouping,
+};
+
+use crate::cli::args::{CheckCommand, ReportArgs, RulesCommand, ShowConfigCommand};
+use crate::cli::files::{listed_files, python_files, relative_path, FoundFiles};
+use crate::cli::fix::{fix_files, preview_fixes, FixMode};
+use crate::cli::pipeline::{
+    check_files, check_source_see_also, source_symbols, BaselineMode, ChangeFilter, CheckedFile,
+    Compliancy, FileSettings, Reporter, Settings, SettingsResolver,
+};
+use crate::cli::progress::Progress;
+use crate::cli::rules::{list_rules, show_config};
+use crate::cli::watch::watch;
+use crate::cli::Outcome;
+
+/// Checks the files, reporting their errors, or fixes them first if asked to.
+pub(crate) fn check(args: CheckCommand, matches: &ArgMatches) -> Result<Outcome> {
+    if args.show_config {
+        let command = ShowConfigCommand {
+            path: args.files.path,
+            rules: args.rules,
+            format: args.report.format,
+        };
+
+        return show_config(&command, matches);
+    }
+
+    if args.list_rules {
+        let command = RulesCommand {
+            path: args.files.path,
+            rules: args.rules,
+            format: args.report.format,
+        };
+
+        return list_rules(&command, matches);
+    }
+
+    let color = args.report.color.enabled();
+    let start = Instant::now();
+    let mut resolver = SettingsResolver::new(&args.rules, matches, args.check_options())?;
+
+    let path = Path::new(args.files.path.as_deref().unwrap_or("."));
+    let check_dir = !args.input.stdin
+        && (args.files.files_from.is_some() || !args.files.glob.is_empty() || path.is_dir());
+
+    // Read before anything else, so that editors get an error if it is not valid UTF-8.
+    let stdin_source = args
+        .input
+        .stdin
+        .then(|| std::io::read_to_string(std::io::stdin()))
+        .transpose()
+        .context("could not read the source code from the standard input")?;
+
+    let found = if let Some(list) = &args.files.files_from {
+        listed_files(list, &args.files)?
+    } else if args.input.stdin {
+        let filename = args
+            .input
+            .stdin_filename
+            .as_deref()
+            .unwrap_or(Path::new("-"));
+
+        FoundFiles {
+            python: vec![relative_path(filename, &current_dir()?)],
+            ..Default::default()
+        }
+    } else if check_dir {
+        python_files(path, &args.files)?
+    } else {
+        FoundFiles {
+            python: vec![relative_path(path, &current_dir()?)],
+            ..Default::default()
+        }
+    };
+    let files = found.python;
+
+    let settings = FileSettings::resolve(&files, &mut resolver)?;
+
+    // Found as for checking them, so that the list is what would be checked.
+    if args.list_files {
+        let mut listed: Vec<_> = files
+            .iter()
+            .filter(|file| {
+                let skip = settings.get(file).skip;
+
+                if skip {
+                    tracing::debug!("{}: skipped: configured with `skip = true`", file.display());
+                }
+
+                !skip
+            })
+            .collect();
+        listed.sort();
+
+        for file in listed {
+            println!("{}", file.display());
+        }
+
+        return Ok(Outcome::Clean);
+    }
+
+    // The fixes are planned with tree-sitter, whichever parser checks the files.
+    let previews_fixes = args.fixes.diff || args.fixes.fix_output.is_some();
+
+    if args.fix || previews_fixes || settings.uses(CompliancyChecker::TreeSitter) {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(0)
+            .stack_size(100_000_000) // TODO: Make the algorithm non-recursive and remove the stack expansion.
+            .build_global()
+            .expect("thread pool should be possible to initialize");
+    }
+
+    if previews_fixes {
+        return preview_fixes(&files, &settings, &args.fixes);
+    }
+
+    // Opened before checking any files, so that a bad path does not waste a whole run.
+    let mut output_file = args
+        .report
+        .output
+        .as_deref()
+        .map(|output_path| {
+            File::create(output_path).with_context(|| {
+                format!(
+                    "could not open {} to write the output",
+                    output_path.display()
+                )
+            })
+        })
+        .transpose()?;
+
+    // Lets an interrupted run stop checking files and still report what was found.
+    let cancellation = CancellationToken::new();
+    let handler_cancellation = cancellation.clone();
+    ctrlc::set_handler(move || handler_cancellation.cancel())?;
+
+    let baseline = match &args.report.baseline {
+        Some(_) if args.report.write_baseline => Some(BaselineMode::Write),
+        Some(path) => Some(BaselineMode::Filter(Baseline::load(path)?)),
+        None => None,
+    };
+    let reporter = Reporter::new(baseline, ChangeFilter::new(&args.report)?);
+
+    // The files are checked once fixed, so that only what is left is reported.
+    let fixed = args
+        .fix
+        .then(|| fix_files(&files, &settings, FixMode::Write));
+
+    if check_dir {
+        let report = &args.report;
+        // Drawn in stderr, as long as the errors are printed to a terminal, where the bar
+        // does not get in the way of whatever reads them.
+        let progress = (!report.no_progress
+            && report.format == OutputFormat::Text
+            && report.output.is_none()
+            && !report.quiet
+            && !report.count
+            && std::io::stdout().is_terminal()
+            && std::io::stderr().is_terminal())
+        .then(|| Progress::new(files.len()));
+
+        check_files(
+            &files,
+            &settings,
+            &cancellation,
+            &reporter,
+            progress.as_ref(),
+        );
+
+        if let Some(progress) = progress {
+            progress.finish();
+        }
+
+        reporter.skip(found.other);
+        reporter.skip_too_large(found.too_large);
+    } else if settings.get(&files[0]).skip {
+        tracing::debug!(
+            "{}: skipped: configured with `skip = true`",
+            files[0].display()
+        );
+        reporter.skip(1);
+    } else {
+        // In this branch, path is a file, or the name of the source code in stdin.
+        let path = &files[0];
+        let Settings {
+            parser, options, ..
+        } = settings.get(path);
+
+        let result = match &stdin_source {
+            Some(contents) => parser.check_contents(contents, path, options, &cancellation),
+            None => parser.check_file(path, options, &cancellation),
+        };
+
+        match result {
+            Err(CheckError::Cancelled) => reporter.report(CheckedFile {
+                report: SourceReport::default(),
+                trivial: false,
+            }),
+            Err(error @ CheckError::SkippedFile) => {
+                tracing::debug!("{}: skipped: {error}", path.display());
+                reporter.skip(1);
+            }
+            Err(error @ CheckError::Generated(_)) => {
+                tracing::debug!("{}: skipped: {error}", path.display());
+                reporter.skip_generated();
+            }
+            result => {
+                let mut file = result?;
+
+                if options.check_see_also {
+                    let contents = match &stdin_source {
+                        Some(contents) => contents.clone(),
+                        None => std::fs::read_to_string(path)?,
+                    };
+                    let symbols = source_symbols(path, &contents);
+
+                    file.report.diagnostics.extend(check_source_see_also(
+                        path,
+                        &contents,
+                        &symbols,
+                        &options.rules,
+                    )?);
+                }
+
+                reporter.report(file);
+            }
+        }
+    }
+
+    let cancelled = cancellation.is_cancelled();
+    // Entries of the files that were not checked are stale if the files are gone, or if
+    // the run checked every file of the current directory, which they would be among.
+    let whole_tree = check_dir
+        && !cancelled
+        && args.files.files_from.is_none()
+        && args.files.glob.is_empty()
+        && args.files.include.is_empty()
+        && path.canonicalize().ok() == current_dir()?.canonicalize().ok();
+    reporter.unvisited(&files, whole_tree);
+
+    let untouched = reporter.untouched();
+    let (checked, stats, baseline) = reporter.finish();
+    let (reports, trivial): (Vec<_>, Vec<_>) = checked
+        .into_iter()
+        .map(|file| (file.report, file.trivial))
+        .unzip();
+    let renderer = HumanRenderer::new(color);
+
+    let report = &args.report;
+
+    if report.format == OutputFormat::Text
+        && report.output.is_none()
+        && report.statistics.is_none()
+        && report.stats_by.is_none()
+        && !report.quiet
+        && !report.count
+    {
+        for rendered in reports.iter().filter_map(|report| renderer.render(report)) {
+            println!("{rendered}");
+        }
+    }
+
+    let output = if let Some(StatsGrouping::Directory) = report.stats_by {
+        let directories = directory_stats(&reports, report.depth);
+
+        Some(report.format.render_directories(&directories))
+    } else if report.format == OutputFormat::Text && report.output.is_some() {
+        let renderer = HumanRenderer::new(false);
+        let rendered: Vec<_> = reports
+            .iter()
+            .filter_map(|report| renderer.render(report))
+            .collect();
+
+        Some(rendered.join("\n"))
+    } else {
+        report.format.render(&reports)
+    };
+
+    if let (Some(file), Some(output_path)) = (&mut output_file, &report.output) {
+        file.write_all(output.as_deref().unwrap_or_default().as_bytes())
+            .with_context(|| format!("could not write the output to {}", output_path.display()))?;
+    }
+
+    if report.count && !cancelled {
+        println!("{}", stats.violations);
+
+        return Ok(outcome(&stats, report));
+    }
+
+    let mut summary = renderer.summary(
+        &stats,
+        report.count_trivial_files,
+        cancelled,
+        start.elapsed(),
+    );
+
+    if let Some(path) = &report.baseline {
+        if report.write_baseline {
+            let baseline = Baseline::new(baseline.recorded);
+            baseline.write(path)?;
+
+            summary.push_str(&format!(
+                "\nWrote {} to the baseline {}",
+                pluralize(baseline.len(), "violation"),
+                path.display()
+            ));
+        } else {
+            for entry in &baseline.stale {
+                let verb = if entry.count == 1 { "is" } else { "are" };
+
+                tracing::warn!(
+                    "{}: stale entry: {} in `{}` of {} {verb} no longer found",
+                    path.display(),
+                    pluralize(entry.count, &format!("{} violation", entry.rule)),
+                    entry.function,
+                    entry.path
+                );
+            }
+
+            summary.push_str(&format!(
+                "\nLeft out {} known from the baseline, which has {}",
+                pluralize(baseline.known, "violation"),
+                pluralize(baseline.stale.len(), "stale entry")
+            ));
+        }
+    }
+
+    if let Some(reference) = &report.diff_against {
+        if report.report_untouched {
+            summary.push_str(&format!(
+                "\nReported {} in functions not touched since {reference} as warnings",
+                pluralize(untouched, "violation")
+            ));
+        } else {
+            summary.push_str(&format!(
+                "\nLeft out {} in functions not touched since {reference}",
+                pluralize(untouched, "violation")
+            ));
+        }
+    }
+
+    if let Some(fixed) = fixed {
+        let functions: usize = fixed.iter().map(|file| file.functions).sum();
+
+        summary.push_str(&format!(
+            "\nFixed {} in {}",
+            pluralize(functions, "function"),
+            pluralize(fixed.len(), "file")
+        ));
+    }
+
+    if let Some(max_errors) = report.max_errors {
+        let comparison = if stats.violations > max_errors {
+            "more than"
+        } else {
+            "within"
+        };
+
+        summary.push_str(&format!(
+            "\nFound {}, {comparison} the maximum of {max_errors}",
+            pluralize(stats.violations, "error")
+        ));
+    }
+
+    // The statistics go along with the summary, so that they do not mix with the payload.
+    if let Some(grouping) = report.statistics {
+        let counts = rule_counts(&reports, grouping);
+
+        if !counts.is_empty() {
+            summary = format!("{}\n{summary}", statistics_table(&counts));
+        }
+    }
+
+    if report.quiet {
+        // Only the final error, if any, is printed.
+    } else if report.output.is_some() || report.format == OutputFormat::Text {
+        // The errors in the text format are already printed, but not the table of
+        // `--stats-by`.
+        if let Some(output) = output.filter(|_| report.output.is_none()) {
+            println!("{output}");
+        }
+
+        println!("{summary}");
+    } else {
+        if let Some(output) = output {
+            println!("{output}");
+        }
+
+        // Keeps the payload in stdout parseable.
+        eprintln!("{summary}");
+    }
+
+    if cancelled {
+        Err(anyhow!("check was cancelled"))
+    } else if args.watch {
+        let checked = reports
+            .into_iter()
+            .zip(trivial)
+            .map(|(report, trivial)| CheckedFile { report, trivial })
+            .collect();
+        let root = (check_dir && args.files.files_from.is_none()).then_some(path);
+
+        watch(
+            &args,
+            &mut resolver,
+            &files,
+            root,
+            checked,
+            &stats,
+            &cancellation,
+        )?;
+
+        Ok(Outcome::Clean)
+    } else {
+        Ok(outcome(&stats, report))
+    }
+}
+
+/// Determines the outcome of a completed run from its stats.
+fn outcome(stats: &Stats, report: &ReportArgs) -> Outcome {
+    let max_errors = report.max_errors.unwrap_or_default();
+
+    if stats.violations <= max_errors || report.exit_zero {
+        return Outcome::Clean;
+    }
+
+    let mut message = format!(
+        "found {} in {}",
+        pluralize(stats.violations, "error"),
+        pluralize(stats.files_with_errors, "file")
+    );
+
+    if max_errors > 0 {
+        message.push_str(&format!(", more than the maximum of {max_errors}"));
+    }
+
+    // The count is all there is in the output, even without a final error.
+    Outcome::Errors((!report.count).then_some(message))
+}
@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use pystaleds::conversion::{apply_conversions, plan_conversions, ConversionTarget};
+use pystaleds::stats::pluralize;
+use tracing::Level;
+
+use crate::cli::files::is_hidden;
+use crate::cli::Outcome;
+
+/// Converts the docstrings of the Python files in a path to the target style, leaving
+/// untouched the files in which some docstring cannot be converted cleanly.
+pub(crate) fn convert(
+    path: &Path,
+    target: ConversionTarget,
+    dry_run: bool,
+    allow_hidden: bool,
+) -> Result<Outcome> {
+    let files: Vec<PathBuf> = if path.is_dir() {
+        walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_entry(|e| allow_hidden || !is_hidden(e))
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .filter(|entry| {
+                entry.is_file() && entry.extension() == Some(&std::ffi::OsString::from("py"))
+            })
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_python::language())?;
+
+    let mut files_with_errors = 0;
+    let mut converted_docstrings = 0;
+
+    for file in files {
+        let contents = std::fs::read_to_string(&file)?;
+
+        let conversions = match plan_conversions(&mut parser, &contents, target) {
+            Ok(conversions) => conversions,
+            Err(e) => {
+                tracing::event!(Level::ERROR, "{}: {:#}", file.display(), e);
+                files_with_errors += 1;
+
+                continue;
+            }
+        };
+
+        if conversions.is_empty() {
+            continue;
+        }
+
+        if dry_run {
+            for conversion in &conversions {
+                println!("--- {}:{}", file.display(), conversion.row + 1);
+
+                for line in conversion.original.lines() {
+                    println!("-{line}");
+                }
+
+                for line in conversion.converted.lines() {
+                    println!("+{line}");
+                }
+            }
+        } else {
+            std::fs::write(&file, apply_conversions(&contents, &conversions))?;
+        }
+
+        converted_docstrings += conversions.len();
+    }
+
+    if files_with_errors == 0 {
+        if dry_run {
+            println!("✅ {} docstrings would be converted.", converted_docstrings);
+        } else {
+            println!("✅ Converted {} docstrings!", converted_docstrings);
+        }
+
+        Ok(Outcome::Clean)
+    } else {
+        Ok(Outcome::Errors(Some(format!(
+            "could not convert docstrings in {}",
+            pluralize(files_with_errors, "file")
+        ))))
+    }
+}
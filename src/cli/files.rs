@@ -0,0 +1,397 @@
+use std::{
+    collections::HashSet,
+    env::current_dir,
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use glob::glob;
+use walkdir::DirEntry;
+
+use crate::cli::args::FileArgs;
+
+/// Directory of a file, which is the current one for a bare file name, or `None` if the
+/// path is a directory itself.
+pub(crate) fn directory_of(path: &Path) -> Option<&Path> {
+    if path.is_dir() {
+        return None;
+    }
+
+    Some(
+        path.parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new(".")),
+    )
+}
+
+/// Options to match the paths relative to the checked path against patterns, where `*`
+/// does not match across folders, while `**` does, as when walking through a `--glob`.
+const MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Determines if a file matches some of the `--include` patterns, relative to the checked
+/// path, or if there are none.
+fn is_included(file: &Path, root: &Path, include: &[glob::Pattern]) -> bool {
+    let relative = file.strip_prefix(root).unwrap_or(file);
+
+    include.is_empty()
+        || include
+            .iter()
+            .any(|pattern| pattern.matches_path_with(relative, MATCH_OPTIONS))
+}
+
+/// A `--glob`, which excludes the files it matches if it starts with `!`.
+pub(crate) struct GlobPattern<'a> {
+    text: &'a str,
+    pattern: glob::Pattern,
+    negated: bool,
+}
+
+/// Gathers the files matching the globs, relative to the root. Each file is decided by
+/// the last glob that matches it, so that a negated glob excludes the files matched by
+/// the globs before it, but not by the ones after it.
+fn glob_files(root: &Path, globs: &[String]) -> Result<Vec<PathBuf>> {
+    let globs = parse_globs(globs)?;
+    let escaped_root = glob::Pattern::escape(&root.to_string_lossy());
+    let mut seen = HashSet::new();
+    let mut files = Vec::new();
+
+    for positive in globs.iter().filter(|glob| !glob.negated) {
+        let pattern = Path::new(&escaped_root).join(positive.text);
+
+        let matches = glob(&pattern.to_string_lossy())
+            .with_context(|| format!("invalid glob `{}`", positive.text))?
+            .filter_map(|entry| entry.ok());
+
+        // Files matching several globs are checked (and counted) only once.
+        files.extend(matches.filter(|entry| seen.insert(entry.clone())));
+    }
+
+    files.retain(|file| {
+        let relative = file.strip_prefix(root).unwrap_or(file);
+
+        globs
+            .iter()
+            .rev()
+            .find(|glob| glob.pattern.matches_path_with(relative, MATCH_OPTIONS))
+            .is_none_or(|glob| !glob.negated)
+    });
+
+    Ok(files)
+}
+
+/// Parses the `--glob`s, telling apart the negated ones.
+pub(crate) fn parse_globs(globs: &[String]) -> Result<Vec<GlobPattern<'_>>> {
+    globs
+        .iter()
+        .map(|s| {
+            let (text, negated) = match s.strip_prefix('!') {
+                Some(text) => (text, true),
+                None => (s.as_str(), false),
+            };
+            let pattern =
+                glob::Pattern::new(text).with_context(|| format!("invalid glob `{s}`"))?;
+
+            Ok(GlobPattern {
+                text,
+                pattern,
+                negated,
+            })
+        })
+        .collect()
+}
+
+/// Determines if a file relative to the root would be gathered by the globs, i.e. if the
+/// last glob that matches it is not negated.
+pub(crate) fn is_globbed(relative: &Path, globs: &[GlobPattern]) -> bool {
+    globs
+        .iter()
+        .rev()
+        .find(|glob| glob.pattern.matches_path_with(relative, MATCH_OPTIONS))
+        .is_some_and(|glob| !glob.negated)
+}
+
+/// Determines if a listed file relative to the current folder is left out by the globs,
+/// i.e. if the last glob that matches it is negated, or if none does while some glob is
+/// not negated. Without globs, nothing is left out.
+fn is_excluded_by_globs(relative: &Path, globs: &[GlobPattern]) -> bool {
+    match globs
+        .iter()
+        .rev()
+        .find(|glob| glob.pattern.matches_path_with(relative, MATCH_OPTIONS))
+    {
+        Some(glob) => glob.negated,
+        None => globs.iter().any(|glob| !glob.negated),
+    }
+}
+
+/// Determines if a file or folder is hidden, i.e. if it starts with '.'.
+pub(crate) fn is_hidden(e: &DirEntry) -> bool {
+    e.file_name()
+        .to_str()
+        .is_some_and(|s| s.starts_with('.') && s != ".")
+}
+
+/// Names of the folders that are left out unless `--no-default-excludes` is given, since
+/// they hold virtual environments, installed packages, build artifacts or caches instead
+/// of the code of the project. They are left out on top of the hidden folders and of the
+/// `!` globs, instead of replacing them.
+const DEFAULT_EXCLUDES: &[&str] = &[
+    "venv",
+    ".venv",
+    "build",
+    "dist",
+    "site-packages",
+    "__pycache__",
+];
+
+/// Determines if an entry below the walked path is a folder excluded by default.
+fn is_default_excluded(e: &DirEntry) -> bool {
+    e.depth() > 0
+        && e.file_type().is_dir()
+        && e.file_name()
+            .to_str()
+            .is_some_and(|name| DEFAULT_EXCLUDES.contains(&name))
+}
+
+/// Determines if a path is inside a folder excluded by default.
+pub(crate) fn has_default_excluded_component(path: &Path) -> bool {
+    let mut folders = path.parent().into_iter().flat_map(Path::components);
+
+    folders.any(|component| match component {
+        Component::Normal(name) => name
+            .to_str()
+            .is_some_and(|name| DEFAULT_EXCLUDES.contains(&name)),
+        _ => false,
+    })
+}
+
+/// Determines if a path is hidden or inside a hidden folder.
+pub(crate) fn has_hidden_component(path: &Path) -> bool {
+    path.components().any(|component| match component {
+        Component::Normal(name) => name.to_str().is_some_and(|s| s.starts_with('.')),
+        _ => false,
+    })
+}
+
+/// Size above which files are skipped unless `--max-file-size` says otherwise, which
+/// leaves out large generated modules and embedded data, whose checks take the most time
+/// while hardly being worth it.
+pub(crate) const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Python files found in a path, along with the counts of the skipped ones.
+#[derive(Default)]
+pub(crate) struct FoundFiles {
+    pub(crate) python: Vec<PathBuf>,
+    /// Files that are not Python files.
+    pub(crate) other: usize,
+    /// Python files larger than `--max-file-size`.
+    pub(crate) too_large: usize,
+}
+
+/// Gathers the Python files to check, either through the globs (relative to the path) or
+/// by walking through the path.
+///
+/// The files are relative to the current directory whenever possible, which is how they
+/// are reported, since tools such as reviewdog match them against the repository. The
+/// numbers of other files and of too large ones found, which are skipped, are returned
+/// along with them.
+pub(crate) fn python_files(path: &Path, args: &FileArgs) -> Result<FoundFiles> {
+    let working_dir = current_dir()?;
+
+    let files: Vec<PathBuf> = if !args.glob.is_empty() {
+        let mut files = glob_files(path, &args.glob)?;
+        retain_not_excluded(&mut files, path, args, &working_dir);
+
+        files
+    } else {
+        walkdir::WalkDir::new(path)
+            .into_iter()
+            .filter_entry(|e| {
+                if !args.allow_hidden && is_hidden(e) {
+                    tracing::debug!(
+                        "{}: skipped: hidden",
+                        relative_path(e.path(), &working_dir).display()
+                    );
+
+                    return false;
+                }
+
+                let excluded = !args.no_default_excludes && is_default_excluded(e);
+
+                if excluded {
+                    tracing::debug!(
+                        "{}: skipped: folder excluded by default",
+                        relative_path(e.path(), &working_dir).display()
+                    );
+                }
+
+                !excluded
+            })
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .collect()
+    };
+
+    Ok(found_files(files, path, args, &working_dir))
+}
+
+/// Leaves out the files under a root that are hidden or in a folder excluded by default,
+/// unless allowed, as walking through the root would.
+fn retain_not_excluded(files: &mut Vec<PathBuf>, root: &Path, args: &FileArgs, working_dir: &Path) {
+    if !args.allow_hidden {
+        files.retain(|file| {
+            let hidden = has_hidden_component(file.strip_prefix(root).unwrap_or(file));
+
+            if hidden {
+                tracing::debug!(
+                    "{}: skipped: hidden, or in a hidden folder",
+                    relative_path(file, working_dir).display()
+                );
+            }
+
+            !hidden
+        });
+    }
+
+    if !args.no_default_excludes {
+        files.retain(|file| {
+            let excluded = has_default_excluded_component(file.strip_prefix(root).unwrap_or(file));
+
+            if excluded {
+                tracing::debug!(
+                    "{}: skipped: in a folder excluded by default",
+                    relative_path(file, working_dir).display()
+                );
+            }
+
+            !excluded
+        });
+    }
+}
+
+/// Gathers the Python files listed in a file, or in the standard input if it is `-`, one
+/// per line. Files that do not exist, such as the deleted ones in the output of
+/// `git diff --name-only`, are warned about and left out, and so are the ones excluded
+/// by the globs (see [`is_excluded_by_globs`]), the hidden ones and the ones in a folder
+/// excluded by default, as when walking through the current folder.
+pub(crate) fn listed_files(list: &Path, args: &FileArgs) -> Result<FoundFiles> {
+    let working_dir = current_dir()?;
+    let globs = parse_globs(&args.glob)?;
+
+    let (contents, source) = if list == Path::new("-") {
+        let contents = std::io::read_to_string(std::io::stdin())
+            .context("could not read the list of files from the standard input")?;
+
+        (contents, "the standard input".to_string())
+    } else {
+        let contents = std::fs::read_to_string(list)
+            .with_context(|| format!("could not read the list of files {}", list.display()))?;
+
+        (contents, list.display().to_string())
+    };
+
+    let mut seen = HashSet::new();
+    let mut files: Vec<PathBuf> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && seen.insert(*line))
+        .map(PathBuf::from)
+        .filter(|file| {
+            let exists = file.exists();
+
+            if !exists {
+                tracing::warn!("{}: listed in {source}, but does not exist", file.display());
+            }
+
+            exists
+        })
+        .map(|file| relative_path(&file, &working_dir))
+        .filter(|file| {
+            let excluded = is_excluded_by_globs(file, &globs);
+
+            if excluded {
+                tracing::debug!("{}: skipped: excluded by `--glob`", file.display());
+            }
+
+            !excluded
+        })
+        .collect();
+
+    retain_not_excluded(&mut files, Path::new("."), args, &working_dir);
+
+    Ok(found_files(files, Path::new("."), args, &working_dir))
+}
+
+/// Sorts out the files found under a root into the Python files to check and the skipped
+/// ones.
+pub(crate) fn found_files(
+    files: Vec<PathBuf>,
+    root: &Path,
+    args: &FileArgs,
+    working_dir: &Path,
+) -> FoundFiles {
+    let (python_files, other_files): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .filter(|entry| entry.is_file())
+        .filter(|entry| {
+            let included = is_included(entry, root, &args.include);
+
+            if !included {
+                tracing::debug!(
+                    "{}: skipped: not matched by `--include`",
+                    relative_path(entry, working_dir).display()
+                );
+            }
+
+            included
+        })
+        .partition(|entry| entry.extension() == Some(&std::ffi::OsString::from("py")));
+
+    for file in &other_files {
+        tracing::debug!(
+            "{}: skipped: not a Python file",
+            relative_path(file, working_dir).display()
+        );
+    }
+
+    // Only the metadata is read, so that large files are not read at all.
+    let (large_files, python_files): (Vec<_>, Vec<_>) =
+        python_files.into_iter().partition(|file| {
+            let size = file.metadata().map_or(0, |metadata| metadata.len());
+            let too_large = args.max_file_size > 0 && size > args.max_file_size;
+
+            if too_large {
+                tracing::info!(
+                    "{}: skipped: has {size} bytes, more than the maximum of {} \
+                     (see --max-file-size)",
+                    relative_path(file, working_dir).display(),
+                    args.max_file_size
+                );
+            }
+
+            too_large
+        });
+
+    FoundFiles {
+        python: python_files
+            .into_iter()
+            .map(|entry| relative_path(&entry, working_dir))
+            .collect(),
+        other: other_files.len(),
+        too_large: large_files.len(),
+    }
+}
+
+/// Expresses the path relative to the working directory if it is inside it, without any
+/// `./` components.
+pub(crate) fn relative_path(path: &Path, working_dir: &Path) -> PathBuf {
+    path.strip_prefix(working_dir)
+        .unwrap_or(path)
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
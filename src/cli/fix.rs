@@ -0,0 +1,135 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use pystaleds::fixing::{apply_fixes, plan_fixes, unified_diff};
+use pystaleds::rules_checking::{check_header, CheckOptions};
+use pystaleds::stats::pluralize;
+use rayon::prelude::*;
+
+use crate::cli::args::FixArgs;
+use crate::cli::pipeline::FileSettings;
+use crate::cli::Outcome;
+
+/// What is done with the fixes of a file.
+#[derive(Clone, Copy)]
+pub(crate) enum FixMode {
+    /// The file is rewritten with the fixes.
+    Write,
+    /// The changes of the fixes are rendered as a diff, leaving the file untouched.
+    Diff,
+}
+
+/// A file with fixes.
+pub(crate) struct FixedFile {
+    path: PathBuf,
+    /// Number of fixed functions.
+    pub(crate) functions: usize,
+    /// Unified diff of the changes, if they were rendered.
+    diff: String,
+}
+
+/// Fixes the functions of the files whose violations can be fixed, returning the files
+/// with fixes sorted by path, so that their diffs are always in the same order. Files
+/// that cannot be read or written are left for the check to report.
+pub(crate) fn fix_files(
+    files: &[PathBuf],
+    settings: &FileSettings,
+    mode: FixMode,
+) -> Vec<FixedFile> {
+    let mut fixed: Vec<_> = files
+        .par_iter()
+        .filter(|file| !settings.get(file).skip)
+        .filter_map(|file| {
+            fix_file(file, &settings.get(file).options, mode).unwrap_or_else(|error| {
+                tracing::warn!("{}: could not be fixed: {error:#}", file.display());
+                None
+            })
+        })
+        .collect();
+
+    fixed.sort_by(|a, b| a.path.cmp(&b.path));
+
+    fixed
+}
+
+/// Fixes the functions of a file, or `None` if there is nothing to fix. The edits are the
+/// same whatever the mode, so that a diff shows exactly what fixing would do.
+fn fix_file(path: &Path, options: &CheckOptions, mode: FixMode) -> Result<Option<FixedFile>> {
+    let contents = std::fs::read_to_string(path)?;
+
+    // Skipped and generated files are not touched.
+    if check_header(&contents, options).is_err() {
+        return Ok(None);
+    }
+
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_python::language())?;
+
+    let fixes = plan_fixes(&mut parser, &contents, Some(path), options);
+
+    if fixes.is_empty() {
+        return Ok(None);
+    }
+
+    let diff = match mode {
+        FixMode::Write => {
+            std::fs::write(path, apply_fixes(&contents, &fixes))?;
+
+            for fix in &fixes {
+                tracing::debug!(
+                    "{}:{} `{}`: fixed: {}",
+                    path.display(),
+                    fix.row + 1,
+                    fix.function,
+                    fix.title
+                );
+            }
+
+            String::new()
+        }
+        FixMode::Diff => unified_diff(path, &contents, &fixes),
+    };
+
+    Ok(Some(FixedFile {
+        path: path.to_path_buf(),
+        functions: fixes.len(),
+        diff,
+    }))
+}
+
+/// Previews the fixes of the files instead of checking them, printing them as a diff or
+/// writing them as a patch, as asked to.
+pub(crate) fn preview_fixes(
+    files: &[PathBuf],
+    settings: &FileSettings,
+    fixes: &FixArgs,
+) -> Result<Outcome> {
+    let fixed = fix_files(files, settings, FixMode::Diff);
+
+    // Written even if empty, so that a patch of a previous run is not mistaken for one
+    // of this run.
+    if let Some(path) = &fixes.fix_output {
+        let patch: String = fixed.iter().map(|file| file.diff.as_str()).collect();
+
+        std::fs::write(path, patch)
+            .with_context(|| format!("could not write the fixes to {}", path.display()))?;
+    }
+
+    if fixed.is_empty() {
+        return Ok(Outcome::Clean);
+    }
+
+    if fixes.diff {
+        for file in &fixed {
+            print!("{}", file.diff);
+        }
+    }
+
+    let functions: usize = fixed.iter().map(|file| file.functions).sum();
+
+    Ok(Outcome::Errors(Some(format!(
+        "{} would be fixed in {}",
+        pluralize(functions, "function"),
+        pluralize(fixed.len(), "file")
+    ))))
+}
@@ -0,0 +1,713 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::ArgMatches;
+use pystaleds::baseline::{Baseline, BaselineEntry};
+use pystaleds::cancellation::CancellationToken;
+use pystaleds::changes::ChangedLines;
+use pystaleds::config::{self, Config, ConfigResolver};
+use pystaleds::diagnostics::{Diagnostic, Rule, RuleSelection};
+use pystaleds::rules_checking::{
+    check_header, check_source_report, read_header, CheckError, CheckOptions, CompliancyChecker,
+    SourceReport,
+};
+use pystaleds::see_also::{check_see_also, collect_symbols, module_name, SymbolTable};
+use pystaleds::stats::Stats;
+use rayon::prelude::*;
+
+use crate::cli::args::{ReportArgs, RuleArgs};
+use crate::cli::files::directory_of;
+use crate::cli::progress::Progress;
+
+/// Results of checking a file, along with whether it has no functions.
+pub(crate) struct CheckedFile {
+    pub(crate) report: SourceReport,
+    pub(crate) trivial: bool,
+}
+
+impl CheckedFile {
+    /// Records in the stats whether the file has errors, along with its functions.
+    pub(crate) fn record(&self, stats: &mut Stats) {
+        stats.record(
+            self.trivial,
+            !self.report.diagnostics.iter().any(Diagnostic::is_error),
+        );
+        stats.record_functions(&self.report);
+    }
+}
+
+/// Checks a file with a parser, telling whether it is trivial along with its report.
+pub(crate) trait Compliancy {
+    fn check_file(
+        &self,
+        path: &Path,
+        options: &CheckOptions,
+        cancellation: &CancellationToken,
+    ) -> Result<CheckedFile, CheckError>;
+
+    /// Same as [`Compliancy::check_file`], but with the contents of the file given, such
+    /// as an unsaved buffer read from the standard input.
+    fn check_contents(
+        &self,
+        contents: &str,
+        path: &Path,
+        options: &CheckOptions,
+        cancellation: &CancellationToken,
+    ) -> Result<CheckedFile, CheckError>;
+}
+
+impl Compliancy for CompliancyChecker {
+    fn check_file(
+        &self,
+        path: &Path,
+        options: &CheckOptions,
+        cancellation: &CancellationToken,
+    ) -> Result<CheckedFile, CheckError> {
+        tracing::debug!("{}: checking", path.display());
+
+        let io_error = |source| CheckError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        // Skipped files are told apart by their first lines, without reading them whole.
+        let file = File::open(path).map_err(io_error)?;
+        check_header(
+            &read_header(BufReader::new(file)).map_err(io_error)?,
+            options,
+        )?;
+
+        let contents = std::fs::read_to_string(path).map_err(io_error)?;
+
+        self.check_contents(&contents, path, options, cancellation)
+    }
+
+    fn check_contents(
+        &self,
+        contents: &str,
+        path: &Path,
+        options: &CheckOptions,
+        cancellation: &CancellationToken,
+    ) -> Result<CheckedFile, CheckError> {
+        let report = check_source_report(contents, Some(path), *self, options, Some(cancellation))?;
+
+        Ok(CheckedFile {
+            trivial: report.is_trivial(),
+            report,
+        })
+    }
+}
+
+/// Parser and options with which a file is checked.
+pub(crate) struct Settings {
+    pub(crate) parser: CompliancyChecker,
+    pub(crate) options: CheckOptions,
+    /// Whether the file is left unchecked, as configured with `skip = true`.
+    pub(crate) skip: bool,
+}
+
+/// Resolves the settings of the files in a directory from the flags given in the command
+/// line and the configuration files, in that order of precedence.
+pub(crate) struct SettingsResolver {
+    /// Options given in the command line, defaults included.
+    options: CheckOptions,
+    parser: CompliancyChecker,
+    cli_config: Config,
+    /// Configuration given with `--config`, which replaces the discovered ones.
+    explicit_config: Option<Config>,
+    configs: ConfigResolver,
+}
+
+impl SettingsResolver {
+    /// Resolves the settings with the rule options given in the command line, gathered
+    /// into the check options with which the files are checked by default.
+    pub(crate) fn new(
+        rules: &RuleArgs,
+        matches: &ArgMatches,
+        options: CheckOptions,
+    ) -> Result<Self> {
+        Ok(SettingsResolver {
+            options,
+            parser: rules.parser,
+            cli_config: rules.cli_config(matches),
+            explicit_config: rules.config.as_deref().map(config::load).transpose()?,
+            configs: ConfigResolver::default(),
+        })
+    }
+
+    pub(crate) fn resolve(&mut self, directory: &Path) -> Result<Settings> {
+        let config = self.config(directory)?;
+
+        self.settings(config, directory)
+    }
+
+    /// Configuration of the files in a directory, without the flags of the command line.
+    fn config(&mut self, directory: &Path) -> Result<Config> {
+        Ok(match &self.explicit_config {
+            Some(config) => config.clone(),
+            None => self.configs.resolve(directory)?,
+        })
+    }
+
+    /// Settings from a configuration of the files in a directory, on top of which the
+    /// flags of the command line are layered.
+    fn settings(&self, config: Config, directory: &Path) -> Result<Settings> {
+        let config = config.merge(self.cli_config.clone());
+
+        let mut options = self.options.clone();
+        config.apply(&mut options);
+
+        let parser = config.parser.unwrap_or(self.parser);
+        options
+            .validate_for(parser)
+            .with_context(|| format!("cannot check the files in {}", directory.display()))?;
+
+        Ok(Settings {
+            parser,
+            options,
+            skip: config.skip.unwrap_or(false),
+        })
+    }
+}
+
+/// Settings of the checked files, resolved once for each of their directories and each
+/// combination of `per_file` patterns that match some of their files.
+pub(crate) struct FileSettings {
+    settings: Vec<Settings>,
+    /// Index of the settings of each file.
+    by_file: HashMap<PathBuf, usize>,
+}
+
+impl FileSettings {
+    pub(crate) fn resolve(files: &[PathBuf], resolver: &mut SettingsResolver) -> Result<Self> {
+        let mut configs: HashMap<&Path, Config> = HashMap::new();
+        let mut indices: HashMap<(&Path, Vec<usize>), usize> = HashMap::new();
+        let mut settings = Vec::new();
+        let mut by_file = HashMap::new();
+
+        for file in files {
+            let directory = directory_of(file).unwrap_or(file);
+
+            if !configs.contains_key(directory) {
+                configs.insert(directory, resolver.config(directory)?);
+            }
+
+            let config = &configs[directory];
+            let matches = match &config.per_file {
+                Some(per_file) => per_file.matches(file),
+                None => Vec::new(),
+            };
+
+            let index = match indices.get(&(directory, matches.clone())) {
+                Some(&index) => index,
+                None => {
+                    let per_file = config.per_file.as_ref();
+                    let config = matches.iter().fold(config.clone(), |config, &index| {
+                        let (pattern, overrides) = per_file
+                            .expect("matches should come from a per_file")
+                            .get(index);
+                        tracing::debug!("{}: using the `{pattern}` configuration", file.display());
+
+                        config.merge(overrides.clone())
+                    });
+
+                    settings.push(resolver.settings(config, directory)?);
+                    indices.insert((directory, matches), settings.len() - 1);
+
+                    settings.len() - 1
+                }
+            };
+
+            by_file.insert(file.clone(), index);
+        }
+
+        Ok(Self { settings, by_file })
+    }
+
+    /// Settings of a file among the ones they were resolved for.
+    pub(crate) fn get(&self, file: &Path) -> &Settings {
+        &self.settings[self.by_file[file]]
+    }
+
+    /// Whether any of the files is checked with the parser.
+    pub(crate) fn uses(&self, parser: CompliancyChecker) -> bool {
+        self.settings
+            .iter()
+            .any(|settings| !settings.skip && settings.parser == parser)
+    }
+
+    /// Whether any of the files is checked with the options.
+    pub(crate) fn any(&self, predicate: impl Fn(&CheckOptions) -> bool) -> bool {
+        self.settings
+            .iter()
+            .any(|settings| !settings.skip && predicate(&settings.options))
+    }
+}
+
+/// Collects the qualified names defined in the files, in parallel.
+pub(crate) fn collect_symbol_table(files: &[PathBuf]) -> SymbolTable {
+    let symbols = Mutex::new(SymbolTable::default());
+
+    files.par_iter().for_each(|file| {
+        let Ok(contents) = std::fs::read_to_string(file) else {
+            return;
+        };
+
+        let file_symbols = source_symbols(file, &contents);
+
+        symbols
+            .lock()
+            .expect("lock should not be poisoned")
+            .extend(file_symbols);
+    });
+
+    symbols.into_inner().expect("lock should not be poisoned")
+}
+
+/// Collects the symbols defined in the source code of a file, which are none if it is not
+/// in a Python module.
+pub(crate) fn source_symbols(file: &Path, contents: &str) -> SymbolTable {
+    let Some(module) = module_name(file) else {
+        return SymbolTable::default();
+    };
+
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_python::language())
+        .expect("should be able to load Python grammar");
+
+    collect_symbols(&mut parser, contents, &module)
+}
+
+/// Checks the files in parallel, reporting their results, and advancing the progress with
+/// each of them if it is shown.
+pub(crate) fn check_files(
+    files: &[PathBuf],
+    settings: &FileSettings,
+    cancellation: &CancellationToken,
+    reporter: &Reporter,
+    progress: Option<&Progress>,
+) {
+    // The symbols need to be collected from every file before any of them is checked.
+    let symbols = settings
+        .any(|options| options.check_see_also)
+        .then(|| collect_symbol_table(files));
+
+    files.par_iter().for_each(|entry| {
+        assess_success(
+            entry,
+            settings.get(entry),
+            symbols.as_ref(),
+            cancellation,
+            reporter,
+        );
+
+        if let Some(progress) = progress {
+            progress.advance();
+        }
+    });
+}
+
+/// Determines if the file has errors or not, recording it in the stats, or as skipped if it
+/// could not be read.
+pub(crate) fn assess_success(
+    entry: &Path,
+    Settings {
+        parser,
+        options,
+        skip,
+    }: &Settings,
+    symbols: Option<&SymbolTable>,
+    cancellation: &CancellationToken,
+    reporter: &Reporter,
+) {
+    if cancellation.is_cancelled() {
+        return;
+    }
+
+    if *skip {
+        tracing::debug!(
+            "{}: skipped: configured with `skip = true`",
+            entry.display()
+        );
+
+        return reporter.skip(1);
+    }
+
+    let mut file = match parser.check_file(entry, options, cancellation) {
+        Ok(file) => file,
+        Err(CheckError::Cancelled) => return,
+        Err(error @ CheckError::Generated(_)) => {
+            tracing::debug!("{}: skipped: {error}", entry.display());
+
+            return reporter.skip_generated();
+        }
+        Err(error) => {
+            tracing::debug!("{}: skipped: {error:#}", entry.display());
+
+            return reporter.skip(1);
+        }
+    };
+
+    if let Some(symbols) = symbols.filter(|_| options.check_see_also) {
+        let Ok(see_also_diagnostics) = check_file_see_also(entry, symbols, &options.rules) else {
+            return reporter.skip(1);
+        };
+
+        file.report.diagnostics.extend(see_also_diagnostics);
+    }
+
+    reporter.report(file);
+}
+
+/// How the violations found are compared with a baseline.
+pub(crate) enum BaselineMode {
+    /// Violations in the baseline are left out of the reports.
+    Filter(Baseline),
+    /// Violations are recorded to be written as the baseline, and left out of the reports.
+    Write,
+}
+
+/// Violations left out of the reports by the baseline.
+#[derive(Default)]
+pub(crate) struct BaselineOutcome {
+    /// Number of violations in the baseline that were found.
+    pub(crate) known: usize,
+    /// Entries of the baseline that matched fewer violations than they count.
+    pub(crate) stale: Vec<BaselineEntry>,
+    /// Violations found, when writing the baseline.
+    pub(crate) recorded: Vec<BaselineEntry>,
+}
+
+/// Lines changed since the ref given with `--diff-against`, to which the violations are
+/// narrowed down.
+pub(crate) struct ChangeFilter {
+    lines: ChangedLines,
+    /// Whether the violations of the functions that were not touched are still reported,
+    /// as warnings.
+    report_untouched: bool,
+}
+
+impl ChangeFilter {
+    pub(crate) fn new(report: &ReportArgs) -> Result<Option<Self>> {
+        let Some(reference) = &report.diff_against else {
+            return Ok(None);
+        };
+
+        let lines = changed_lines(reference)
+            .with_context(|| format!("could not find the changes since `{reference}`"))?;
+
+        Ok(Some(ChangeFilter {
+            lines,
+            report_untouched: report.report_untouched,
+        }))
+    }
+}
+
+/// Finds the lines changed since a git ref in the repository of the current directory
+/// with `git diff`, counting the files that git does not track as changed whole.
+fn changed_lines(reference: &str) -> Result<ChangedLines> {
+    let root = git(&["rev-parse", "--show-toplevel"])?;
+    // Canonicalized as the checked files are, since git may not resolve every link.
+    let root = std::fs::canonicalize(root.trim_end())?;
+
+    let diff = git(&[
+        "-c",
+        "core.quotePath=false",
+        "diff",
+        "--no-ext-diff",
+        "--no-color",
+        "-U0",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+        "--end-of-options",
+        reference,
+        "--",
+    ])?;
+    let mut lines = ChangedLines::parse(&diff, &root);
+
+    let untracked = git(&[
+        "ls-files",
+        "--others",
+        "--exclude-standard",
+        "--full-name",
+        "-z",
+        "--",
+        ":/",
+    ])?;
+
+    for path in untracked.split('\0').filter(|path| !path.is_empty()) {
+        lines.add_file(root.join(path));
+    }
+
+    Ok(lines)
+}
+
+/// Runs git with the arguments in the current directory, returning what it printed.
+fn git(args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .output()
+        .context("could not run git")?;
+
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    String::from_utf8(output.stdout)
+        .with_context(|| format!("the output of `git {}` is not valid UTF-8", args.join(" ")))
+}
+
+/// Gathers the results of the checked files, which may come in any order, to be
+/// rendered once all of them are checked.
+#[derive(Default)]
+pub(crate) struct Reporter {
+    stats: Mutex<Stats>,
+    files: Mutex<Vec<CheckedFile>>,
+    baseline: Option<BaselineMode>,
+    baseline_outcome: Mutex<BaselineOutcome>,
+    changes: Option<ChangeFilter>,
+    /// Number of violations in functions not touched since `--diff-against`.
+    untouched: Mutex<usize>,
+}
+
+impl Reporter {
+    pub(crate) fn new(baseline: Option<BaselineMode>, changes: Option<ChangeFilter>) -> Self {
+        Reporter {
+            baseline,
+            changes,
+            ..Default::default()
+        }
+    }
+
+    /// Reports the results of a file, recording in the stats whether it has errors.
+    pub(crate) fn report(&self, mut file: CheckedFile) {
+        if let Some(mode) = &self.baseline {
+            let mut outcome = self
+                .baseline_outcome
+                .lock()
+                .expect("lock should not be poisoned");
+
+            match mode {
+                BaselineMode::Filter(baseline) => {
+                    let filtered = baseline.filter(&mut file.report);
+                    outcome.known += filtered.known;
+                    outcome.stale.extend(filtered.stale);
+                }
+                BaselineMode::Write => {
+                    let found = Baseline::from_reports(std::slice::from_ref(&file.report));
+                    outcome.recorded.extend(found.entries().cloned());
+                    file.report.diagnostics.clear();
+                }
+            }
+        }
+
+        // Narrowed down after the baseline, so that its entries of the functions that were
+        // not touched are not taken as stale.
+        if let Some(changes) = &self.changes {
+            let path = file
+                .report
+                .path
+                .as_deref()
+                .and_then(|path| std::fs::canonicalize(path).ok())
+                .unwrap_or_default();
+            let untouched = changes
+                .lines
+                .filter(&mut file.report, &path, changes.report_untouched);
+
+            *self.untouched.lock().expect("lock should not be poisoned") += untouched;
+        }
+
+        file.record(&mut self.stats.lock().expect("lock should not be poisoned"));
+
+        self.files
+            .lock()
+            .expect("lock should not be poisoned")
+            .push(file);
+    }
+
+    /// Records the entries of the baseline of the files other than the checked ones as
+    /// stale, if those files no longer exist or `whole_tree` was checked, since no run
+    /// would match them then.
+    pub(crate) fn unvisited(&self, files: &[PathBuf], whole_tree: bool) {
+        let Some(BaselineMode::Filter(baseline)) = &self.baseline else {
+            return;
+        };
+
+        let stale = baseline
+            .unvisited(files)
+            .into_iter()
+            .filter(|entry| whole_tree || !Path::new(&entry.path).exists())
+            .cloned();
+
+        self.baseline_outcome
+            .lock()
+            .expect("lock should not be poisoned")
+            .stale
+            .extend(stale);
+    }
+
+    /// Number of violations left out (or reported as warnings) so far, since they are in
+    /// functions not touched since `--diff-against`.
+    pub(crate) fn untouched(&self) -> usize {
+        *self.untouched.lock().expect("lock should not be poisoned")
+    }
+
+    /// Records files that were not checked in the stats.
+    pub(crate) fn skip(&self, count: usize) {
+        self.stats
+            .lock()
+            .expect("lock should not be poisoned")
+            .skipped_files += count;
+    }
+
+    /// Records files larger than `--max-file-size`, which were not checked, in the stats.
+    pub(crate) fn skip_too_large(&self, count: usize) {
+        let mut stats = self.stats.lock().expect("lock should not be poisoned");
+        stats.skipped_files += count;
+        stats.too_large_files += count;
+    }
+
+    /// Records a generated file, which was not checked, in the stats.
+    pub(crate) fn skip_generated(&self) {
+        let mut stats = self.stats.lock().expect("lock should not be poisoned");
+        stats.skipped_files += 1;
+        stats.generated_files += 1;
+    }
+
+    /// Returns the checked files sorted by path (with their diagnostics sorted by row), so
+    /// that the output is the same across runs, along with the stats and what was left
+    /// out by the baseline.
+    pub(crate) fn finish(self) -> (Vec<CheckedFile>, Stats, BaselineOutcome) {
+        let mut files = self
+            .files
+            .into_inner()
+            .expect("lock should not be poisoned");
+
+        files.sort_by(|a, b| a.report.path.cmp(&b.report.path));
+        files.iter_mut().for_each(|file| file.report.sort());
+
+        let stats = self
+            .stats
+            .into_inner()
+            .expect("lock should not be poisoned");
+
+        let mut baseline = self
+            .baseline_outcome
+            .into_inner()
+            .expect("lock should not be poisoned");
+        baseline.stale.sort();
+
+        (files, stats, baseline)
+    }
+}
+
+/// Checks the references in the "See Also" sections of a file against the symbols
+/// collected from all checked files, unless the rule is not selected.
+fn check_file_see_also(
+    path: &Path,
+    symbols: &SymbolTable,
+    rules: &RuleSelection,
+) -> Result<Vec<Diagnostic>> {
+    if !rules.enables(Rule::StaleSeeAlso) {
+        return Ok(Vec::new());
+    }
+
+    let contents = std::fs::read_to_string(path)?;
+
+    check_source_see_also(path, &contents, symbols, rules)
+}
+
+/// Same as [`check_file_see_also`], but with the contents of the file given.
+pub(crate) fn check_source_see_also(
+    path: &Path,
+    contents: &str,
+    symbols: &SymbolTable,
+    rules: &RuleSelection,
+) -> Result<Vec<Diagnostic>> {
+    if !rules.enables(Rule::StaleSeeAlso) {
+        return Ok(Vec::new());
+    }
+
+    let Some(module) = module_name(path) else {
+        return Ok(Vec::new());
+    };
+
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_python::language())?;
+
+    Ok(rules.apply(check_see_also(
+        &mut parser,
+        contents,
+        Some(path),
+        &module,
+        symbols,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use pystaleds::human::HumanRenderer;
+
+    use crate::cli::files::python_files;
+    use crate::Args;
+
+    use super::*;
+
+    #[test]
+    fn test_stable_order() {
+        let command = Args::parse_from(["pystaleds", "test_folder"]).check;
+        let files = python_files(Path::new("test_folder"), &command.files)
+            .unwrap()
+            .python;
+
+        let mut resolver = SettingsResolver {
+            options: command.check_options(),
+            parser: command.rules.parser,
+            cli_config: Config::default(),
+            explicit_config: None,
+            configs: ConfigResolver::default(),
+        };
+        let settings = FileSettings::resolve(&files, &mut resolver).unwrap();
+
+        let run = || {
+            let reporter = Reporter::default();
+            check_files(
+                &files,
+                &settings,
+                &CancellationToken::new(),
+                &reporter,
+                None,
+            );
+            let (files, _, _) = reporter.finish();
+            let reports: Vec<_> = files.into_iter().map(|file| file.report).collect();
+
+            let paths: Vec<_> = reports.iter().map(|report| report.path.clone()).collect();
+            let mut sorted_paths = paths.clone();
+            sorted_paths.sort();
+            assert_eq!(paths, sorted_paths);
+
+            reports
+                .iter()
+                .filter_map(|report| HumanRenderer::new(false).render(report))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let output = run();
+
+        assert!(!output.is_empty());
+        assert_eq!(output, run());
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Time between redraws of the progress bar, which is also how long a run takes before it
+/// is first drawn, so that quick runs do not flash it.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Number of characters of the bar itself, between its brackets.
+const BAR_WIDTH: usize = 30;
+
+/// Bar with the number of files checked out of the total, drawn in stderr while the
+/// files are checked in parallel and cleared once they are.
+pub(crate) struct Progress {
+    total: usize,
+    done: AtomicUsize,
+    /// When the bar was last drawn, or when the progress started if it was not yet.
+    drawn_at: Mutex<Instant>,
+    shown: AtomicBool,
+}
+
+impl Progress {
+    pub(crate) fn new(total: usize) -> Self {
+        Progress {
+            total,
+            done: AtomicUsize::new(0),
+            drawn_at: Mutex::new(Instant::now()),
+            shown: AtomicBool::new(false),
+        }
+    }
+
+    /// Records a file as processed, redrawing the bar unless it was drawn too recently.
+    pub(crate) fn advance(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+
+        // Another worker is drawing it, which is as good.
+        let Ok(mut drawn_at) = self.drawn_at.try_lock() else {
+            return;
+        };
+
+        if drawn_at.elapsed() < REDRAW_INTERVAL {
+            return;
+        }
+
+        *drawn_at = Instant::now();
+        self.shown.store(true, Ordering::Relaxed);
+
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(stderr, "\r{}", bar(done, self.total));
+        let _ = stderr.flush();
+    }
+
+    /// Clears the bar, if it was drawn, so that what is printed next starts in its line.
+    pub(crate) fn finish(&self) {
+        if self.shown.load(Ordering::Relaxed) {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+/// Renders the bar of `done` out of `total` files, as in `[=====>    ] 5/10 files`.
+fn bar(done: usize, total: usize) -> String {
+    let filled = (done * BAR_WIDTH).checked_div(total).unwrap_or(BAR_WIDTH);
+    let bar = if filled >= BAR_WIDTH {
+        "=".repeat(BAR_WIDTH)
+    } else {
+        format!(
+            "{}>{}",
+            "=".repeat(filled),
+            " ".repeat(BAR_WIDTH - filled - 1)
+        )
+    };
+
+    format!("[{bar}] {done}/{total} files")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendering() {
+        assert_eq!(bar(0, 10), format!("[>{}] 0/10 files", " ".repeat(29)));
+        assert_eq!(
+            bar(5, 10),
+            format!("[{}>{}] 5/10 files", "=".repeat(15), " ".repeat(14))
+        );
+        assert_eq!(bar(10, 10), format!("[{}] 10/10 files", "=".repeat(30)));
+        assert_eq!(bar(0, 0), format!("[{}] 0/0 files", "=".repeat(30)));
+    }
+}
@@ -0,0 +1,147 @@
+use std::path::Path;
+
+use anyhow::Result;
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use pystaleds::config::{self, Config};
+use pystaleds::output::OutputFormat;
+
+use crate::cli::args::{RulesCommand, ShowConfigCommand};
+use crate::cli::files::directory_of;
+use crate::cli::pipeline::SettingsResolver;
+use crate::cli::Outcome;
+use crate::ENV_PREFIX;
+
+/// Lists every rule with its code, whether it is enforced for the files in the path and
+/// its default severity.
+pub(crate) fn list_rules(command: &RulesCommand, matches: &ArgMatches) -> Result<Outcome> {
+    let mut resolver =
+        SettingsResolver::new(&command.rules, matches, command.rules.check_options())?;
+    let settings = resolver.resolve(directory(command.path.as_deref()))?;
+
+    println!("{}", command.format.render_rules(&settings.options));
+
+    Ok(Outcome::Clean)
+}
+
+/// Prints the configuration with which the files in the path would be checked.
+pub(crate) fn show_config(command: &ShowConfigCommand, matches: &ArgMatches) -> Result<Outcome> {
+    let directory = directory(command.path.as_deref());
+
+    println!("{}", render_config(command, matches, directory)?);
+
+    Ok(Outcome::Clean)
+}
+
+/// Directory whose configuration applies to the path, which is the current one by
+/// default.
+fn directory(path: Option<&str>) -> &Path {
+    path.map_or(Path::new("."), |path| {
+        directory_of(Path::new(path)).unwrap_or(Path::new(path))
+    })
+}
+
+/// Renders the configuration of the files in a directory, annotating each value with
+/// where it comes from: the command line, the environment, a configuration file or the
+/// defaults, in that order of precedence.
+fn render_config(
+    command: &ShowConfigCommand,
+    matches: &ArgMatches,
+    directory: &Path,
+) -> Result<String> {
+    let files = match &command.rules.config {
+        Some(path) => vec![(path.clone(), config::load(path)?)],
+        None => config::chain(directory)?,
+    };
+
+    let configured = files.iter().fold(
+        command.rules.config_where(|_| true),
+        |configured, (_, config)| configured.merge(config.clone()),
+    );
+
+    // A file also gets the configurations of the `per_file` patterns that match it.
+    let target = command
+        .path
+        .as_deref()
+        .map(Path::new)
+        .filter(|path| path.is_file());
+    let overrides: Vec<_> = match (&configured.per_file, target) {
+        (Some(per_file), Some(file)) => per_file
+            .matches(file)
+            .into_iter()
+            .map(|index| per_file.get(index))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let per_file_path = files
+        .iter()
+        .rev()
+        .find(|(_, config)| config.per_file.is_some())
+        .map(|(path, _)| path.display());
+
+    let effective = overrides
+        .iter()
+        .fold(configured.clone(), |effective, (_, config)| {
+            effective.merge((*config).clone())
+        })
+        .merge(command.rules.cli_config(matches));
+    let values = toml::Table::try_from(&effective)?;
+
+    // Keys such as `per_file` have no flag, so they can only come from the files.
+    let given = |key: &str| matches.ids().any(|id| id == key);
+    let source = |key: &str| match given(key).then(|| matches.value_source(key)).flatten() {
+        Some(ValueSource::CommandLine) => "command line".to_string(),
+        Some(ValueSource::EnvVariable) => {
+            format!("environment ({ENV_PREFIX}{})", key.to_uppercase())
+        }
+        _ => match overrides
+            .iter()
+            .rev()
+            .find(|(_, config)| config.set_keys().contains(&key))
+        {
+            Some((pattern, _)) => format!(
+                "{} (per_file `{pattern}`)",
+                per_file_path
+                    .as_ref()
+                    .expect("per_file should come from a file")
+            ),
+            None => files
+                .iter()
+                .rev()
+                .find(|(_, config)| config.set_keys().contains(&key))
+                .map_or("default".to_string(), |(path, _)| {
+                    path.display().to_string()
+                }),
+        },
+    };
+
+    let entries = Config::KEYS
+        .iter()
+        .filter_map(|&key| Some((key, values.get(key)?, source(key))));
+
+    if command.format == OutputFormat::Json {
+        let json: serde_json::Map<_, _> = entries
+            .map(|(key, value, source)| {
+                (
+                    key.to_string(),
+                    serde_json::json!({ "value": value, "source": source }),
+                )
+            })
+            .collect();
+
+        return Ok(serde_json::Value::Object(json).to_string());
+    }
+
+    let lines: Vec<_> = entries
+        .map(|(key, value, source)| format!("{key} = {value}  # {source}"))
+        .collect();
+
+    Ok(format!(
+        "# Configuration of {}\n{}",
+        target.map_or_else(
+            || format!("the files in {}", directory.display()),
+            |file| file.display().to_string()
+        ),
+        lines.join("\n")
+    ))
+}
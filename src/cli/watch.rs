@@ -0,0 +1,275 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env::current_dir,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, RecvTimeoutError},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use notify::{RecursiveMode, Watcher};
+use pystaleds::baseline::Baseline;
+use pystaleds::cancellation::CancellationToken;
+use pystaleds::human::HumanRenderer;
+use pystaleds::stats::Stats;
+use rayon::prelude::*;
+
+use crate::cli::args::{CheckCommand, FileArgs, ReportArgs};
+use crate::cli::files::{
+    found_files, has_default_excluded_component, has_hidden_component, is_globbed, parse_globs,
+    relative_path, GlobPattern,
+};
+use crate::cli::pipeline::{
+    assess_success, collect_symbol_table, BaselineMode, ChangeFilter, CheckedFile, FileSettings,
+    Reporter, SettingsResolver,
+};
+
+/// Time without further changes after which the changed files are checked again, so that
+/// the several events of a single save, or of saving several files at once, lead to a
+/// single check.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Keeps watching the checked files after the initial run, checking again the ones that
+/// are changed or created and printing their errors along with the updated summary, until
+/// cancelled.
+///
+/// With a root, the files are the ones that walking through (or globbing) it would find,
+/// so that new files are checked too. Otherwise, they are the initial files, which are
+/// checked again if they are created after being deleted. Files that had errors and no
+/// longer do, including the deleted ones, are marked as resolved. Skipped files are
+/// counted as in the initial run.
+pub(crate) fn watch(
+    command: &CheckCommand,
+    resolver: &mut SettingsResolver,
+    files: &[PathBuf],
+    root: Option<&Path>,
+    checked: Vec<CheckedFile>,
+    initial_stats: &Stats,
+    cancellation: &CancellationToken,
+) -> Result<()> {
+    let working_dir = current_dir()?;
+    let globs = parse_globs(&command.files.glob)?;
+    let renderer = HumanRenderer::new(command.report.color.enabled());
+
+    let mut checked: BTreeMap<PathBuf, CheckedFile> = checked
+        .into_iter()
+        .filter_map(|file| Some((file.report.path.clone()?, file)))
+        .collect();
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).context("could not watch the files")?;
+
+    let watched: BTreeSet<(&Path, RecursiveMode)> = match root {
+        Some(root) => BTreeSet::from([(root, RecursiveMode::Recursive)]),
+        // Editors often save a file by replacing it, which would end the watch of the file
+        // itself, so its folder is watched instead.
+        None => files
+            .iter()
+            .map(|file| {
+                let folder = file
+                    .parent()
+                    .filter(|parent| !parent.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."));
+
+                (folder, RecursiveMode::NonRecursive)
+            })
+            .collect(),
+    };
+
+    for (path, mode) in watched {
+        watcher
+            .watch(path, mode)
+            .with_context(|| format!("could not watch {}", path.display()))?;
+    }
+
+    tracing::info!("watching for changes, press Ctrl-C to stop");
+
+    let is_watched = |file: &Path| match root {
+        Some(root) => is_found_file(file, root, &globs, &command.files, &working_dir),
+        None => files.iter().any(|watched| watched == file) && file.is_file(),
+    };
+
+    while !cancellation.is_cancelled() {
+        // Waits for a short while at a time, so that an interruption is noticed.
+        let event = match receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        // The events that follow are gathered until there are none for a while, so that
+        // rapid successive saves are checked once. Reading the files, as checking them
+        // does, is not a change.
+        let events = std::iter::once(event)
+            .chain(std::iter::from_fn(|| {
+                receiver.recv_timeout(WATCH_DEBOUNCE).ok()
+            }))
+            .filter_map(|event| {
+                event
+                    .inspect_err(|error| tracing::warn!("could not watch some changes: {error}"))
+                    .ok()
+            });
+
+        let changed: BTreeSet<PathBuf> = events
+            .filter(|event| !event.kind.is_access())
+            .flat_map(|event| event.paths)
+            .map(|path| relative_path(&path, &working_dir))
+            .collect();
+        let start = Instant::now();
+
+        let to_check: Vec<PathBuf> = changed
+            .iter()
+            .filter(|file| is_watched(file))
+            .cloned()
+            .collect();
+
+        // A deleted folder may only be reported by itself, instead of by each of its files.
+        let gone: Vec<PathBuf> = checked
+            .keys()
+            .filter(|file| {
+                changed.iter().any(|path| file.starts_with(path))
+                    && !to_check.contains(file)
+                    && !is_watched(file)
+            })
+            .cloned()
+            .collect();
+
+        if to_check.is_empty() && gone.is_empty() {
+            continue;
+        }
+
+        let had_errors: BTreeSet<PathBuf> = to_check
+            .iter()
+            .chain(&gone)
+            .filter(|file| {
+                checked
+                    .remove(*file)
+                    .is_some_and(|file| !file.report.diagnostics.is_empty())
+            })
+            .cloned()
+            .collect();
+
+        let rechecked = match recheck(&command.report, resolver, &to_check, &checked, cancellation)
+        {
+            Ok(rechecked) => rechecked,
+            Err(error) => {
+                tracing::error!("{error:#}");
+                continue;
+            }
+        };
+
+        if cancellation.is_cancelled() {
+            break;
+        }
+
+        for file in rechecked {
+            if let Some(rendered) = renderer.render(&file.report) {
+                println!("{rendered}");
+            }
+
+            if let Some(path) = file.report.path.clone() {
+                checked.insert(path, file);
+            }
+        }
+
+        for file in had_errors {
+            let resolved = checked
+                .get(&file)
+                .is_none_or(|file| file.report.diagnostics.is_empty());
+
+            if resolved {
+                println!("{}", renderer.resolved(&file));
+            }
+        }
+
+        let mut stats = Stats {
+            skipped_files: initial_stats.skipped_files,
+            generated_files: initial_stats.generated_files,
+            too_large_files: initial_stats.too_large_files,
+            ..Default::default()
+        };
+        checked.values().for_each(|file| file.record(&mut stats));
+
+        println!(
+            "{}",
+            renderer.summary(
+                &stats,
+                command.report.count_trivial_files,
+                false,
+                start.elapsed()
+            )
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks again the files changed while watching, along with the symbols of all the
+/// watched files if the "See Also" sections are checked.
+fn recheck(
+    report: &ReportArgs,
+    resolver: &mut SettingsResolver,
+    files: &[PathBuf],
+    checked: &BTreeMap<PathBuf, CheckedFile>,
+    cancellation: &CancellationToken,
+) -> Result<Vec<CheckedFile>> {
+    let settings = FileSettings::resolve(files, resolver)?;
+
+    // Loaded again, in case it was written since the initial run.
+    let baseline = report
+        .baseline
+        .as_deref()
+        .map(Baseline::load)
+        .transpose()?
+        .map(BaselineMode::Filter);
+    let reporter = Reporter::new(baseline, ChangeFilter::new(report)?);
+
+    let symbols = settings.any(|options| options.check_see_also).then(|| {
+        let all: BTreeSet<PathBuf> = checked.keys().chain(files).cloned().collect();
+
+        collect_symbol_table(&all.into_iter().collect::<Vec<_>>())
+    });
+
+    files.par_iter().for_each(|file| {
+        assess_success(
+            file,
+            settings.get(file),
+            symbols.as_ref(),
+            cancellation,
+            &reporter,
+        )
+    });
+
+    let (rechecked, _, _) = reporter.finish();
+
+    Ok(rechecked)
+}
+
+/// Determines if a file is among the Python files that walking through (or globbing) the
+/// root finds, as when it changes while watching them.
+fn is_found_file(
+    file: &Path,
+    root: &Path,
+    globs: &[GlobPattern],
+    args: &FileArgs,
+    working_dir: &Path,
+) -> bool {
+    let root = relative_path(root, working_dir);
+    let relative = file.strip_prefix(&root).unwrap_or(file);
+
+    if !args.allow_hidden && has_hidden_component(relative) {
+        return false;
+    }
+
+    if !args.no_default_excludes && has_default_excluded_component(relative) {
+        return false;
+    }
+
+    if !globs.is_empty() && !is_globbed(relative, globs) {
+        return false;
+    }
+
+    !found_files(vec![file.to_path_buf()], &root, args, working_dir)
+        .python
+        .is_empty()
+}
@@ -0,0 +1,765 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex::Regex;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::diagnostics::RuleSelector;
+use crate::rules_checking::{CheckOptions, CompliancyChecker, DocstringStyle, FunctionSelection};
+
+/// Name of the standalone configuration file.
+pub const CONFIG_FILE: &str = "pystaleds.toml";
+
+/// Name of the packaging file whose `[tool.pystaleds]` table is used as the configuration
+/// if there is no [`CONFIG_FILE`].
+pub const PYPROJECT_FILE: &str = "pyproject.toml";
+
+/// Error of a configuration file that could not be loaded.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("could not read {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    /// The file is not valid TOML or does not follow the schema of [`Config`], e.g. a key
+    /// has a value of the wrong type. The source tells where in the file.
+    #[error("could not parse {}", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    /// The file has a key that is not one of [`Config::KEYS`], along with the most similar
+    /// one, if any is similar enough to be a typo.
+    #[error(
+        "unknown key `{key}` in {}{}{}",
+        path.display(),
+        line.map(|line| format!(" at line {line}")).unwrap_or_default(),
+        suggestion.map(|key| format!(", did you mean `{key}`?")).unwrap_or_default()
+    )]
+    UnknownKey {
+        path: PathBuf,
+        key: String,
+        line: Option<usize>,
+        suggestion: Option<&'static str>,
+    },
+}
+
+/// Declares the keys of [`Config`], which are all optional so that configurations can be
+/// layered with [`Config::merge`].
+macro_rules! config {
+    ($($(#[$attribute:meta])* $key:ident: $type:ty,)*) => {
+        /// Options read from a configuration file, each with the name of the flag of the
+        /// same meaning (with underscores instead of dashes), e.g.
+        /// `forbid_no_docstring = true` or `docstyle = "numpy"`. Keys that are not set keep
+        /// the value given by the command line or its default.
+        #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        pub struct Config {
+            $(
+                $(#[$attribute])*
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                pub $key: Option<$type>,
+            )*
+        }
+
+        impl Config {
+            /// Keys of the configuration, in the order they are declared.
+            pub const KEYS: &'static [&'static str] = &[$(stringify!($key),)*];
+
+            /// Keys that are set, in the order they are declared.
+            pub fn set_keys(&self) -> Vec<&'static str> {
+                let mut keys = Vec::new();
+                $(
+                    if self.$key.is_some() {
+                        keys.push(stringify!($key));
+                    }
+                )*
+                keys
+            }
+
+            /// Layers another configuration on top of this one, key by key.
+            pub fn merge(self, other: Config) -> Config {
+                Config {
+                    $($key: other.$key.or(self.$key),)*
+                }
+            }
+        }
+    };
+}
+
+config! {
+    parser: CompliancyChecker,
+    docstyle: DocstringStyle,
+    break_on_empty_line: bool,
+    forbid_no_docstring: bool,
+    forbid_no_args_in_docstring: bool,
+    forbid_untyped_docstrings: bool,
+    include_args_and_kwargs: bool,
+    forbid_style_mismatch: bool,
+    check_examples_style: bool,
+    forbid_missing_returns: bool,
+    check_return_type: bool,
+    check_see_also: bool,
+    check_raises: bool,
+    forbid_missing_yields: bool,
+    check_yield_type: bool,
+    check_attributes: bool,
+    init_args_in_class_docstring: bool,
+    forbid_no_module_docstring: bool,
+    forbid_empty_modules: bool,
+    include_trivial_init: bool,
+    forbid_placeholders: bool,
+    placeholders: Vec<String>,
+    forbid_empty_descriptions: bool,
+    check_default_mentions: bool,
+    #[serde(deserialize_with = "regex", serialize_with = "regex_pattern")]
+    default_mention_pattern: Regex,
+    check_optional_consistency: bool,
+    allow_out_of_order_args: bool,
+    check_section_order: bool,
+    section_order: Vec<String>,
+    forbid_missing_summary: bool,
+    allow_documented_self: bool,
+    require_full_typing: bool,
+    allow_omitting_defaulted_args: bool,
+    allow_kwargs_docs: bool,
+    skip_stub_bodies: bool,
+    skip_generated: bool,
+    generated_markers: Vec<String>,
+    ignore_param_case: bool,
+    min_function_lines: usize,
+    ignore_private: bool,
+    ignore_dunder: bool,
+    skip_decorated: Vec<String>,
+    #[serde(deserialize_with = "regexes", serialize_with = "regex_patterns")]
+    exclude_functions: Vec<Regex>,
+    ignore_params: Vec<String>,
+    only: FunctionSelection,
+    select: Vec<RuleSelector>,
+    ignore: Vec<RuleSelector>,
+    warn: Vec<RuleSelector>,
+    skip: bool,
+    per_file: PerFile,
+}
+
+impl Config {
+    /// Sets the options for the keys that are set. `skip` and `per_file` are not options
+    /// of the check, but decide which configuration each file gets.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pystaleds::config::Config;
+    /// use pystaleds::rules_checking::CheckOptions;
+    ///
+    /// let config: Config = toml::from_str("forbid_no_docstring = true").unwrap();
+    ///
+    /// let mut options = CheckOptions::default();
+    /// config.apply(&mut options);
+    ///
+    /// assert!(!options.succeed_if_no_docstring);
+    /// ```
+    pub fn apply(&self, options: &mut CheckOptions) {
+        fn set<T: Clone>(option: &mut T, value: &Option<T>) {
+            if let Some(value) = value {
+                *option = value.clone();
+            }
+        }
+
+        fn unset(option: &mut bool, value: &Option<bool>) {
+            if let Some(value) = value {
+                *option = !value;
+            }
+        }
+
+        set(&mut options.docstyle, &self.docstyle);
+        set(&mut options.break_on_empty_line, &self.break_on_empty_line);
+        unset(
+            &mut options.succeed_if_no_docstring,
+            &self.forbid_no_docstring,
+        );
+        unset(
+            &mut options.succeed_if_no_args_in_docstring,
+            &self.forbid_no_args_in_docstring,
+        );
+        unset(
+            &mut options.succeed_if_docstrings_are_not_typed,
+            &self.forbid_untyped_docstrings,
+        );
+        unset(
+            &mut options.skip_args_and_kwargs,
+            &self.include_args_and_kwargs,
+        );
+        set(
+            &mut options.forbid_style_mismatch,
+            &self.forbid_style_mismatch,
+        );
+        set(
+            &mut options.check_examples_style,
+            &self.check_examples_style,
+        );
+        set(
+            &mut options.forbid_missing_returns,
+            &self.forbid_missing_returns,
+        );
+        set(&mut options.check_return_type, &self.check_return_type);
+        set(&mut options.check_see_also, &self.check_see_also);
+        set(&mut options.check_raises, &self.check_raises);
+        set(
+            &mut options.forbid_missing_yields,
+            &self.forbid_missing_yields,
+        );
+        set(&mut options.check_yield_type, &self.check_yield_type);
+        set(&mut options.check_attributes, &self.check_attributes);
+        set(
+            &mut options.init_args_in_class_docstring,
+            &self.init_args_in_class_docstring,
+        );
+        set(
+            &mut options.forbid_no_module_docstring,
+            &self.forbid_no_module_docstring,
+        );
+        unset(
+            &mut options.succeed_if_empty_module,
+            &self.forbid_empty_modules,
+        );
+        unset(&mut options.skip_trivial_init, &self.include_trivial_init);
+        set(&mut options.forbid_placeholders, &self.forbid_placeholders);
+        set(&mut options.placeholders, &self.placeholders);
+        set(
+            &mut options.forbid_empty_descriptions,
+            &self.forbid_empty_descriptions,
+        );
+        set(
+            &mut options.check_default_mentions,
+            &self.check_default_mentions,
+        );
+        set(
+            &mut options.default_mention_pattern,
+            &self.default_mention_pattern,
+        );
+        set(
+            &mut options.check_optional_consistency,
+            &self.check_optional_consistency,
+        );
+        set(
+            &mut options.allow_out_of_order_args,
+            &self.allow_out_of_order_args,
+        );
+        set(&mut options.check_section_order, &self.check_section_order);
+        set(&mut options.section_order, &self.section_order);
+        set(
+            &mut options.forbid_missing_summary,
+            &self.forbid_missing_summary,
+        );
+        set(
+            &mut options.allow_documented_self,
+            &self.allow_documented_self,
+        );
+        set(&mut options.require_full_typing, &self.require_full_typing);
+        set(
+            &mut options.allow_omitting_defaulted_args,
+            &self.allow_omitting_defaulted_args,
+        );
+        set(&mut options.allow_kwargs_docs, &self.allow_kwargs_docs);
+        set(&mut options.skip_stub_bodies, &self.skip_stub_bodies);
+        set(&mut options.skip_generated, &self.skip_generated);
+        set(&mut options.generated_markers, &self.generated_markers);
+        set(&mut options.ignore_param_case, &self.ignore_param_case);
+        set(&mut options.min_function_lines, &self.min_function_lines);
+        set(&mut options.ignore_private, &self.ignore_private);
+        set(&mut options.ignore_dunder, &self.ignore_dunder);
+        set(&mut options.skip_decorated, &self.skip_decorated);
+        set(&mut options.exclude_functions, &self.exclude_functions);
+        set(&mut options.ignore_params, &self.ignore_params);
+        set(&mut options.only, &self.only);
+        set(&mut options.rules.select, &self.select);
+        set(&mut options.rules.ignore, &self.ignore);
+        set(&mut options.rules.warn, &self.warn);
+    }
+}
+
+/// Configurations of the files that match glob patterns, relative to the directory of
+/// the configuration file that has them, e.g.
+///
+/// ```toml
+/// [per_file]
+/// "tests/**" = { forbid_no_docstring = false }
+/// "**/__init__.py" = { skip = true }
+/// ```
+///
+/// They are layered on top of the configuration of the directory of each file, from the
+/// least to the most specific matching pattern, so that the more specific one wins.
+#[derive(Debug, Clone)]
+pub struct PerFile {
+    /// Directory that the patterns are relative to, which is only known once the file is
+    /// loaded.
+    root: PathBuf,
+    /// Patterns and their configurations, sorted by [`specificity`].
+    overrides: Vec<(String, Config)>,
+    globs: GlobSet,
+}
+
+impl PerFile {
+    /// Indices of the patterns that match a file, from the least to the most specific.
+    pub fn matches(&self, file: &Path) -> Vec<usize> {
+        // Files that do not exist, such as unsaved buffers, are resolved through their
+        // folder.
+        let absolute = file.canonicalize().ok().or_else(|| {
+            let folder = file
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+
+            Some(folder.canonicalize().ok()?.join(file.file_name()?))
+        });
+        let relative =
+            absolute.and_then(|file| Some(file.strip_prefix(&self.root).ok()?.to_path_buf()));
+
+        let Some(relative) = relative else {
+            return Vec::new();
+        };
+
+        let mut matches = self.globs.matches(relative);
+        matches.sort_unstable();
+
+        matches
+    }
+
+    /// Pattern and configuration of a match.
+    pub fn get(&self, index: usize) -> (&str, &Config) {
+        let (pattern, config) = &self.overrides[index];
+
+        (pattern, config)
+    }
+}
+
+impl Serialize for PerFile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.overrides
+                .iter()
+                .map(|(pattern, config)| (pattern, config)),
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for PerFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut overrides: Vec<_> = BTreeMap::<String, Config>::deserialize(deserializer)?
+            .into_iter()
+            .collect();
+
+        if overrides
+            .iter()
+            .any(|(_, config)| config.per_file.is_some())
+        {
+            return Err(de::Error::custom("`per_file` tables cannot be nested"));
+        }
+
+        overrides.sort_by_key(|(pattern, _)| specificity(pattern));
+
+        let mut globs = GlobSetBuilder::new();
+
+        for (pattern, _) in &overrides {
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .map_err(de::Error::custom)?;
+            globs.add(glob);
+        }
+
+        Ok(PerFile {
+            root: PathBuf::new(),
+            overrides,
+            globs: globs.build().map_err(de::Error::custom)?,
+        })
+    }
+}
+
+/// Number of characters of a pattern that only match themselves, so that `tests/unit/**`
+/// is more specific than `tests/**`, which is more specific than `**/*.py`.
+fn specificity(pattern: &str) -> usize {
+    let mut in_class = false;
+    let mut literals = 0;
+
+    for character in pattern.chars() {
+        match character {
+            '[' | '{' => in_class = true,
+            ']' | '}' => in_class = false,
+            '*' | '?' => {}
+            _ if !in_class => literals += 1,
+            _ => {}
+        }
+    }
+
+    literals
+}
+
+fn regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Regex>, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+
+    Regex::new(&pattern).map(Some).map_err(de::Error::custom)
+}
+
+fn regex_pattern<S: Serializer>(regex: &Option<Regex>, serializer: S) -> Result<S::Ok, S::Error> {
+    regex.as_ref().map(Regex::as_str).serialize(serializer)
+}
+
+fn regex_patterns<S: Serializer>(
+    regexes: &Option<Vec<Regex>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    regexes
+        .as_ref()
+        .map(|regexes| regexes.iter().map(Regex::as_str).collect::<Vec<_>>())
+        .serialize(serializer)
+}
+
+fn regexes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<Regex>>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|pattern| Regex::new(pattern).map_err(de::Error::custom))
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+/// A `pyproject.toml`, of which only the `[tool.pystaleds]` table matters.
+#[derive(Debug, Deserialize)]
+struct PyProject {
+    tool: Option<Tools>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tools {
+    pystaleds: Option<Config>,
+}
+
+/// Loads a configuration file, which is read as a `pyproject.toml` (i.e., from its
+/// `[tool.pystaleds]` table) if it has that name, and as a [`CONFIG_FILE`] otherwise.
+pub fn load(path: &Path) -> Result<Config, ConfigError> {
+    if path.file_name().is_some_and(|name| name == PYPROJECT_FILE) {
+        return load_pyproject(path).map(Option::unwrap_or_default);
+    }
+
+    let config = parse(path, &read(path)?)?;
+
+    with_root(config, path)
+}
+
+/// Makes the `per_file` patterns of a configuration relative to the directory of its file.
+fn with_root(mut config: Config, path: &Path) -> Result<Config, ConfigError> {
+    if let Some(per_file) = &mut config.per_file {
+        let directory = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+
+        per_file.root = directory.canonicalize().map_err(|source| ConfigError::Io {
+            path: directory.to_path_buf(),
+            source,
+        })?;
+    }
+
+    Ok(config)
+}
+
+/// Parses the contents of a configuration file, rejecting unknown keys.
+fn parse<T: de::DeserializeOwned>(path: &Path, contents: &str) -> Result<T, ConfigError> {
+    toml::from_str(contents).map_err(|source| parse_error(path, contents, source))
+}
+
+/// Turns an error about an unknown key into one that suggests the intended key, since the
+/// error of serde lists every known key instead.
+fn parse_error(path: &Path, contents: &str, source: toml::de::Error) -> ConfigError {
+    let key = source
+        .message()
+        .strip_prefix("unknown field `")
+        .and_then(|rest| rest.split_once('`'))
+        .map(|(key, _)| key.to_string());
+
+    let Some(key) = key else {
+        return ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        };
+    };
+
+    let line = source
+        .span()
+        .map(|span| contents[..span.start].matches('\n').count() + 1);
+
+    let suggestion = Config::KEYS
+        .iter()
+        .map(|&known| (edit_distance(&key, known), known))
+        .filter(|&(distance, _)| distance <= key.len() / 3 + 1)
+        .min()
+        .map(|(_, known)| known);
+
+    ConfigError::UnknownKey {
+        path: path.to_path_buf(),
+        key,
+        line,
+        suggestion,
+    }
+}
+
+/// Levenshtein distance between two strings, i.e. the number of characters that need to
+/// be inserted, removed or replaced to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, &b) in b.iter().enumerate() {
+            let replaced = previous[j] + usize::from(a != b);
+            current.push(replaced.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Finds the configuration in a directory, i.e. its [`CONFIG_FILE`] or else its
+/// `pyproject.toml` if it has a `[tool.pystaleds]` table, returning it along with its path.
+pub fn discover(directory: &Path) -> Result<Option<(PathBuf, Config)>, ConfigError> {
+    let path = directory.join(CONFIG_FILE);
+
+    if path.is_file() {
+        return load(&path).map(|config| Some((path, config)));
+    }
+
+    let path = directory.join(PYPROJECT_FILE);
+
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    Ok(load_pyproject(&path)?.map(|config| (path, config)))
+}
+
+/// Finds the configurations of a directory and of the directories above it, from the
+/// outermost to the innermost, along with their paths.
+pub fn chain(directory: &Path) -> Result<Vec<(PathBuf, Config)>, ConfigError> {
+    let absolute = directory.canonicalize().map_err(|source| ConfigError::Io {
+        path: directory.to_path_buf(),
+        source,
+    })?;
+
+    let mut configs = Vec::new();
+
+    for directory in absolute.ancestors() {
+        configs.extend(discover(directory)?);
+    }
+
+    configs.reverse();
+
+    Ok(configs)
+}
+
+/// Resolves the configuration of the files in each directory, merging the configuration
+/// files found in it and in the directories above it, with the deeper ones overriding the
+/// shallower ones key by key.
+///
+/// The configuration of each directory is cached, so that its files are only read once.
+#[derive(Default)]
+pub struct ConfigResolver {
+    resolved: HashMap<PathBuf, Config>,
+}
+
+impl ConfigResolver {
+    /// Resolves the configuration of the files in a directory.
+    pub fn resolve(&mut self, directory: &Path) -> Result<Config, ConfigError> {
+        let absolute = directory.canonicalize().map_err(|source| ConfigError::Io {
+            path: directory.to_path_buf(),
+            source,
+        })?;
+
+        self.resolve_absolute(&absolute)
+    }
+
+    fn resolve_absolute(&mut self, directory: &Path) -> Result<Config, ConfigError> {
+        if let Some(config) = self.resolved.get(directory) {
+            return Ok(config.clone());
+        }
+
+        let parent = match directory.parent() {
+            Some(parent) => self.resolve_absolute(parent)?,
+            None => Config::default(),
+        };
+
+        let config = match discover(directory)? {
+            Some((path, config)) => {
+                tracing::debug!("using the configuration in {}", path.display());
+
+                parent.merge(config)
+            }
+            None => parent,
+        };
+
+        self.resolved
+            .insert(directory.to_path_buf(), config.clone());
+
+        Ok(config)
+    }
+}
+
+/// Loads the `[tool.pystaleds]` table of a `pyproject.toml`, if it has one.
+fn load_pyproject(path: &Path) -> Result<Option<Config>, ConfigError> {
+    let pyproject: PyProject = parse(path, &read(path)?)?;
+
+    pyproject
+        .tool
+        .and_then(|tool| tool.pystaleds)
+        .map(|config| with_root(config, path))
+        .transpose()
+}
+
+fn read(path: &Path) -> Result<String, ConfigError> {
+    std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge() {
+        let base: Config = toml::from_str(
+            r#"
+            docstyle = "numpy"
+            forbid_no_docstring = true
+            select = ["PSD2"]
+            "#,
+        )
+        .unwrap();
+        let top: Config = toml::from_str(
+            r#"
+            forbid_no_docstring = false
+            exclude_functions = ["^test_"]
+            "#,
+        )
+        .unwrap();
+
+        let mut options = CheckOptions::default();
+        base.merge(top).apply(&mut options);
+
+        assert!(matches!(options.docstyle, DocstringStyle::Numpy));
+        assert!(options.succeed_if_no_docstring);
+        assert_eq!(options.rules.select, vec!["PSD2".parse().unwrap()]);
+        assert_eq!(options.exclude_functions[0].as_str(), "^test_");
+        assert!(options.succeed_if_no_args_in_docstring);
+    }
+
+    #[test]
+    fn resolver() {
+        let mut resolver = ConfigResolver::default();
+
+        let strict = resolver
+            .resolve(Path::new("tests/config/nested/strict"))
+            .unwrap();
+        assert_eq!(strict.forbid_no_docstring, Some(true));
+        assert_eq!(strict.ignore_private, Some(false));
+
+        let lenient = resolver
+            .resolve(Path::new("tests/config/nested/lenient"))
+            .unwrap();
+        assert_eq!(lenient.forbid_no_docstring, Some(false));
+        assert_eq!(lenient.ignore_private, Some(true));
+
+        assert!(resolver.resolve(Path::new("tests/config/missing")).is_err());
+    }
+
+    #[test]
+    fn per_file() {
+        let config = load(Path::new("tests/per_file/pystaleds.toml")).unwrap();
+        let per_file = config.per_file.unwrap();
+        let matching = |file: &str| -> Vec<_> {
+            per_file
+                .matches(Path::new(file))
+                .into_iter()
+                .map(|index| per_file.get(index).0)
+                .collect()
+        };
+
+        assert_eq!(
+            matching("tests/per_file/tests/strict/undocumented.py"),
+            ["tests/**", "tests/strict/**"]
+        );
+        assert_eq!(
+            matching("tests/per_file/src/__init__.py"),
+            ["**/__init__.py"]
+        );
+        assert!(matching("tests/per_file/src/undocumented.py").is_empty());
+        assert!(matching("tests/config/nested/undocumented.py").is_empty());
+
+        assert_eq!(specificity("**/*.py"), 4);
+        assert_eq!(specificity("src/[ab]*.py"), 7);
+
+        let nested = "[per_file.\"tests/**\".per_file]\n\"*.py\" = { skip = true }";
+        assert!(toml::from_str::<Config>(nested).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        for contents in [
+            "forbid_no_docstring = ",
+            "forbid_no_docstring = \"yes\"",
+            "docstyle = \"sphinx\"",
+            "select = [\"PSD9\"]",
+            "exclude_functions = [\"(\"]",
+        ] {
+            assert!(toml::from_str::<Config>(contents).is_err(), "{contents}");
+        }
+
+        let path = Path::new("pystaleds.toml");
+        let error = |contents: &str| {
+            let error = parse::<Config>(path, contents).unwrap_err();
+
+            match std::error::Error::source(&error) {
+                Some(source) => format!("{error}: {source}"),
+                None => error.to_string(),
+            }
+        };
+
+        assert_eq!(
+            error("forbid_no_docstring = true\ndocstlye = \"google\""),
+            "unknown key `docstlye` in pystaleds.toml at line 2, did you mean `docstyle`?"
+        );
+        assert_eq!(
+            error("colour = \"never\""),
+            "unknown key `colour` in pystaleds.toml at line 1"
+        );
+
+        let wrong_type = error("\nforbid_no_docstring = \"yes\"");
+        assert!(wrong_type.starts_with("could not parse pystaleds.toml"));
+        assert!(wrong_type.contains("line 2"));
+        assert!(wrong_type.contains("invalid type: string \"yes\", expected a boolean"));
+
+        assert!(error("docstyle = \"sphinx\"").contains(
+            "unknown variant `sphinx`, expected one of `google`, `numpy`, `auto-detect`"
+        ));
+
+        // Only the table of pystaleds is checked in a `pyproject.toml`.
+        let pyproject = "[tool.black]\nline-length = 88\n\n[tool.pystaleds]\nselekt = [\"PSD1\"]";
+        assert!(parse::<PyProject>(path, "[tool.black]\nline-length = 88").is_ok());
+        assert_eq!(
+            parse::<PyProject>(Path::new(PYPROJECT_FILE), pyproject)
+                .unwrap_err()
+                .to_string(),
+            "unknown key `selekt` in pyproject.toml at line 5, did you mean `select`?"
+        );
+    }
+}
@@ -0,0 +1,449 @@
+use std::ops::Range;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use thiserror::Error;
+use tree_sitter::Parser;
+
+use crate::parsing::{parse_google_sections, parse_numpy_sections, Section};
+use crate::rules_checking::walk_rec;
+
+/// Docstring style to convert docstrings to.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ConversionTarget {
+    Google,
+    Numpy,
+}
+
+impl ConversionTarget {
+    /// Parses the sections written in the style that is converted *from*.
+    fn parse_source_sections(self, docstring: &str) -> Vec<Section<'_>> {
+        match self {
+            ConversionTarget::Google => parse_numpy_sections(docstring),
+            ConversionTarget::Numpy => parse_google_sections(docstring),
+        }
+    }
+
+    /// Parses the sections written in the style that is converted *to*.
+    fn parse_target_sections(self, docstring: &str) -> Vec<Section<'_>> {
+        match self {
+            ConversionTarget::Google => parse_google_sections(docstring),
+            ConversionTarget::Numpy => parse_numpy_sections(docstring),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConversionError {
+    #[error("section `{0}` cannot be represented in the target style without losing information")]
+    Lossy(&'static str),
+    #[error("converted docstring does not parse back into the original contents")]
+    RoundTrip,
+}
+
+/// A docstring of a source file, along with what it becomes after conversion.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DocstringConversion {
+    /// Byte range of the docstring in the source code.
+    pub range: Range<usize>,
+    /// Row in which the docstring starts.
+    pub row: usize,
+    pub original: String,
+    pub converted: String,
+}
+
+/// Renders a section in google style.
+fn render_google_section(section: &Section) -> Result<String, ConversionError> {
+    let heading = section.kind.google_heading();
+    let indentation = " ".repeat(section.indentation);
+
+    let mut lines = vec![format!("{indentation}{heading}")];
+
+    for entry in &section.entries {
+        let mut description = entry.description.iter();
+
+        let first_line = match (section.kind.has_named_entries(), entry.name, entry.typ) {
+            (true, Some(name), typ) => {
+                let typ = typ.map_or(String::new(), |typ| format!(" ({typ})"));
+
+                match description.next() {
+                    Some(line) => format!("{name}{typ}: {line}"),
+                    None => format!("{name}{typ}:"),
+                }
+            }
+            (false, None, Some(typ)) => match description.next() {
+                Some(line) => format!("{typ}: {line}"),
+                None => format!("{typ}:"),
+            },
+            (false, None, None) => match description.next() {
+                Some(line) => line.to_string(),
+                None => continue,
+            },
+            _ => return Err(ConversionError::Lossy(heading)),
+        };
+
+        lines.push(format!("{indentation}    {first_line}"));
+        lines.extend(description.map(|line| indent_line(line, section.indentation + 8)));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+/// Renders a section in numpy style.
+fn render_numpy_section(section: &Section) -> Result<String, ConversionError> {
+    let heading = section.kind.numpy_heading();
+    let indentation = " ".repeat(section.indentation);
+
+    let mut lines = vec![
+        format!("{indentation}{heading}"),
+        format!("{indentation}{}", "-".repeat(heading.len())),
+    ];
+
+    for entry in &section.entries {
+        let first_line = match (section.kind.has_named_entries(), entry.name, entry.typ) {
+            (true, Some(name), Some(typ)) => format!("{name} : {typ}"),
+            (true, Some(name), None) => name.to_string(),
+            (false, None, Some(typ)) => typ.to_string(),
+            _ => return Err(ConversionError::Lossy(heading)),
+        };
+
+        lines.push(format!("{indentation}{first_line}"));
+        lines.extend(
+            entry
+                .description
+                .iter()
+                .map(|line| indent_line(line, section.indentation + 4)),
+        );
+    }
+
+    Ok(lines.join("\n"))
+}
+
+/// Indents a line, leaving empty lines without trailing whitespace.
+fn indent_line(line: &str, indentation: usize) -> String {
+    if line.is_empty() {
+        String::new()
+    } else {
+        format!("{}{line}", " ".repeat(indentation))
+    }
+}
+
+/// Converts the sections of a docstring to the target style.
+///
+/// Returns `Ok(None)` if there is nothing to convert, and an error if the conversion
+/// would not preserve the names, types and descriptions of every entry.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::conversion::{convert_docstring, ConversionTarget};
+///
+/// let converted = convert_docstring(
+///            r#""""This is my docstring!!!.
+///
+///    Parameters
+///    ----------
+///    x : int
+///        This is my first parameter.
+///    """"#,
+///            ConversionTarget::Google,
+///        )
+///        .unwrap();
+///
+/// assert_eq!(
+///     converted.unwrap(),
+///            r#""""This is my docstring!!!.
+///
+///    Args:
+///        x (int): This is my first parameter.
+///    """"#,
+/// );
+/// ```
+pub fn convert_docstring(
+    docstring: &str,
+    target: ConversionTarget,
+) -> Result<Option<String>, ConversionError> {
+    let sections = target.parse_source_sections(docstring);
+
+    if sections.is_empty() {
+        return Ok(None);
+    }
+
+    let mut converted = docstring.to_string();
+
+    for section in sections.iter().rev() {
+        let rendered = match target {
+            ConversionTarget::Google => render_google_section(section)?,
+            ConversionTarget::Numpy => render_numpy_section(section)?,
+        };
+
+        converted.replace_range(section.range.clone(), &rendered);
+    }
+
+    let reparsed = target.parse_target_sections(&converted);
+
+    let round_trips = sections.len() == reparsed.len()
+        && sections.iter().zip(&reparsed).all(|(original, reparsed)| {
+            original.kind == reparsed.kind && original.entries == reparsed.entries
+        });
+
+    if !round_trips {
+        return Err(ConversionError::RoundTrip);
+    }
+
+    Ok(Some(converted))
+}
+
+/// Plans the conversion of every function and class docstring of the source code that
+/// has sections in the style other than the target one.
+pub fn plan_conversions(
+    parser: &mut Parser,
+    source_code: &str,
+    target: ConversionTarget,
+) -> Result<Vec<DocstringConversion>> {
+    let tree = parser
+        .parse(source_code, None)
+        .expect("parser should be ready to parse");
+
+    let mut docstrings = Vec::new();
+
+    walk_rec(&mut tree.walk(), &mut |node| {
+        if node.kind() != "function_definition" && node.kind() != "class_definition" {
+            return;
+        }
+
+        let Some(statement) = node
+            .child_by_field_name("body")
+            .and_then(|body| body.named_child(0))
+            .filter(|statement| statement.kind() == "expression_statement")
+        else {
+            return;
+        };
+
+        if let Some(string) = statement.named_child(0).filter(|s| s.kind() == "string") {
+            docstrings.push((string.byte_range(), string.start_position().row));
+        }
+    });
+
+    let mut conversions = Vec::new();
+
+    for (range, row) in docstrings {
+        let original = &source_code[range.clone()];
+
+        let converted =
+            convert_docstring(original, target).with_context(|| format!("Line {}", row + 1))?;
+
+        if let Some(converted) = converted.filter(|converted| converted != original) {
+            conversions.push(DocstringConversion {
+                range,
+                row,
+                original: original.to_string(),
+                converted,
+            });
+        }
+    }
+
+    Ok(conversions)
+}
+
+/// Applies the planned conversions to the source code.
+pub fn apply_conversions(source_code: &str, conversions: &[DocstringConversion]) -> String {
+    let mut converted = source_code.to_string();
+
+    for conversion in conversions.iter().rev() {
+        converted.replace_range(conversion.range.clone(), &conversion.converted);
+    }
+
+    converted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_parser() -> Parser {
+        let mut parser = Parser::new();
+
+        parser
+            .set_language(&tree_sitter_python::language())
+            .expect("should be able to load Python grammar");
+
+        parser
+    }
+
+    #[test]
+    fn numpy_to_google() {
+        let docstring = r#""""Hey.
+
+            Parameters
+            ----------
+            x : int, optional
+                First var.
+
+                Still first var.
+            y
+                Second var.
+
+            Returns
+            -------
+            int
+                The result.
+            """"#;
+
+        let converted = convert_docstring(docstring, ConversionTarget::Google).unwrap();
+
+        assert_eq!(
+            converted.unwrap(),
+            r#""""Hey.
+
+            Args:
+                x (int, optional): First var.
+
+                    Still first var.
+                y: Second var.
+
+            Returns:
+                int: The result.
+            """"#
+        );
+    }
+
+    #[test]
+    fn google_to_numpy() {
+        let docstring = r#""""Hey.
+
+            Args:
+                x (int): First var.
+                    Continues here.
+                y: Second var.
+
+            Raises:
+                ValueError: If something is off.
+            """"#;
+
+        let converted = convert_docstring(docstring, ConversionTarget::Numpy).unwrap();
+
+        assert_eq!(
+            converted.unwrap(),
+            r#""""Hey.
+
+            Parameters
+            ----------
+            x : int
+                First var.
+                Continues here.
+            y
+                Second var.
+
+            Raises
+            ------
+            ValueError
+                If something is off.
+            """"#
+        );
+    }
+
+    #[test]
+    fn lossy_conversions() {
+        let docstring = r#""""Hey.
+
+            Returns
+            -------
+            result : int
+                The result.
+            """"#;
+
+        assert_eq!(
+            convert_docstring(docstring, ConversionTarget::Google),
+            Err(ConversionError::Lossy("Returns:"))
+        );
+
+        let docstring = r#""""Hey.
+
+            Returns:
+                The result.
+            """"#;
+
+        assert_eq!(
+            convert_docstring(docstring, ConversionTarget::Numpy),
+            Err(ConversionError::Lossy("Returns"))
+        );
+
+        assert_eq!(
+            convert_docstring(r#""""Nothing to convert.""""#, ConversionTarget::Numpy),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn converting_source() {
+        let source_code = r#"def f(x, y):
+    """Hey.
+
+    Parameters
+    ----------
+    x : int
+        First var.
+    y : str
+        Second var.
+    """
+    return x
+
+
+class A:
+    """Just a class."""
+
+    def g(self, x):
+        """Hey.
+
+        Parameters
+        ----------
+        x
+            First var.
+        """
+        return x
+"#;
+
+        let mut parser = get_parser();
+
+        let conversions =
+            plan_conversions(&mut parser, source_code, ConversionTarget::Google).unwrap();
+
+        assert_eq!(conversions.len(), 2);
+        assert_eq!(conversions[0].row, 1);
+        assert_eq!(conversions[1].row, 17);
+
+        let converted = apply_conversions(source_code, &conversions);
+
+        assert_eq!(
+            converted,
+            r#"def f(x, y):
+    """Hey.
+
+    Args:
+        x (int): First var.
+        y (str): Second var.
+    """
+    return x
+
+
+class A:
+    """Just a class."""
+
+    def g(self, x):
+        """Hey.
+
+        Args:
+            x: First var.
+        """
+        return x
+"#
+        );
+
+        assert!(
+            plan_conversions(&mut parser, &converted, ConversionTarget::Google)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,471 @@
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tracing::Level;
+
+use crate::fixing::Fix;
+
+/// Rule that a diagnostic refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    MissingDocstring,
+    MissingArgs,
+    MissingReturns,
+    MissingYields,
+    MissingModuleDocstring,
+    MissingSummary,
+    MissingArg,
+    ExtraArg,
+    ArgTypeMismatch,
+    ArgsOrder,
+    ParamCaseMismatch,
+    DuplicateArg,
+    DocumentedSelf,
+    UntypedDocstringArg,
+    MissingAnnotation,
+    TypeCommentMismatch,
+    OptionalMismatch,
+    MissingDefaultMention,
+    ReturnTypeMismatch,
+    YieldTypeMismatch,
+    RaisesMismatch,
+    AttributesMismatch,
+    StaleSeeAlso,
+    InvalidExample,
+    StyleMismatch,
+    SectionOrder,
+    PlaceholderText,
+    EmptyDescription,
+}
+
+impl Rule {
+    /// Every rule, sorted by code.
+    pub const ALL: [Rule; 28] = [
+        Rule::MissingDocstring,
+        Rule::MissingArgs,
+        Rule::MissingReturns,
+        Rule::MissingYields,
+        Rule::MissingModuleDocstring,
+        Rule::MissingSummary,
+        Rule::MissingArg,
+        Rule::ExtraArg,
+        Rule::ArgTypeMismatch,
+        Rule::ArgsOrder,
+        Rule::ParamCaseMismatch,
+        Rule::DuplicateArg,
+        Rule::DocumentedSelf,
+        Rule::UntypedDocstringArg,
+        Rule::MissingAnnotation,
+        Rule::TypeCommentMismatch,
+        Rule::OptionalMismatch,
+        Rule::MissingDefaultMention,
+        Rule::ReturnTypeMismatch,
+        Rule::YieldTypeMismatch,
+        Rule::RaisesMismatch,
+        Rule::AttributesMismatch,
+        Rule::StaleSeeAlso,
+        Rule::InvalidExample,
+        Rule::StyleMismatch,
+        Rule::SectionOrder,
+        Rule::PlaceholderText,
+        Rule::EmptyDescription,
+    ];
+
+    /// Identifier of the rule, as used in machine-readable outputs.
+    pub fn id(self) -> &'static str {
+        match self {
+            Rule::MissingDocstring => "missing-docstring",
+            Rule::MissingArgs => "missing-args",
+            Rule::MissingReturns => "missing-returns",
+            Rule::MissingYields => "missing-yields",
+            Rule::MissingModuleDocstring => "missing-module-docstring",
+            Rule::MissingSummary => "missing-summary",
+            Rule::MissingArg => "missing-arg",
+            Rule::ExtraArg => "extra-arg",
+            Rule::ArgTypeMismatch => "arg-type-mismatch",
+            Rule::ArgsOrder => "args-order",
+            Rule::ParamCaseMismatch => "param-case-mismatch",
+            Rule::DuplicateArg => "duplicate-arg",
+            Rule::DocumentedSelf => "documented-self",
+            Rule::UntypedDocstringArg => "untyped-docstring-arg",
+            Rule::MissingAnnotation => "missing-annotation",
+            Rule::TypeCommentMismatch => "type-comment-mismatch",
+            Rule::OptionalMismatch => "optional-mismatch",
+            Rule::MissingDefaultMention => "missing-default-mention",
+            Rule::ReturnTypeMismatch => "return-type-mismatch",
+            Rule::YieldTypeMismatch => "yield-type-mismatch",
+            Rule::RaisesMismatch => "raises-mismatch",
+            Rule::AttributesMismatch => "attributes-mismatch",
+            Rule::StaleSeeAlso => "stale-see-also",
+            Rule::InvalidExample => "invalid-example",
+            Rule::StyleMismatch => "style-mismatch",
+            Rule::SectionOrder => "section-order",
+            Rule::PlaceholderText => "placeholder-text",
+            Rule::EmptyDescription => "empty-description",
+        }
+    }
+
+    /// Short code of the rule, e.g. `PSD201`, which is stable across releases.
+    ///
+    /// The first digit is the family of the rule: 1 for missing docstrings and sections,
+    /// 2 for the consistency of the args, 3 for the consistency of the other sections and
+    /// 4 for the style and content of the docstrings.
+    pub fn code(self) -> &'static str {
+        match self {
+            Rule::MissingDocstring => "PSD101",
+            Rule::MissingArgs => "PSD102",
+            Rule::MissingReturns => "PSD103",
+            Rule::MissingYields => "PSD104",
+            Rule::MissingModuleDocstring => "PSD105",
+            Rule::MissingSummary => "PSD106",
+            Rule::MissingArg => "PSD201",
+            Rule::ExtraArg => "PSD202",
+            Rule::ArgTypeMismatch => "PSD203",
+            Rule::ArgsOrder => "PSD204",
+            Rule::ParamCaseMismatch => "PSD205",
+            Rule::DuplicateArg => "PSD206",
+            Rule::DocumentedSelf => "PSD207",
+            Rule::UntypedDocstringArg => "PSD208",
+            Rule::MissingAnnotation => "PSD209",
+            Rule::TypeCommentMismatch => "PSD210",
+            Rule::OptionalMismatch => "PSD211",
+            Rule::MissingDefaultMention => "PSD212",
+            Rule::ReturnTypeMismatch => "PSD301",
+            Rule::YieldTypeMismatch => "PSD302",
+            Rule::RaisesMismatch => "PSD303",
+            Rule::AttributesMismatch => "PSD304",
+            Rule::StaleSeeAlso => "PSD305",
+            Rule::InvalidExample => "PSD306",
+            Rule::StyleMismatch => "PSD401",
+            Rule::SectionOrder => "PSD402",
+            Rule::PlaceholderText => "PSD403",
+            Rule::EmptyDescription => "PSD404",
+        }
+    }
+
+    /// Severity of the violations of the rule, unless configured otherwise.
+    pub fn default_severity(self) -> Severity {
+        Severity::Error
+    }
+
+    /// URL of the documentation of the rule.
+    pub fn url(self) -> String {
+        format!("https://github.com/AloizioMacedo/pystaleds#{}", self.id())
+    }
+}
+
+/// How the violations of a rule are treated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Fails the check.
+    #[default]
+    Error,
+    /// Reported without failing the check.
+    Warning,
+}
+
+impl Severity {
+    /// Identifier of the severity, as used in machine-readable outputs.
+    pub fn id(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// Prefix of the codes of the rules to select or ignore, such as `PSD2` for every
+/// `PSD2xx` rule or `PSD101` for just that one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub struct RuleSelector(String);
+
+impl RuleSelector {
+    /// Whether the code of the rule starts with the selector.
+    pub fn matches(&self, rule: Rule) -> bool {
+        rule.code().starts_with(&self.0)
+    }
+}
+
+impl FromStr for RuleSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let selector = RuleSelector(s.trim().to_uppercase());
+
+        if selector.0.is_empty() || !Rule::ALL.iter().any(|rule| selector.matches(*rule)) {
+            return Err(format!("`{s}` is not the prefix of the code of any rule"));
+        }
+
+        Ok(selector)
+    }
+}
+
+impl TryFrom<String> for RuleSelector {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Which of the enabled rules are reported, as in ruff's `--select` and `--ignore`, and
+/// with which severity.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSelection {
+    /// Selectors of the rules to report. Every rule is selected if empty.
+    pub select: Vec<RuleSelector>,
+    /// Selectors of the rules not to report.
+    pub ignore: Vec<RuleSelector>,
+    /// Selectors of the rules reported as warnings rather than with their default
+    /// severity.
+    pub warn: Vec<RuleSelector>,
+}
+
+impl RuleSelection {
+    /// Determines if the rule is reported. The most specific selector matching it wins,
+    /// so `PSD201` in `ignore` overrides `PSD2` in `select` and vice versa, with `ignore`
+    /// winning between selectors of the same length.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pystaleds::diagnostics::{Rule, RuleSelection};
+    ///
+    /// let selection = RuleSelection {
+    ///     select: vec!["PSD2".parse().unwrap()],
+    ///     ignore: vec!["PSD203".parse().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(selection.enables(Rule::MissingArg));
+    /// assert!(!selection.enables(Rule::ArgTypeMismatch));
+    /// assert!(!selection.enables(Rule::MissingDocstring));
+    /// ```
+    pub fn enables(&self, rule: Rule) -> bool {
+        let specificity = |selectors: &[RuleSelector]| {
+            selectors
+                .iter()
+                .filter(|selector| selector.matches(rule))
+                .map(|selector| selector.0.len())
+                .max()
+        };
+
+        let selected = if self.select.is_empty() {
+            Some(0)
+        } else {
+            specificity(&self.select)
+        };
+
+        match (selected, specificity(&self.ignore)) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(selected), Some(ignored)) => selected > ignored,
+        }
+    }
+
+    /// Severity with which the violations of the rule are reported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pystaleds::diagnostics::{Rule, RuleSelection, Severity};
+    ///
+    /// let selection = RuleSelection {
+    ///     warn: vec!["PSD2".parse().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(selection.severity(Rule::MissingArg), Severity::Warning);
+    /// assert_eq!(selection.severity(Rule::MissingDocstring), Severity::Error);
+    /// ```
+    pub fn severity(&self, rule: Rule) -> Severity {
+        if self.warn.iter().any(|selector| selector.matches(rule)) {
+            Severity::Warning
+        } else {
+            rule.default_severity()
+        }
+    }
+
+    /// Keeps the diagnostics of the selected rules, setting their severity.
+    pub fn apply(&self, mut diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics.retain(|diagnostic| self.enables(diagnostic.rule));
+
+        for diagnostic in &mut diagnostics {
+            diagnostic.severity = self.severity(diagnostic.rule);
+        }
+
+        diagnostics
+    }
+}
+
+/// A violation of a rule found in a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    /// Path of the file in which the function is defined, if any.
+    pub path: Option<PathBuf>,
+    /// Name of the function (or class), or the row in which it is defined if the name is
+    /// unknown.
+    pub function: String,
+    /// Row (0-based) the diagnostic points at, if known. This is where the function is
+    /// defined, unless the diagnostic is about a specific entry of its docstring.
+    pub row: Option<usize>,
+    /// Column (0-based) the diagnostic points at, if known.
+    pub column: Option<usize>,
+    /// Row (0-based) in which the function is defined, if known.
+    pub function_row: Option<usize>,
+    pub message: String,
+    /// Whether the diagnostic fails the check, as configured for its rule.
+    pub severity: Severity,
+    /// Entries from the code and from the docstring, for the diagnostics that compare
+    /// them (such as args).
+    pub entries: Option<EntriesComparison>,
+    /// Byte ranges of the parts of the function, for the diagnostics about one.
+    pub spans: Spans,
+    /// Edits that fix the diagnostic, if it can be fixed and fixes were planned with
+    /// [`crate::rules_checking::CheckOptions::attach_fixes`].
+    pub fix: Option<Fix>,
+}
+
+/// Byte ranges in the checked source code of the parts of a function, so that editors can
+/// highlight or replace them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Spans {
+    /// From the `def` keyword to the colon that ends the signature.
+    pub signature: Option<Range<usize>>,
+    /// The params, including the parentheses around them.
+    pub parameters: Option<Range<usize>>,
+    /// The docstring, including its quotes.
+    pub docstring: Option<Range<usize>>,
+}
+
+/// Names and types of the entries (such as args) from the code and from a docstring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntriesComparison {
+    pub expected: Vec<(String, Option<String>)>,
+    pub actual: Vec<(String, Option<String>)>,
+}
+
+impl EntriesComparison {
+    /// Creates the comparison from the entries from the code and from the docstring.
+    pub fn new(expected: &[(&str, Option<&str>)], actual: &[(&str, Option<&str>)]) -> Self {
+        let to_owned = |entries: &[(&str, Option<&str>)]| {
+            entries
+                .iter()
+                .map(|(name, typ)| (name.to_string(), typ.map(str::to_string)))
+                .collect()
+        };
+
+        Self {
+            expected: to_owned(expected),
+            actual: to_owned(actual),
+        }
+    }
+}
+
+impl Diagnostic {
+    /// Logs the diagnostic as an error or a warning, depending on its severity.
+    pub fn log(&self) {
+        match self.severity {
+            Severity::Error => tracing::event!(Level::ERROR, "{}", self),
+            Severity::Warning => tracing::event!(Level::WARN, "{}", self),
+        }
+    }
+
+    /// Whether the diagnostic fails the check.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Describes the diagnostic without its path, as in
+    /// "`f`: PSD201 `x` missing from docstring".
+    pub fn text(&self) -> String {
+        format!("`{}`: {} {}", self.function, self.rule.code(), self.message)
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path.to_string_lossy())?;
+        }
+
+        write!(f, "{}", self.text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn codes() {
+        let codes: Vec<_> = Rule::ALL.iter().map(|rule| rule.code()).collect();
+
+        assert!(codes.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(codes.iter().all(|code| code.len() == 6
+            && code.starts_with("PSD")
+            && code[3..].chars().all(|c| c.is_ascii_digit())));
+
+        let ids: HashSet<_> = Rule::ALL.iter().map(|rule| rule.id()).collect();
+        assert_eq!(ids.len(), Rule::ALL.len());
+
+        // Codes are referenced in suppressions and configurations, so they must not change.
+        assert_eq!(Rule::MissingDocstring.code(), "PSD101");
+        assert_eq!(Rule::MissingArgs.code(), "PSD102");
+        assert_eq!(Rule::MissingArg.code(), "PSD201");
+        assert_eq!(Rule::ExtraArg.code(), "PSD202");
+        assert_eq!(Rule::ArgTypeMismatch.code(), "PSD203");
+    }
+
+    #[test]
+    fn selection() {
+        let selectors = |codes: &[&str]| -> Vec<RuleSelector> {
+            codes.iter().map(|code| code.parse().unwrap()).collect()
+        };
+
+        assert!(Rule::ALL
+            .iter()
+            .all(|rule| RuleSelection::default().enables(*rule)));
+
+        let selection = RuleSelection {
+            select: Vec::new(),
+            ignore: selectors(&["psd101"]),
+            ..Default::default()
+        };
+        assert!(!selection.enables(Rule::MissingDocstring));
+        assert!(selection.enables(Rule::MissingArgs));
+
+        let selection = RuleSelection {
+            select: selectors(&["PSD201"]),
+            ignore: selectors(&["PSD2"]),
+            ..Default::default()
+        };
+        assert!(selection.enables(Rule::MissingArg));
+        assert!(!selection.enables(Rule::ExtraArg));
+
+        let selection = RuleSelection {
+            select: selectors(&["PSD2"]),
+            ignore: selectors(&["PSD2"]),
+            ..Default::default()
+        };
+        assert!(!selection.enables(Rule::MissingArg));
+        assert_eq!(selection.severity(Rule::MissingArg), Severity::Error);
+
+        let selection = RuleSelection {
+            warn: selectors(&["PSD2"]),
+            ..Default::default()
+        };
+        assert_eq!(selection.severity(Rule::MissingArg), Severity::Warning);
+        assert_eq!(selection.severity(Rule::MissingDocstring), Severity::Error);
+
+        assert!("PSD9".parse::<RuleSelector>().is_err());
+        assert!("".parse::<RuleSelector>().is_err());
+    }
+}
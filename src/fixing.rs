@@ -0,0 +1,868 @@
+use std::ops::Range;
+use std::path::Path;
+
+use similar::TextDiff;
+use tree_sitter::Parser;
+
+use crate::ast_parsing::{body_line_count, get_function_signature, Param};
+use crate::diagnostics::Rule;
+use crate::parsing::{offset_within, parse_google_sections, parse_numpy_sections, SectionKind};
+use crate::rules_checking::{
+    check_function_info, describe_function, walk_rec, CheckOptions, DocstringStyle,
+};
+
+/// Description given to the args added to a docstring, to be filled in afterwards. It is
+/// one of the default placeholders, so that `forbid_placeholders` flags the ones left.
+pub const PLACEHOLDER_DESCRIPTION: &str = "_description_";
+
+/// Rules whose violations can be fixed.
+pub const FIXABLE_RULES: [Rule; 3] = [Rule::MissingArg, Rule::ExtraArg, Rule::ArgsOrder];
+
+/// A replacement of a byte range of the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    /// Byte range of the source code that is replaced.
+    pub range: Range<usize>,
+    /// Row and column (0-based, the column in bytes) in which the range starts.
+    pub start: (usize, usize),
+    /// Row and column (0-based, the column in bytes) in which the range ends.
+    pub end: (usize, usize),
+    pub replacement: String,
+}
+
+/// Edits that fix the violations of a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// Row (0-based) in which the function is defined.
+    pub row: usize,
+    /// Name of the function.
+    pub function: String,
+    /// What the fix does, e.g. "update Args section to match signature".
+    pub title: String,
+    /// Rules whose violations in the function are fixed.
+    pub rules: Vec<Rule>,
+    /// Edits of the fix, which do not overlap.
+    pub edits: Vec<Edit>,
+}
+
+/// Plans the fixes of the args of every function of the source code whose google
+/// docstring does not match its signature, according to the options.
+///
+/// The args section is rewritten to follow the signature: the entries of the params that
+/// still exist are kept as they are written, those of the params that no longer exist are
+/// removed and the new params (including renamed ones) get an entry with
+/// [`PLACEHOLDER_DESCRIPTION`].
+///
+/// Docstrings whose style is ambiguous or whose args section cannot be rewritten without
+/// guessing are left untouched, so that their violations are still reported.
+pub fn plan_fixes(
+    parser: &mut Parser,
+    source_code: &str,
+    path: Option<&Path>,
+    options: &CheckOptions,
+) -> Vec<Fix> {
+    if let DocstringStyle::Numpy = options.docstyle {
+        return Vec::new();
+    }
+
+    let tree = parser
+        .parse(source_code, None)
+        .expect("parser should be ready to parse");
+
+    let mut checked_params = Vec::with_capacity(8);
+    let mut params = Vec::with_capacity(8);
+    let mut fixes = Vec::new();
+
+    walk_rec(&mut tree.walk(), &mut |node| {
+        if options.min_function_lines > 0
+            && body_line_count(node).is_some_and(|lines| lines < options.min_function_lines)
+        {
+            return;
+        }
+
+        let Some(info) = get_function_signature(
+            node,
+            source_code,
+            &mut checked_params,
+            options.skip_args_and_kwargs,
+            &options.ignore_params,
+        ) else {
+            return;
+        };
+
+        let found: Vec<_> = check_function_info(&info, options)
+            .into_iter()
+            .map(|diagnostic| diagnostic.rule)
+            .collect();
+        let rules: Vec<_> = FIXABLE_RULES
+            .into_iter()
+            .filter(|&rule| found.contains(&rule) && options.fixes(rule))
+            .collect();
+
+        let Some(docstring) = info.docstring.filter(|_| !rules.is_empty()) else {
+            return;
+        };
+
+        // Every param is placed in the section, including the ones that are not checked,
+        // such as `*args`, in case they are documented.
+        let Some(all_params) = get_function_signature(node, source_code, &mut params, false, &[])
+        else {
+            return;
+        };
+
+        let signature = Signature {
+            params: all_params.params,
+            checked: info.params,
+            has_var_kwargs: info.has_var_kwargs,
+        };
+
+        // The order of the signature is followed when it is expected, unless the documented
+        // order is wrong and is not to be fixed.
+        let changes = Changes {
+            add_missing: rules.contains(&Rule::MissingArg),
+            remove_extra: rules.contains(&Rule::ExtraArg),
+            follow_signature: !options.allow_out_of_order_args
+                && (rules.contains(&Rule::ArgsOrder) || !found.contains(&Rule::ArgsOrder)),
+        };
+
+        let row = node.start_position().row;
+
+        let Some((range, replacement)) =
+            rewrite_args_section(docstring, &signature, &changes, options)
+        else {
+            tracing::debug!("{}: cannot be fixed", describe_function(path, &info));
+            return;
+        };
+
+        let start = offset_within(source_code, docstring).expect("docstring is in the source");
+        let range = start + range.start..start + range.end;
+
+        fixes.push(Fix {
+            row,
+            function: info.name.to_string(),
+            title: "update Args section to match signature".to_string(),
+            rules,
+            edits: vec![Edit {
+                start: position(source_code, range.start),
+                end: position(source_code, range.end),
+                range,
+                replacement,
+            }],
+        });
+    });
+
+    fixes
+}
+
+/// Determines the row and column (0-based, the column in bytes) of an offset of the source
+/// code.
+fn position(source_code: &str, offset: usize) -> (usize, usize) {
+    let before = &source_code[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+    (before.matches('\n').count(), offset - line_start)
+}
+
+/// Applies the edits of the fixes to the source code, from the end of it backwards so
+/// that the ranges of the remaining edits stay valid.
+pub fn apply_fixes(source_code: &str, fixes: &[Fix]) -> String {
+    let mut edits: Vec<_> = fixes.iter().flat_map(|fix| &fix.edits).collect();
+    edits.sort_by_key(|edit| edit.range.start);
+
+    let mut fixed = source_code.to_string();
+
+    for edit in edits.into_iter().rev() {
+        fixed.replace_range(edit.range.clone(), &edit.replacement);
+    }
+
+    fixed
+}
+
+/// Renders the changes that the fixes make to a file as a unified diff, with the path
+/// prefixed by `a/` and `b/` as git does, or an empty string if there are none.
+pub fn unified_diff(path: &Path, source_code: &str, fixes: &[Fix]) -> String {
+    let fixed = apply_fixes(source_code, fixes);
+
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = path.strip_prefix("./").unwrap_or(&path);
+
+    TextDiff::from_lines(source_code, &fixed)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{path}"), &format!("b/{path}"))
+        .to_string()
+}
+
+/// Params of a function, as far as its args section is concerned.
+struct Signature<'a, 'b> {
+    /// Every param, except for the receiver of methods.
+    params: &'b [Param<'a>],
+    /// The params that are checked against the docstring.
+    checked: &'b [Param<'a>],
+    has_var_kwargs: bool,
+}
+
+/// Changes made to an args section, according to the rules that are fixed.
+struct Changes {
+    /// Adds the params that are not documented.
+    add_missing: bool,
+    /// Removes the documented args that are not in the signature.
+    remove_extra: bool,
+    /// Sorts the entries in the order of the signature. Otherwise, the documented order is
+    /// kept, with the new entries at the end.
+    follow_signature: bool,
+}
+
+/// An entry of the args section, as written: the line with its name and the lines of its
+/// description, without the blank lines that follow it.
+struct Chunk<'a> {
+    name: &'a str,
+    lines: Vec<&'a str>,
+}
+
+/// Entry of the rewritten args section.
+enum NewEntry<'a, 'b> {
+    /// An entry kept as it is written.
+    Kept(&'b Chunk<'a>),
+    /// An entry of a param that was not documented.
+    Added(Param<'a>),
+}
+
+/// Rewrites the args section of a google docstring to follow the signature, returning the
+/// byte range of the section within the docstring along with its new contents, or `None`
+/// if there is nothing to rewrite or it cannot be done without guessing.
+fn rewrite_args_section(
+    docstring: &str,
+    signature: &Signature,
+    changes: &Changes,
+    options: &CheckOptions,
+) -> Option<(Range<usize>, String)> {
+    let sections = parse_google_sections(docstring);
+
+    let mut args_sections = sections
+        .iter()
+        .filter(|section| section.kind == SectionKind::Args);
+    let section = args_sections.next()?;
+
+    // Docstrings that also read as numpy ones are ambiguous.
+    let is_ambiguous = args_sections.next().is_some()
+        || parse_numpy_sections(docstring)
+            .iter()
+            .any(|section| section.kind == SectionKind::Args);
+
+    if is_ambiguous {
+        return None;
+    }
+
+    let text = &docstring[section.range.clone()];
+    let (heading, body) = text.split_once('\n').unwrap_or((text, ""));
+
+    if body.contains('\t') || (options.break_on_empty_line && body.contains("\n\n")) {
+        return None;
+    }
+
+    let (chunks, gaps) = split_chunks(body)?;
+
+    let matches_parser = chunks.len() == section.entries.len()
+        && chunks
+            .iter()
+            .zip(&section.entries)
+            .all(|(chunk, entry)| entry.name == Some(chunk.name));
+
+    if !matches_parser {
+        return None;
+    }
+
+    let entry_indentation = match chunks.first() {
+        Some(chunk) => {
+            chunk.lines[0][..chunk.lines[0].len() - chunk.lines[0].trim_start().len()].to_string()
+        }
+        None => {
+            let heading_indentation = heading.len() - heading.trim_start().len();
+            " ".repeat(heading_indentation + 4)
+        }
+    };
+
+    let new_entries = plan_entries(&chunks, signature, changes, options)?;
+
+    // Entries added among typed ones are typed after the signature.
+    let typed = chunks.is_empty() || section.entries.iter().any(|entry| entry.typ.is_some());
+
+    let rendered: Vec<String> = new_entries
+        .iter()
+        .map(|entry| match entry {
+            NewEntry::Kept(chunk) => chunk.lines.join("\n"),
+            NewEntry::Added(param) => {
+                let typ = match param.typ {
+                    Some(typ) if typed => format!(" ({typ})"),
+                    _ => String::new(),
+                };
+
+                format!(
+                    "{entry_indentation}{}{typ}: {PLACEHOLDER_DESCRIPTION}",
+                    param.name
+                )
+            }
+        })
+        .collect();
+
+    // Entries separated by blank lines stay so, as long as they all were.
+    let separator = match gaps {
+        Gaps::None => "\n",
+        Gaps::All => "\n\n",
+        Gaps::Mixed => return None,
+    };
+
+    let mut replacement = heading.to_string();
+
+    if !rendered.is_empty() {
+        replacement.push('\n');
+        replacement.push_str(&rendered.join(separator));
+    }
+
+    if replacement == text {
+        return None;
+    }
+
+    let mut fixed = docstring.to_string();
+    fixed.replace_range(section.range.clone(), &replacement);
+
+    // The rest of the docstring is expected to read the same, and the args to be the
+    // planned ones.
+    let reparsed = parse_google_sections(&fixed);
+
+    let expected_names: Vec<_> = new_entries
+        .iter()
+        .map(|entry| match entry {
+            NewEntry::Kept(chunk) => chunk.name,
+            NewEntry::Added(param) => param.name,
+        })
+        .collect();
+
+    let round_trips = reparsed.len() == sections.len()
+        && sections.iter().zip(&reparsed).all(|(original, reparsed)| {
+            original.kind == reparsed.kind
+                && (original.kind == SectionKind::Args || original.entries == reparsed.entries)
+        })
+        && reparsed
+            .iter()
+            .find(|section| section.kind == SectionKind::Args)
+            .is_some_and(|section| {
+                section
+                    .entries
+                    .iter()
+                    .map(|entry| entry.name)
+                    .eq(expected_names.iter().map(|&name| Some(name)))
+            });
+
+    round_trips.then(|| (section.range.clone(), replacement))
+}
+
+/// Whether the entries of a section are separated by blank lines.
+enum Gaps {
+    None,
+    All,
+    Mixed,
+}
+
+/// Splits the lines that follow the heading of an args section into its entries, which
+/// start at the lines indented as much as the first one, or `None` if some line is
+/// indented less.
+fn split_chunks(body: &str) -> Option<(Vec<Chunk<'_>>, Gaps)> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut blank_gaps = Vec::new();
+    let mut pending_blank_lines = 0;
+    let mut entry_indentation = None;
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            pending_blank_lines += 1;
+            continue;
+        }
+
+        let indentation = line.len() - line.trim_start().len();
+        let entry_indentation = *entry_indentation.get_or_insert(indentation);
+
+        // Entries indented differently are left for people to sort out.
+        if indentation < entry_indentation {
+            return None;
+        }
+
+        if indentation == entry_indentation {
+            if !chunks.is_empty() {
+                blank_gaps.push(pending_blank_lines > 0);
+            }
+
+            let name = trimmed
+                .split_once(':')
+                .map_or(trimmed, |(arg, _)| arg)
+                .split(' ')
+                .next()
+                .unwrap_or_default();
+
+            chunks.push(Chunk {
+                name,
+                lines: vec![line],
+            });
+        } else {
+            // Blank lines within the description of an entry are kept.
+            let chunk = chunks.last_mut()?;
+            chunk
+                .lines
+                .extend(std::iter::repeat_n("", pending_blank_lines));
+            chunk.lines.push(line);
+        }
+
+        pending_blank_lines = 0;
+    }
+
+    let gaps = if blank_gaps.iter().all(|&gap| gap) && !blank_gaps.is_empty() {
+        Gaps::All
+    } else if blank_gaps.iter().any(|&gap| gap) {
+        Gaps::Mixed
+    } else {
+        Gaps::None
+    };
+
+    Some((chunks, gaps))
+}
+
+/// Decides the entries of the rewritten args section out of the documented ones, or
+/// `None` if some of them cannot be told apart.
+fn plan_entries<'a, 'b>(
+    chunks: &'b [Chunk<'a>],
+    signature: &Signature<'a, '_>,
+    changes: &Changes,
+    options: &CheckOptions,
+) -> Option<Vec<NewEntry<'a, 'b>>> {
+    let names: Vec<_> = chunks.iter().map(|chunk| chunk.name).collect();
+
+    let has_duplicates = names
+        .iter()
+        .enumerate()
+        .any(|(i, name)| names[..i].contains(name));
+    let documents_receiver = names.iter().any(|&name| {
+        matches!(name, "self" | "cls") && !signature.params.iter().any(|p| p.name == name)
+    });
+
+    if has_duplicates || documents_receiver {
+        return None;
+    }
+
+    // The chunk documenting each param, by exact name or else differing only by case.
+    let mut documented: Vec<Option<usize>> = vec![None; signature.params.len()];
+    let mut is_used = vec![false; chunks.len()];
+
+    for (i, param) in signature.params.iter().enumerate() {
+        let exact = (0..chunks.len()).find(|&j| !is_used[j] && chunks[j].name == param.name);
+        let found = exact.or_else(|| {
+            (0..chunks.len())
+                .find(|&j| !is_used[j] && chunks[j].name.eq_ignore_ascii_case(param.name))
+        });
+
+        if let Some(j) = found {
+            documented[i] = Some(j);
+            is_used[j] = true;
+        }
+    }
+
+    // Extra entries are left if they are not removed, and so are the keys accepted by
+    // `**kwargs` when they may be documented as if they were args.
+    let keeps_extra =
+        !changes.remove_extra || (options.allow_kwargs_docs && signature.has_var_kwargs);
+
+    let extra: Vec<_> = (0..chunks.len()).filter(|&j| !is_used[j]).collect();
+
+    let missing: Vec<_> = (0..signature.params.len())
+        .filter(|&i| documented[i].is_none() && changes.add_missing)
+        .filter(|&i| {
+            let param = &signature.params[i];
+
+            signature.checked.contains(param)
+                && !(options.allow_omitting_defaulted_args && param.has_default)
+        })
+        .collect();
+
+    let entry_of = |i: usize| match documented[i] {
+        Some(j) => Some(NewEntry::Kept(&chunks[j])),
+        None if missing.contains(&i) => Some(NewEntry::Added(signature.params[i])),
+        None => None,
+    };
+
+    let mut entries = Vec::new();
+
+    if changes.follow_signature {
+        entries.extend((0..signature.params.len()).filter_map(entry_of));
+    } else {
+        // The documented order is kept, with the new entries at the end.
+        entries.extend(
+            chunks
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| is_used[j] || keeps_extra)
+                .map(|(_, chunk)| NewEntry::Kept(chunk)),
+        );
+        entries.extend(
+            missing
+                .iter()
+                .map(|&i| NewEntry::Added(signature.params[i])),
+        );
+
+        return Some(entries);
+    }
+
+    if keeps_extra {
+        entries.extend(extra.iter().map(|&j| NewEntry::Kept(&chunks[j])));
+    }
+
+    Some(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::RuleSelection;
+    use crate::rules_checking::{check_source, check_source_report, CompliancyChecker};
+
+    use super::*;
+
+    fn get_parser() -> Parser {
+        let mut parser = Parser::new();
+
+        parser
+            .set_language(&tree_sitter_python::language())
+            .expect("should be able to load Python grammar");
+
+        parser
+    }
+
+    fn fix(source_code: &str, options: &CheckOptions) -> String {
+        let fixes = plan_fixes(&mut get_parser(), source_code, None, options);
+
+        apply_fixes(source_code, &fixes)
+    }
+
+    #[test]
+    fn fixing_args() {
+        let source_code = r#"def f(y: int, x: str, z):
+    """Hey.
+
+    Args:
+        x (str): First var.
+            Continues here.
+
+            And here.
+        y (int): Second var.
+        w (int): Gone.
+
+    Returns:
+        int: Something.
+    """
+    return 1
+
+
+class A:
+    def g(self, renamed, *args):
+        """Hey.
+
+        Args:
+          old: Kept description.
+          *args: Extra args.
+        """
+"#;
+
+        let fixes = plan_fixes(
+            &mut get_parser(),
+            source_code,
+            None,
+            &CheckOptions::default(),
+        );
+
+        assert_eq!(fixes.len(), 2);
+        assert_eq!(fixes[0].row, 0);
+        assert_eq!(
+            fixes[0].rules,
+            [Rule::MissingArg, Rule::ExtraArg, Rule::ArgsOrder]
+        );
+        assert_eq!(fixes[1].function, "g");
+
+        assert_eq!(
+            apply_fixes(source_code, &fixes),
+            r#"def f(y: int, x: str, z):
+    """Hey.
+
+    Args:
+        y (int): Second var.
+        x (str): First var.
+            Continues here.
+
+            And here.
+        z: _description_
+
+    Returns:
+        int: Something.
+    """
+    return 1
+
+
+class A:
+    def g(self, renamed, *args):
+        """Hey.
+
+        Args:
+          renamed: _description_
+          *args: Extra args.
+        """
+"#
+        );
+    }
+
+    #[test]
+    fn fixing_into_empty_section() {
+        let source_code = r#"def f(x: int, y=1):
+    """Hey.
+
+    Args:
+    """
+"#;
+
+        assert_eq!(
+            fix(source_code, &CheckOptions::default()),
+            r#"def f(x: int, y=1):
+    """Hey.
+
+    Args:
+        x (int): _description_
+        y: _description_
+    """
+"#
+        );
+
+        let options = CheckOptions {
+            allow_omitting_defaulted_args: true,
+            ..Default::default()
+        };
+
+        assert!(fix(source_code, &options).contains("x (int): _description_\n    \"\"\""));
+    }
+
+    #[test]
+    fn keeping_documented_order() {
+        let source_code = r#"def f(x, y, z):
+    """Hey.
+
+    Args:
+        y: Second var.
+
+        x: First var.
+    """
+"#;
+
+        let options = CheckOptions {
+            allow_out_of_order_args: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            fix(source_code, &options),
+            r#"def f(x, y, z):
+    """Hey.
+
+    Args:
+        y: Second var.
+
+        x: First var.
+
+        z: _description_
+    """
+"#
+        );
+    }
+
+    #[test]
+    fn fixing_only_some_rules() {
+        let source_code = r#"def f(a, b, c):
+    """Hey.
+
+    Args:
+        c: Third var.
+        a: First var.
+        d: Gone.
+    """
+"#;
+
+        let fix_only = |code: &str| CheckOptions {
+            fix_only: vec![code.parse().unwrap()],
+            ..Default::default()
+        };
+
+        let fixes = plan_fixes(&mut get_parser(), source_code, None, &fix_only("PSD202"));
+
+        assert_eq!(fixes[0].rules, [Rule::ExtraArg]);
+        assert!(apply_fixes(source_code, &fixes)
+            .contains("Args:\n        c: Third var.\n        a: First var.\n    \"\"\""));
+
+        // The new entry goes at the end, since the documented order is not fixed.
+        assert!(fix(source_code, &fix_only("PSD201")).contains(
+            "c: Third var.\n        a: First var.\n        d: Gone.\n        b: _description_\n"
+        ));
+
+        assert!(fix(source_code, &fix_only("PSD204"))
+            .contains("a: First var.\n        c: Third var.\n        d: Gone.\n"));
+
+        // The reported rules are fixed by default.
+        let options = CheckOptions {
+            rules: RuleSelection {
+                select: vec!["PSD202".parse().unwrap()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert_eq!(
+            fix(source_code, &options),
+            fix(source_code, &fix_only("PSD202"))
+        );
+
+        assert!(plan_fixes(&mut get_parser(), source_code, None, &fix_only("PSD1")).is_empty());
+    }
+
+    #[test]
+    fn unfixable_docstrings() {
+        let options = CheckOptions::default();
+
+        // Numpy docstrings are not fixed yet.
+        let numpy = r#"def f(x, y):
+    """Hey.
+
+    Parameters
+    ----------
+    x
+        First var.
+    """
+"#;
+
+        // Entries that are not told apart by the indentation.
+        let weird = r#"def f(x, y):
+    """Hey.
+
+    Args:
+        x: First var.
+      z: Who knows.
+    """
+"#;
+
+        // Repeated entries.
+        let duplicated = r#"def f(x, y):
+    """Hey.
+
+    Args:
+        x: First var.
+        x: First var again.
+    """
+"#;
+
+        for source_code in [numpy, weird, duplicated] {
+            assert!(plan_fixes(&mut get_parser(), source_code, None, &options).is_empty());
+        }
+
+        let google_only = CheckOptions {
+            docstyle: DocstringStyle::Numpy,
+            ..Default::default()
+        };
+
+        assert!(plan_fixes(
+            &mut get_parser(),
+            "def f(x):\n    \"\"\"Hey.\n\n    Args:\n    \"\"\"\n",
+            None,
+            &google_only
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn attached_fixes() {
+        let source_code = r#"def f(a, b, c):
+    """Hey.
+
+    Args:
+        c: Third var.
+        a: First var.
+    """
+
+
+def g(x):
+    """Hey.
+
+    Parameters
+    ----------
+    y
+        Gone.
+    """
+"#;
+
+        let options = CheckOptions {
+            attach_fixes: true,
+            ..Default::default()
+        };
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let report = check_source_report(source_code, None, checker, &options, None).unwrap();
+
+            let (fixable, unfixable): (Vec<_>, Vec<_>) = report
+                .diagnostics
+                .iter()
+                .partition(|diagnostic| diagnostic.fix.is_some());
+
+            assert_eq!(fixable.len(), 2);
+            assert_eq!(unfixable.len(), 2);
+            assert!(unfixable
+                .iter()
+                .all(|diagnostic| diagnostic.function_row == Some(9)));
+
+            // Every diagnostic of the function carries the same fix, to be applied once.
+            let mut fixes: Vec<Fix> = fixable
+                .into_iter()
+                .filter_map(|diagnostic| diagnostic.fix.clone())
+                .collect();
+            fixes.dedup();
+
+            assert_eq!(fixes.len(), 1);
+            assert_eq!(fixes[0].title, "update Args section to match signature");
+
+            let fixed = apply_fixes(source_code, &fixes);
+
+            let remaining = check_source(&fixed, None, checker, &options, None).unwrap();
+
+            assert_eq!(remaining.len(), 2);
+            assert!(remaining.iter().all(|diagnostic| diagnostic.fix.is_none()));
+        }
+    }
+
+    #[test]
+    fn fixed_source_passes() {
+        let source_code = r#"def f(a, b, c):
+    """Hey.
+
+    Args:
+        c: Third var.
+        a: First var.
+        d: Gone.
+        e: Also gone.
+    """
+"#;
+
+        let options = CheckOptions::default();
+        let fixed = fix(source_code, &options);
+
+        assert!(fixed.contains("a: First var.\n        b: _description_\n        c: Third var."));
+        assert!(plan_fixes(&mut get_parser(), &fixed, None, &options).is_empty());
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            assert!(check_source(&fixed, None, checker, &options, None)
+                .unwrap()
+                .is_empty());
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::diagnostics::Diagnostic;
+use crate::rules_checking::SourceReport;
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #24292f; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2em; }
+th, td { border: 1px solid #d0d7de; padding: 0.4em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+code { font-family: monospace; }
+.stats { display: flex; gap: 2em; margin-bottom: 2em; }
+.stat { font-size: 1.5em; font-weight: bold; }
+.clean { color: #1a7f37; }";
+
+/// Renders the results as a self-contained HTML report, with the totals at the top and a
+/// section per file with errors, listing the params from the signature and from the
+/// docstring side by side for the errors that compare them.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::html::to_html;
+///
+/// let html = to_html(&[]);
+///
+/// assert!(html.starts_with("<!DOCTYPE html>"));
+/// assert!(html.contains("All docstrings up to date"));
+/// ```
+pub fn to_html(reports: &[SourceReport]) -> String {
+    let functions: usize = reports.iter().map(|r| r.functions.len()).sum();
+    let violations: usize = reports.iter().map(|r| r.diagnostics.len()).sum();
+    let failing = reports.iter().filter(|r| !r.diagnostics.is_empty());
+
+    let stats = [
+        ("Files scanned", reports.len()),
+        ("Functions checked", functions),
+        ("Files with errors", failing.clone().count()),
+        ("Violations", violations),
+    ]
+    .map(|(label, value)| format!("<div><div class=\"stat\">{value}</div>{label}</div>"))
+    .concat();
+
+    let body = if violations == 0 {
+        "<p class=\"clean\">✅ All docstrings up to date.</p>\n".to_string()
+    } else {
+        failing.map(file_section).collect()
+    };
+
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>pystaleds report</title>
+<style>
+{STYLE}
+</style>
+</head>
+<body>
+<h1>pystaleds report</h1>
+<div class=\"stats\">{stats}</div>
+{body}</body>
+</html>
+"
+    )
+}
+
+/// Renders the section of a file with its errors.
+fn file_section(report: &SourceReport) -> String {
+    let path = report
+        .path
+        .as_ref()
+        .map_or(String::new(), |path| path.to_string_lossy().to_string());
+
+    let rows: String = report.diagnostics.iter().map(diagnostic_row).collect();
+
+    format!(
+        "<section>
+<h2><code>{}</code></h2>
+<table>
+<tr><th>Function</th><th>Line</th><th>Problem</th><th>Signature</th><th>Docstring</th></tr>
+{rows}</table>
+</section>
+",
+        escape_html(&path),
+    )
+}
+
+fn diagnostic_row(diagnostic: &Diagnostic) -> String {
+    let (signature, docstring) = diagnostic
+        .entries
+        .as_ref()
+        .map_or_else(Default::default, |entries| {
+            (entry_list(&entries.expected), entry_list(&entries.actual))
+        });
+
+    format!(
+        "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{signature}</td><td>{docstring}</td></tr>\n",
+        escape_html(&diagnostic.function),
+        diagnostic.row.map_or(String::new(), |row| (row + 1).to_string()),
+        escape_html(&format!("{} {}", diagnostic.rule.code(), diagnostic.message)),
+    )
+}
+
+/// Lists the entries (such as params) with their types, one per line.
+fn entry_list(entries: &[(String, Option<String>)]) -> String {
+    entries
+        .iter()
+        .map(|(name, type_)| match type_ {
+            Some(type_) => format!("<code>{}: {}</code>", escape_html(name), escape_html(type_)),
+            None => format!("<code>{}</code>", escape_html(name)),
+        })
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+/// Escapes the characters with special meaning in HTML text and attributes.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::diagnostics::{EntriesComparison, Rule, Severity, Spans};
+
+    use super::*;
+
+    #[test]
+    fn html() {
+        let reports = [
+            SourceReport {
+                path: Some(PathBuf::from("src/module.py")),
+                diagnostics: vec![Diagnostic {
+                    rule: Rule::MissingArg,
+                    path: Some(PathBuf::from("src/module.py")),
+                    function: "f".to_string(),
+                    row: Some(3),
+                    column: Some(0),
+                    function_row: Some(3),
+                    message: "Docstring args not matching".to_string(),
+                    severity: Severity::Error,
+                    spans: Spans::default(),
+                    entries: Some(EntriesComparison::new(
+                        &[("x", Some("Dict<str, int>")), ("y", None)],
+                        &[("x", None)],
+                    )),
+                    fix: None,
+                }],
+                functions: Vec::new(),
+                suppressed: 0,
+                definitions: 0,
+            },
+            SourceReport {
+                path: Some(PathBuf::from("src/clean.py")),
+                diagnostics: Vec::new(),
+                functions: Vec::new(),
+                suppressed: 0,
+                definitions: 0,
+            },
+        ];
+
+        let html = to_html(&reports);
+
+        assert!(html.contains("<div><div class=\"stat\">2</div>Files scanned</div>"));
+        assert!(html.contains("<div><div class=\"stat\">1</div>Violations</div>"));
+        assert!(html.contains("<h2><code>src/module.py</code></h2>"));
+        assert!(!html.contains("src/clean.py"));
+        assert!(html.contains(
+            "<tr><td><code>f</code></td><td>4</td><td>PSD201 Docstring args not matching</td>\
+             <td><code>x: Dict&lt;str, int&gt;</code><br><code>y</code></td>\
+             <td><code>x</code></td></tr>"
+        ));
+        assert!(!html.contains("All docstrings up to date"));
+    }
+}
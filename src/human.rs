@@ -0,0 +1,306 @@
+use std::io::IsTerminal;
+use std::path::Path;
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::rules_checking::SourceReport;
+use crate::stats::Stats;
+
+const BOLD: &str = "1";
+const DIM: &str = "2";
+const UNDERLINE: &str = "4";
+const RED: &str = "31";
+const GREEN: &str = "32";
+const YELLOW: &str = "33";
+const CYAN: &str = "36";
+
+/// Whether the human-readable output is colored.
+#[derive(Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Colored if stdout is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Determines if the output should be colored.
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// Renders the results for humans, with a header per file followed by a line per
+/// diagnostic, optionally colored with ANSI escape codes.
+pub struct HumanRenderer {
+    color: bool,
+}
+
+impl HumanRenderer {
+    pub fn new(color: bool) -> Self {
+        Self { color }
+    }
+
+    /// Renders the diagnostics of a file, or `None` if it has none.
+    ///
+    /// The entries of the diagnostics that compare them are listed below the diagnostic,
+    /// highlighting the ones that are only in the signature or only in the docstring.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pystaleds::human::HumanRenderer;
+    /// use pystaleds::rules_checking::SourceReport;
+    ///
+    /// assert!(HumanRenderer::new(false).render(&SourceReport::default()).is_none());
+    /// ```
+    pub fn render(&self, report: &SourceReport) -> Option<String> {
+        if report.diagnostics.is_empty() {
+            return None;
+        }
+
+        let path = report
+            .path
+            .as_ref()
+            .map_or(String::new(), |path| path.to_string_lossy().to_string());
+
+        let mut rendered = self.paint(&[BOLD, UNDERLINE], &path);
+
+        for diagnostic in &report.diagnostics {
+            rendered.push('\n');
+            rendered.push_str(&self.render_diagnostic(diagnostic));
+        }
+
+        Some(rendered)
+    }
+
+    fn render_diagnostic(&self, diagnostic: &Diagnostic) -> String {
+        let location = match (diagnostic.row, diagnostic.column) {
+            (Some(row), Some(column)) => format!("{}:{}", row + 1, column + 1),
+            (Some(row), None) => (row + 1).to_string(),
+            (None, _) => "-".to_string(),
+        };
+
+        let severity_color = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        let mut rendered = format!(
+            "  {} {} {} {}",
+            self.paint(&[CYAN], &location),
+            self.paint(&[BOLD, YELLOW], &format!("`{}`", diagnostic.function)),
+            self.paint(&[BOLD, severity_color], diagnostic.rule.code()),
+            diagnostic.message,
+        );
+
+        if let Some(entries) = &diagnostic.entries {
+            rendered.push_str(&format!(
+                "\n      signature: {}\n      docstring: {}",
+                self.render_entries(&entries.expected, &entries.actual, RED),
+                self.render_entries(&entries.actual, &entries.expected, YELLOW),
+            ));
+        }
+
+        rendered
+    }
+
+    /// Lists the entries, highlighting the ones not in the other side of the comparison.
+    fn render_entries(
+        &self,
+        entries: &[(String, Option<String>)],
+        others: &[(String, Option<String>)],
+        highlight: &str,
+    ) -> String {
+        if entries.is_empty() {
+            return self.paint(&[DIM], "(none)");
+        }
+
+        entries
+            .iter()
+            .map(|entry| {
+                let text = match &entry.1 {
+                    Some(type_) => format!("{}: {type_}", entry.0),
+                    None => entry.0.clone(),
+                };
+
+                if others.contains(entry) {
+                    text
+                } else {
+                    self.paint(&[BOLD, highlight], &text)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Renders the path of a file that had errors and no longer has them, such as when
+    /// watching the files.
+    pub fn resolved(&self, path: &Path) -> String {
+        format!(
+            "{} {}",
+            self.paint(&[BOLD, UNDERLINE], &path.to_string_lossy()),
+            self.paint(&[GREEN], "✅ Resolved")
+        )
+    }
+
+    /// Renders the summary of the run with how long it took, and whether it succeeded or
+    /// was interrupted.
+    pub fn summary(
+        &self,
+        stats: &Stats,
+        count_trivial_files: bool,
+        cancelled: bool,
+        elapsed: Duration,
+    ) -> String {
+        let mut summary = self.paint(
+            &[BOLD],
+            &format!(
+                "{} in {:.2}s",
+                stats.summary(count_trivial_files),
+                elapsed.as_secs_f64()
+            ),
+        );
+
+        if cancelled {
+            summary.push('\n');
+            summary.push_str(&self.paint(
+                &[YELLOW],
+                &format!(
+                    "⚠️ Interrupted! Files with errors found so far: {}",
+                    stats.files_with_errors
+                ),
+            ));
+        } else if stats.files_with_errors == 0 {
+            summary.push('\n');
+            summary.push_str(&self.paint(&[GREEN], "✅ Success!"));
+        }
+
+        summary
+    }
+
+    fn paint(&self, codes: &[&str], text: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{text}\x1b[0m", codes.join(";"))
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::diagnostics::{EntriesComparison, Rule, Spans};
+
+    use super::*;
+
+    fn report() -> SourceReport {
+        SourceReport {
+            path: Some(PathBuf::from("src/module.py")),
+            diagnostics: vec![
+                Diagnostic {
+                    rule: Rule::MissingArg,
+                    path: Some(PathBuf::from("src/module.py")),
+                    function: "f".to_string(),
+                    row: Some(3),
+                    column: Some(0),
+                    function_row: Some(3),
+                    message: "Docstring args not matching".to_string(),
+                    severity: Severity::Error,
+                    spans: Spans::default(),
+                    entries: Some(EntriesComparison::new(
+                        &[("x", Some("int")), ("y", None)],
+                        &[("x", Some("int"))],
+                    )),
+                    fix: None,
+                },
+                Diagnostic {
+                    rule: Rule::MissingModuleDocstring,
+                    path: Some(PathBuf::from("src/module.py")),
+                    function: "<module>".to_string(),
+                    row: None,
+                    column: None,
+                    function_row: None,
+                    message: "Module docstring missing".to_string(),
+                    severity: Severity::Error,
+                    spans: Spans::default(),
+                    entries: None,
+                    fix: None,
+                },
+            ],
+            functions: Vec::new(),
+            suppressed: 0,
+            definitions: 0,
+        }
+    }
+
+    #[test]
+    fn render() {
+        assert_eq!(
+            HumanRenderer::new(false).render(&report()).unwrap(),
+            "src/module.py
+  4:1 `f` PSD201 Docstring args not matching
+      signature: x: int, y
+      docstring: x: int
+  - `<module>` PSD105 Module docstring missing"
+        );
+    }
+
+    #[test]
+    fn render_colored() {
+        let rendered = HumanRenderer::new(true).render(&report()).unwrap();
+
+        assert!(rendered.starts_with("\x1b[1;4msrc/module.py\x1b[0m\n"));
+        assert!(rendered.contains("signature: x: int, \x1b[1;31my\x1b[0m\n"));
+        assert!(rendered.contains("docstring: x: int\n"));
+    }
+
+    #[test]
+    fn summary() {
+        let stats = Stats {
+            files: 2,
+            ..Default::default()
+        };
+
+        let elapsed = Duration::from_millis(1234);
+
+        assert_eq!(
+            HumanRenderer::new(false).summary(&stats, false, false, elapsed),
+            "Checked 2 files, 0 with errors, and 0 functions, 0 with errors; skipped 0 files; \
+             found 0 violations in 1.23s\n\
+             ✅ Success!"
+        );
+        assert_eq!(
+            HumanRenderer::new(true).summary(&stats, false, true, elapsed),
+            "\x1b[1mChecked 2 files, 0 with errors, and 0 functions, 0 with errors; skipped 0 \
+             files; found 0 violations in 1.23s\x1b[0m\n\
+             \x1b[33m⚠️ Interrupted! Files with errors found so far: 0\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn resolved() {
+        let path = Path::new("src/module.py");
+
+        assert_eq!(
+            HumanRenderer::new(false).resolved(path),
+            "src/module.py ✅ Resolved"
+        );
+        assert_eq!(
+            HumanRenderer::new(true).resolved(path),
+            "\x1b[1;4msrc/module.py\x1b[0m \x1b[32m✅ Resolved\x1b[0m"
+        );
+    }
+}
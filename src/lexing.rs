@@ -1,28 +1,67 @@
 use anyhow::{anyhow, Result};
 use logos::{Lexer, Logos, Source};
 
-use crate::ast_parsing::{FunctionInfo, FunctionLocation};
-
+use crate::ast_parsing::{
+    apply_type_comment, parse_suppression, suppression_above, FunctionInfo, FunctionLocation,
+    Param, Suppression,
+};
+use crate::diagnostics::Spans;
+
+/// Finds the next function in the lexer, leaving out of its params the receiver of methods
+/// (see [`method_context`]), the ones in `ignored_params` and, if `skip_args_and_kwargs`,
+/// `*args` and `**kwargs`.
 pub fn get_next_function_info<'a, 'b>(
     lexer: &mut Lexer<'a, Token>,
-    params: &'b mut Vec<(&'a str, Option<&'a str>)>,
+    params: &'b mut Vec<Param<'a>>,
     skip_args_and_kwargs: bool,
+    ignored_params: &[String],
 ) -> Option<FunctionInfo<'a, 'b>> {
     params.clear();
 
+    let is_ignored = |name: &str| {
+        ignored_params.iter().any(|ignored| ignored == name)
+            || (skip_args_and_kwargs && name.starts_with('*'))
+    };
+
     while let Some(next) = lexer.next() {
         let Ok(Token::DefStart) = next else {
             continue;
         };
 
+        let def_start = lexer.span().start;
+        let row = row_of(lexer, def_start);
+
+        let method_context = method_context(lexer, def_start);
+        let mut skip_receiver = method_context == Some(false);
+
         lexer.next(); // Going to function name;
-        let function_name = FunctionLocation::Name(lexer.slice());
+        let name = lexer.slice();
+        let function_name = FunctionLocation::Name(name);
 
         lexer.next(); // Going to first parenthesis;
+        let params_start = lexer.span().start;
         let mut current = lexer.next(); // Going to first variable;
 
-        while let Some(Ok(Token::Text)) = current {
+        loop {
+            // The separators of positional-only (`/`) and keyword-only (`*`) params, which
+            // are never params themselves.
+            let is_separator = match current {
+                Some(Ok(Token::Slash)) => true,
+                Some(Ok(Token::Text)) => lexer.slice() == "*",
+                _ => break,
+            };
+
+            if is_separator {
+                if let Some(Ok(Token::ParClose)) = lexer.next() {
+                    break;
+                }
+
+                current = lexer.next();
+                continue;
+            }
+
             let param_name = lexer.slice();
+            let is_receiver = std::mem::take(&mut skip_receiver);
 
             let next = lexer.next();
             match next {
@@ -31,11 +70,10 @@ pub fn get_next_function_info<'a, 'b>(
 
                     let (typ, finished_on) = extract_possibly_parenthesized_content(lexer).ok()?;
 
-                    if param_name != "self"
-                        && !(skip_args_and_kwargs
-                            && (param_name.starts_with('*') || param_name.starts_with("**")))
-                    {
-                        params.push((param_name, Some(typ)));
+                    if !is_receiver {
+                        let has_default = matches!(finished_on, FinishedOn::Equals);
+
+                        params.push(Param::new(param_name, Some(typ), has_default));
                     }
 
                     match finished_on {
@@ -59,11 +97,8 @@ pub fn get_next_function_info<'a, 'b>(
 
                     let (_, finished_on) = extract_possibly_parenthesized_content(lexer).ok()?;
 
-                    if param_name != "self"
-                        && !(skip_args_and_kwargs
-                            && (param_name.starts_with('*') || param_name.starts_with("**")))
-                    {
-                        params.push((param_name, None));
+                    if !is_receiver {
+                        params.push(Param::new(param_name, None, true));
                     }
 
                     if let FinishedOn::ParClose = finished_on {
@@ -71,11 +106,8 @@ pub fn get_next_function_info<'a, 'b>(
                     }
                 }
                 _ => {
-                    if param_name != "self"
-                        && !(skip_args_and_kwargs
-                            && (param_name.starts_with('*') || param_name.starts_with("**")))
-                    {
-                        params.push((param_name, None));
+                    if !is_receiver {
+                        params.push(Param::new(param_name, None, false));
                     }
                 }
             }
@@ -83,45 +115,314 @@ pub fn get_next_function_info<'a, 'b>(
             current = lexer.next();
         }
 
+        let mut return_type = None;
+        let mut params_end = None;
+
         while let Some(ref t) = current {
-            if let Ok(Token::Colon) = t {
-                break;
+            match t {
+                Ok(Token::Colon) => break,
+                Ok(Token::Arrow) => {
+                    params_end = Some(lexer.span().start);
+                    return_type = extract_return_type(lexer);
+                    break;
+                }
+                _ => (),
             }
 
             current = lexer.next();
         }
 
-        while let Some(t) = current {
-            if let Ok(Token::Text) = t {
-                let start = lexer.span().start;
-
-                let slice = lexer.slice();
-
-                let docstring = if slice.starts_with(r#"""""#) {
-                    let end = lexer.source()[start + 3..]
-                        .find(r#"""""#)
-                        .expect("docstring should end");
-                    Some(&lexer.source()[start..(start + end + 6)])
-                } else if slice.starts_with(r#"'''"#) {
-                    let end = lexer.source()[start + 3..]
-                        .find(r#"'''"#)
-                        .expect("docstring should end");
-                    Some(&lexer.source()[start..(start + end + 6)])
-                } else {
-                    None
-                };
-
-                return Some(FunctionInfo {
-                    params,
-                    docstring,
-                    function_name,
-                });
+        // Comments are skipped by the lexer, so the type comment is taken from the rest
+        // of the line in which the signature ends.
+        let type_comment = lexer.source()[lexer.span().end..]
+            .lines()
+            .next()
+            .map(str::trim)
+            .filter(|rest| rest.starts_with('#'));
+
+        let type_comment_mismatch =
+            type_comment.and_then(|comment| apply_type_comment(comment, params, &mut return_type));
+
+        let def_line = lexer.source()[def_start..]
+            .lines()
+            .next()
+            .unwrap_or_default();
+        let def_line_comment = def_line.find('#').map(|comment| &def_line[comment..]);
+        let is_multiline = lexer.source()[def_start..lexer.span().end].contains('\n');
+        let suppression = [def_line_comment, type_comment.filter(|_| is_multiline)]
+            .into_iter()
+            .flatten()
+            .filter_map(parse_suppression)
+            .chain(suppression_above(lexer.source(), def_start))
+            .reduce(Suppression::merge);
+
+        let has_var_kwargs = params.iter().any(|param| param.name.starts_with("**"));
+
+        params.retain(|param| !is_ignored(param.name));
+
+        if current.is_none() {
+            break;
+        }
+
+        let signature_end = lexer.span().end;
+
+        // The parentheses of the params are not always left behind by the same token, so
+        // the closing one is found as the last one before the return annotation or colon.
+        let params_end = params_end.unwrap_or(lexer.span().start);
+        let parameters = lexer.source()[params_start..params_end]
+            .rfind(')')
+            .map(|close| params_start..params_start + close + 1);
+
+        // The body is taken from the source code instead of the tokens, since a body such
+        // as `...` has no tokens of its own and would make the lexer reach the next `def`.
+        let body = first_statement(&lexer.source()[lexer.span().end..]);
+
+        let docstring = if let Some(content) = body.strip_prefix(r#"""""#) {
+            content.find(r#"""""#).map(|end| &body[..end + 6])
+        } else if let Some(content) = body.strip_prefix(r#"'''"#) {
+            content.find(r#"'''"#).map(|end| &body[..end + 6])
+        } else {
+            None
+        };
+
+        let body_start = lexer.source().len() - body.len();
+
+        let docstring_start = docstring.map(|_| {
+            (
+                row_of(lexer, body_start),
+                body_start - line_start(lexer.source(), body_start),
+            )
+        });
+
+        let statement = match docstring {
+            Some(docstring) => first_statement(&body[docstring.len()..]),
+            None => body,
+        };
+
+        let statement = statement.lines().next().unwrap_or_default();
+        let statement = statement.split('#').next().unwrap_or_default().trim();
+
+        return Some(FunctionInfo {
+            params,
+            docstring,
+            function_name,
+            row: Some(row),
+            end_row: None,
+            column: Some(def_start - line_start(lexer.source(), def_start)),
+            docstring_start,
+            spans: Spans {
+                signature: Some(def_start..signature_end),
+                parameters,
+                docstring: docstring.map(|docstring| body_start..body_start + docstring.len()),
+            },
+            name,
+            return_type,
+            raises: None,
+            is_generator: None,
+            class_docstring: None,
+            decorators: Vec::new(),
+            type_comment_mismatch,
+            has_var_kwargs,
+            is_stub: statement == "..." || statement == "pass",
+            is_method: method_context.is_some(),
+            suppression,
+        });
+    }
+
+    None
+}
+
+/// Determines the row (0-based) of an offset of the source code, which must not precede
+/// the one of the previous call.
+///
+/// The extras of the lexer keep the last offset and its row, so that the source code is
+/// only scanned once.
+fn row_of(lexer: &mut Lexer<Token>, offset: usize) -> usize {
+    let (last_offset, last_row) = lexer.extras.row;
+
+    let row = last_row
+        + lexer.source()[last_offset..offset]
+            .bytes()
+            .filter(|&byte| byte == b'\n')
+            .count();
+
+    lexer.extras.row = (offset, row);
+
+    row
+}
+
+/// Finds the first statement of a body, skipping blank lines and comments, and returns
+/// the source code from it onwards.
+fn first_statement(body: &str) -> &str {
+    let mut rest = body.trim_start();
+
+    while rest.starts_with('#') {
+        rest = rest
+            .split_once('\n')
+            .map_or("", |(_, rest)| rest)
+            .trim_start();
+    }
+
+    rest
+}
+
+/// Determines, on a best-effort basis, if the function whose `def` starts at `def_start`
+/// is a method, i.e., if it is defined directly in the body of a class. If so, returns
+/// whether it is decorated with `staticmethod`, in which case it has no receiver.
+///
+/// The enclosing block is taken to be the closest previous line that is less indented
+/// than the `def`, leaving out the lines inside strings. The blocks are tracked in the
+/// extras of the lexer as it advances (see [`Blocks`]), which must not go back.
+fn method_context(lexer: &mut Lexer<Token>, def_start: usize) -> Option<bool> {
+    let source_code = lexer.source();
+    let line_start = line_start(source_code, def_start);
+    let indentation = indentation_of(&source_code[line_start..]);
+
+    // Nothing encloses a top-level function, so the lines before it need not be scanned.
+    if indentation == 0 {
+        return None;
+    }
+
+    let blocks = &mut lexer.extras.blocks;
+    blocks.advance(source_code, line_start);
+
+    let &(_, is_class) = blocks
+        .enclosing
+        .iter()
+        .rev()
+        .find(|&&(block_indentation, _)| block_indentation < indentation)?;
+
+    is_class.then_some(blocks.is_static)
+}
+
+/// State kept by the lexer along the source code, so that it is only scanned once.
+#[derive(Default)]
+pub struct Extras {
+    /// Last offset whose row was determined, and its row.
+    row: (usize, usize),
+    blocks: Blocks,
+}
+
+/// Blocks that enclose the line up to which the source code was scanned.
+#[derive(Default)]
+struct Blocks {
+    /// Offset of the start of the line up to which the source code was scanned.
+    scanned: usize,
+    /// Indentation of the lines that may enclose the next ones, and whether each starts a
+    /// class, from the outermost. Every line is more indented than the ones before it.
+    enclosing: Vec<(usize, bool)>,
+    /// Delimiter of the triple-quoted string in which the next line starts, if any.
+    string: Option<&'static str>,
+    /// Whether the lines right before the next one are decorators, one of which is
+    /// `@staticmethod`.
+    is_static: bool,
+}
+
+impl Blocks {
+    /// Scans the lines from the last one scanned up to the one starting at `line_start`.
+    fn advance(&mut self, source_code: &str, line_start: usize) {
+        for line in source_code[self.scanned..line_start].lines() {
+            let starts_in_string = self.string.is_some();
+            self.string = string_left_open(line, self.string);
+
+            let trimmed = line.trim();
+
+            if starts_in_string || trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
             }
 
-            current = lexer.next();
+            let indentation = indentation_of(line);
+
+            while self
+                .enclosing
+                .last()
+                .is_some_and(|&(block_indentation, _)| block_indentation >= indentation)
+            {
+                self.enclosing.pop();
+            }
+
+            self.enclosing
+                .push((indentation, trimmed.starts_with("class ")));
+            self.is_static =
+                trimmed.starts_with('@') && (self.is_static || trimmed == "@staticmethod");
         }
 
-        break;
+        self.scanned = line_start;
+    }
+}
+
+/// Finds the triple-quoted string left open at the end of a line, given the one in which
+/// the line starts, if any. Strings delimited by a single quote end in their line.
+fn string_left_open(line: &str, mut string: Option<&'static str>) -> Option<&'static str> {
+    let mut chars = line.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        let delimiter = match string {
+            Some(_) if c == '\\' => {
+                chars.next();
+                continue;
+            }
+            Some(delimiter) if line[i..].starts_with(delimiter) => {
+                string = None;
+                delimiter
+            }
+            None if c == '#' => break,
+            None if c == '"' || c == '\'' => {
+                let delimiter = [r#"""""#, "'''", "\"", "'"]
+                    .into_iter()
+                    .find(|delimiter| line[i..].starts_with(delimiter))
+                    .expect("every quote should start a delimiter");
+                string = Some(delimiter);
+                delimiter
+            }
+            _ => continue,
+        };
+
+        for _ in 1..delimiter.len() {
+            chars.next();
+        }
+    }
+
+    string.filter(|delimiter| delimiter.len() == 3)
+}
+
+/// Offset in which the line containing `offset` starts.
+fn line_start(source_code: &str, offset: usize) -> usize {
+    source_code[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+fn indentation_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Determines if the source code starts with a docstring, skipping blank lines, comments
+/// (including a shebang) and `from __future__` imports. Returns `None` if there is
+/// nothing else in the source code.
+pub(crate) fn has_module_docstring(source_code: &str) -> Option<bool> {
+    let first_statement = source_code.lines().map(str::trim).find(|line| {
+        !line.is_empty() && !line.starts_with('#') && !line.starts_with("from __future__ ")
+    })?;
+
+    let unprefixed = first_statement.trim_start_matches(['r', 'R', 'u', 'U', 'b', 'B', 'f', 'F']);
+
+    Some(unprefixed.starts_with(['"', '\'']))
+}
+
+/// Extracts the return annotation of a function, assuming the lexer is at the `->`
+/// token. Leaves the lexer at the colon that ends the signature.
+fn extract_return_type<'a>(lexer: &mut Lexer<'a, Token>) -> Option<&'a str> {
+    let start = lexer.span().end;
+    let mut depth = 0;
+
+    while let Some(tok) = lexer.next() {
+        match tok {
+            Ok(Token::ParOpen | Token::BraceOpen | Token::BracketOpen) => depth += 1,
+            Ok(Token::ParClose | Token::BraceClose | Token::BracketClose) => depth -= 1,
+            Ok(Token::Colon) if depth == 0 => {
+                return Some(lexer.source()[start..lexer.span().start].trim());
+            }
+            _ => (),
+        }
     }
 
     None
@@ -142,7 +443,13 @@ fn extract_possibly_parenthesized_content<'a>(
 
     let start = lexer.span().start;
 
-    while let Some(Ok(tok)) = lexer.next() {
+    while let Some(tok) = lexer.next() {
+        // Characters without a token of their own, such as the `.` in a default value
+        // like `1.0`, are part of the content.
+        let Ok(tok) = tok else {
+            continue;
+        };
+
         match tok {
             Token::ParOpen => count_par += 1,
             Token::ParClose => {
@@ -164,31 +471,27 @@ fn extract_possibly_parenthesized_content<'a>(
             Token::BraceClose => count_brace -= 1,
             Token::BracketOpen => count_bracket += 1,
             Token::BracketClose => count_bracket -= 1,
-            Token::Equals => {
-                if count_par == 0 && count_brace == 0 && count_bracket == 0 {
-                    let end = lexer.span().end - 1;
-                    return lexer
-                        .source()
-                        .slice(start..end)
-                        .map(|s| (s.trim(), FinishedOn::Equals))
-                        .ok_or(anyhow!(
+            Token::Equals if count_par == 0 && count_brace == 0 && count_bracket == 0 => {
+                let end = lexer.span().end - 1;
+                return lexer
+                    .source()
+                    .slice(start..end)
+                    .map(|s| (s.trim(), FinishedOn::Equals))
+                    .ok_or(anyhow!(
             "could not extract type after variable. This is probably indicative of a syntax error"
 
-                        ));
-                }
+                    ));
             }
-            Token::Comma => {
-                if count_par == 0 && count_brace == 0 && count_bracket == 0 {
-                    let end = lexer.span().end - 1;
-                    return lexer
-                        .source()
-                        .slice(start..end)
-                        .map(|s| (s.trim(), FinishedOn::Comma))
-                        .ok_or(anyhow!(
+            Token::Comma if count_par == 0 && count_brace == 0 && count_bracket == 0 => {
+                let end = lexer.span().end - 1;
+                return lexer
+                    .source()
+                    .slice(start..end)
+                    .map(|s| (s.trim(), FinishedOn::Comma))
+                    .ok_or(anyhow!(
             "could not extract type after variable. This is probably indicative of a syntax error"
 
-                        ));
-                }
+                    ));
             }
             _ => {}
         }
@@ -199,6 +502,7 @@ fn extract_possibly_parenthesized_content<'a>(
 
 #[derive(Logos, Debug, PartialEq)]
 #[logos(skip r"(\s+)|(\#.*\n)")] // Ignore this regex pattern between tokens
+#[logos(extras = Extras)]
 pub enum Token {
     // Tokens can be literal strings, of any length.
     #[token("def")]
@@ -231,6 +535,12 @@ pub enum Token {
     #[token("=")]
     Equals,
 
+    #[token("->")]
+    Arrow,
+
+    #[token("/")]
+    Slash,
+
     // Or regular expressions.
     #[regex("[a-zA-Z0-9\'\"_|*]+")]
     Text,
@@ -278,11 +588,15 @@ mod tests {
 
         let mut params = Vec::new();
 
-        let function_info = get_next_function_info(&mut lex, &mut params, true).unwrap();
+        let function_info = get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
 
         assert_eq!(
             function_info.params,
-            vec![("x", None), ("y", Some("int")), ("z", None)]
+            vec![
+                Param::new("x", None, false),
+                Param::new("y", Some("int"), true),
+                Param::new("z", None, true)
+            ]
         );
 
         assert_eq!(function_info.function_name, FunctionLocation::Name("f"));
@@ -290,6 +604,193 @@ mod tests {
         assert_eq!(function_info.docstring.unwrap(), r#""""Hello!""""#);
     }
 
+    #[test]
+    fn test_return_type() {
+        let def = r#"def f(x, y: int=2) -> Dict[str, int]:
+    """Hello!"""
+
+def g() -> None:
+    """Hello!"""
+
+def h(x):
+    """Hello!""""#;
+
+        let mut lex = Token::lexer(def);
+
+        let mut params = Vec::new();
+
+        let function_info = get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
+        assert_eq!(function_info.name, "f");
+        assert_eq!(function_info.return_type, Some("Dict[str, int]"));
+        assert_eq!(function_info.docstring.unwrap(), r#""""Hello!""""#);
+
+        let function_info = get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
+        assert_eq!(function_info.name, "g");
+        assert_eq!(function_info.return_type, Some("None"));
+
+        let function_info = get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
+        assert_eq!(function_info.name, "h");
+        assert_eq!(function_info.return_type, None);
+        assert_eq!(params, vec![Param::new("x", None, false)]);
+    }
+
+    #[test]
+    fn test_float_default() {
+        let def = r#"def f(x, timeout: float = 1.0, retries=3):
+    """Hello!""""#;
+
+        let mut lex = Token::lexer(def);
+
+        let mut params = Vec::new();
+
+        get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
+
+        assert_eq!(
+            params,
+            vec![
+                Param::new("x", None, false),
+                Param::new("timeout", Some("float"), true),
+                Param::new("retries", None, true)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_positional_only() {
+        let def = r#"def f(a, b: int, /, c=1):
+    """Hello!""""#;
+
+        let mut lex = Token::lexer(def);
+
+        let mut params = Vec::new();
+
+        get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
+
+        assert_eq!(
+            params,
+            vec![
+                Param::new("a", None, false),
+                Param::new("b", Some("int"), false),
+                Param::new("c", None, true)
+            ]
+        );
+
+        let def = r#"def f(a, b, /):
+    """Hello!""""#;
+
+        let mut lex = Token::lexer(def);
+
+        let function_info = get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
+
+        assert_eq!(
+            function_info.params,
+            vec![Param::new("a", None, false), Param::new("b", None, false)]
+        );
+        assert_eq!(function_info.docstring, Some(r#""""Hello!""""#));
+    }
+
+    #[test]
+    fn test_rows() {
+        let def = r#"def f(x):
+    """Hello!"""
+
+# def commented(y):
+def g(y):
+    """Hello!"""
+
+
+    def h(z):
+        pass
+"#;
+
+        let mut lex = Token::lexer(def);
+
+        let mut params = Vec::new();
+
+        let rows: Vec<_> = std::iter::from_fn(|| {
+            get_next_function_info(&mut lex, &mut params, true, &[]).map(|info| info.row)
+        })
+        .collect();
+
+        assert_eq!(rows, vec![Some(0), Some(4), Some(8)]);
+    }
+
+    #[test]
+    fn test_top_level_functions() {
+        let mut def = "def f(self, x):\n    pass\n\n".repeat(20_000);
+        def.push_str("class A:\n    def g(self, y):\n        pass\n");
+
+        let mut lex = Token::lexer(&def);
+
+        let mut params = Vec::new();
+
+        for _ in 0..20_000 {
+            get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
+            assert_eq!(
+                params,
+                vec![
+                    Param::new("self", None, false),
+                    Param::new("x", None, false)
+                ]
+            );
+        }
+
+        // The lines before top-level functions are never scanned for enclosing blocks.
+        assert_eq!(lex.extras.blocks.scanned, 0);
+
+        let function_info = get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
+        assert_eq!(function_info.params, vec![Param::new("y", None, false)]);
+    }
+
+    #[test]
+    fn test_strings_before_methods() {
+        let def = r#"class A:
+    def f(self, x):
+        """Does.
+
+Args:
+    x: Thing.
+        """
+        return '"""'
+
+    def g(self, y):
+        pass
+"#;
+
+        let mut lex = Token::lexer(def);
+
+        let mut params = Vec::new();
+
+        get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
+        assert_eq!(params, vec![Param::new("x", None, false)]);
+
+        get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
+        assert_eq!(params, vec![Param::new("y", None, false)]);
+    }
+
+    #[test]
+    fn test_keyword_only() {
+        let def = r#"def f(a, *, b: int, c=1):
+    """Hello!""""#;
+
+        for skip_args_and_kwargs in [true, false] {
+            let mut lex = Token::lexer(def);
+
+            let mut params = Vec::new();
+
+            get_next_function_info(&mut lex, &mut params, skip_args_and_kwargs, &[]).unwrap();
+
+            assert_eq!(
+                params,
+                vec![
+                    Param::new("a", None, false),
+                    Param::new("b", Some("int"), false),
+                    Param::new("c", None, true)
+                ]
+            );
+        }
+    }
+
     #[test]
     fn test_get_function_info2() {
         let def = r#"def f(a, b: str = "wololo", c=323):
@@ -299,9 +800,16 @@ mod tests {
 
         let mut params = Vec::new();
 
-        get_next_function_info(&mut lex, &mut params, true);
+        get_next_function_info(&mut lex, &mut params, true, &[]);
 
-        assert_eq!(params, vec![("a", None), ("b", Some("str")), ("c", None)]);
+        assert_eq!(
+            params,
+            vec![
+                Param::new("a", None, false),
+                Param::new("b", Some("str"), true),
+                Param::new("c", None, true)
+            ]
+        );
     }
 
     #[test]
@@ -323,13 +831,23 @@ def g(x,y):
 
         let mut params = Vec::new();
 
-        get_next_function_info(&mut lex, &mut params, true);
+        get_next_function_info(&mut lex, &mut params, true, &[]);
 
-        assert_eq!(params, vec![("a", None), ("b", None), ("c", None)]);
+        assert_eq!(
+            params,
+            vec![
+                Param::new("a", None, false),
+                Param::new("b", None, false),
+                Param::new("c", None, false)
+            ]
+        );
 
-        get_next_function_info(&mut lex, &mut params, true);
+        get_next_function_info(&mut lex, &mut params, true, &[]);
 
-        assert_eq!(params, vec![("x", None), ("y", None)]);
+        assert_eq!(
+            params,
+            vec![Param::new("x", None, false), Param::new("y", None, false)]
+        );
     }
 
     #[test]
@@ -362,11 +880,16 @@ def g(x,y):
     "#,
         );
         let mut params = Vec::new();
-        get_next_function_info(&mut lex, &mut params, true).unwrap();
+        get_next_function_info(&mut lex, &mut params, true, &[]).unwrap();
 
         assert_eq!(
             params,
-            vec![("x", None), ("y", None), ("z", None), ("a", None)]
+            vec![
+                Param::new("x", None, false),
+                Param::new("y", None, false),
+                Param::new("z", None, false),
+                Param::new("a", None, true)
+            ]
         );
 
         let mut lex = Token::lexer(
@@ -390,16 +913,16 @@ def g(x,y):
         );
 
         let mut params = Vec::new();
-        get_next_function_info(&mut lex, &mut params, false).unwrap();
+        get_next_function_info(&mut lex, &mut params, false, &[]).unwrap();
 
         assert_eq!(
             params,
             vec![
-                ("x", None),
-                ("y", None),
-                ("z", None),
-                ("*args", None),
-                ("a", None)
+                Param::new("x", None, false),
+                Param::new("y", None, false),
+                Param::new("z", None, false),
+                Param::new("*args", None, false),
+                Param::new("a", None, true)
             ]
         );
     }
@@ -1,5 +1,17 @@
 mod ast_parsing;
+pub mod baseline;
+pub mod cancellation;
+pub mod changes;
+pub mod config;
+pub mod conversion;
 mod debug;
+pub mod diagnostics;
+pub mod fixing;
+pub mod html;
+pub mod human;
 mod lexing;
+pub mod output;
 pub mod parsing;
 pub mod rules_checking;
+pub mod see_also;
+pub mod stats;
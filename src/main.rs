@@ -1,294 +1,268 @@
-use std::{env::set_current_dir, path::Path, sync::atomic::AtomicU32};
+mod cli;
 
-use anyhow::{anyhow, Result};
-use clap::{Parser, ValueEnum};
-use glob::glob;
-use pystaleds::rules_checking::{respects_rules, respects_rules_through_lexing, DocstringStyle};
-use rayon::prelude::*;
-use walkdir::DirEntry;
+use std::{path::Path, process::ExitCode};
+
+use anyhow::Result;
+use clap::builder::BoolishValueParser;
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::{ArgAction, CommandFactory, FromArgMatches, Parser, Subcommand};
+use pystaleds::conversion::ConversionTarget;
+use pystaleds::human::ColorChoice;
+use pystaleds::output::OutputFormat;
+use tracing_subscriber::filter::LevelFilter;
+
+use cli::args::{CheckCommand, FixCommand, RulesCommand, ShowConfigCommand};
+use cli::check::check;
+use cli::convert::convert;
+use cli::rules::{list_rules, show_config};
+use cli::Outcome;
 
 #[derive(Parser)]
-#[command(version, about, long_about=None)]
+#[command(
+    version,
+    about,
+    long_about=None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    after_help = "Exit codes:
+  0  No errors were found.
+  1  Errors were found in the checked files.
+  2  The run could not be completed, e.g. due to a bad path or usage, or an interruption."
+)]
 struct Args {
-    path: String,
-
-    #[arg(long, default_value_t = false, alias = "ah")]
-    /// Will allow hidden files.
-    allow_hidden: bool,
-
-    #[arg(long, default_value_t = false, alias = "be")]
-    /// Will consider that an "Args" section breaks on an empty line.
-    break_on_empty_line: bool,
-
-    #[arg(long, default_value_t = false, alias = "nd")]
-    /// Will consider an error for a docstring to be absent.
-    forbid_no_docstring: bool,
-
-    #[arg(long, default_value_t = false, alias = "na")]
-    /// Will consider an error for an "Args" or "Parameters" section to be absent.
-    forbid_no_args_in_docstring: bool,
-
-    #[arg(long, default_value_t = false, alias = "nu")]
-    /// Will consider an error for an arg in docstring to be untyped. Otherwise, only
-    /// raises an error if the docstring's type and the signature's type are mismatched.
-    forbid_untyped_docstrings: bool,
-
-    #[arg(long, default_value_t = false, alias = "ak")]
-    /// Will consider *args and **kwargs when checking the docstrings. If this flag is
-    /// not set, they are just completely ignored.
-    include_args_and_kwargs: bool,
-
-    #[arg(short, long, default_value_t, value_enum)]
-    /// Which parsing to use. Defaults to simple lexer, which is faster. Select
-    /// `tree-sitter` in case you might be getting false positives/negatives.
-    parser: CompliancyChecker,
-
-    #[arg(short, long)]
-    /// Runs over glob matches considering root to be the path specified in the command.
-    /// Disconsiders the allow_hidden flag.
-    glob: Option<String>,
-
-    #[arg(short, long, default_value_t, value_enum)]
-    /// Determines the docstring style to consider for parsing.
-    docstyle: DocstringStyle,
-}
+    #[command(subcommand)]
+    command: Option<Command>,
 
-trait Compliancy {
-    #[allow(clippy::too_many_arguments)]
-    fn is_file_compliant(
-        &self,
-        path: &Path,
-        break_on_empty_line: bool,
-        forbid_no_docstring: bool,
-        forbid_no_args_in_docstring: bool,
-        forbid_untyped_docstrings: bool,
-        args_and_kwargs: bool,
-        docstyle: DocstringStyle,
-    ) -> Result<bool>;
+    #[command(flatten)]
+    check: CheckCommand,
 }
 
-#[derive(Default, Clone, Copy, ValueEnum)]
-enum CompliancyChecker {
-    TreeSitter,
+#[derive(Subcommand)]
+enum Command {
+    /// Checks the docstrings of the Python files in a file or folder, which is also what
+    /// is done without a command, as in `pystaleds PATH`.
+    Check(CheckCommand),
 
-    #[default]
-    Lexer,
-}
+    /// Fixes the docstrings of the Python files in a file or folder whose args differ from
+    /// the signature, then checks them for what is left.
+    Fix(FixCommand),
 
-impl Compliancy for CompliancyChecker {
-    fn is_file_compliant(
-        &self,
-        path: &Path,
-        break_on_empty_line: bool,
-        forbid_no_docstring: bool,
-        forbid_no_args_in_docstring: bool,
-        forbid_untyped_docstrings: bool,
-        args_and_kwargs: bool,
-        docstyle: DocstringStyle,
-    ) -> Result<bool> {
-        match self {
-            CompliancyChecker::Lexer => is_file_compliant_lexing(
-                path,
-                break_on_empty_line,
-                forbid_no_docstring,
-                forbid_no_args_in_docstring,
-                forbid_untyped_docstrings,
-                args_and_kwargs,
-                docstyle,
-            ),
-            CompliancyChecker::TreeSitter => is_file_compliant_tree_sitter(
-                path,
-                break_on_empty_line,
-                forbid_no_docstring,
-                forbid_no_args_in_docstring,
-                forbid_untyped_docstrings,
-                args_and_kwargs,
-                docstyle,
-            ),
-        }
-    }
-}
+    /// Lists every rule with its code, whether it is enforced with the other flags and its
+    /// default severity.
+    Rules(RulesCommand),
 
-/// Determines if a file or folder is hidden, i.e. if it starts with '.'.
-fn is_hidden(e: &DirEntry) -> bool {
-    e.file_name()
-        .to_str()
-        .map_or(false, |s| s.starts_with('.') && s != ".")
-}
+    /// Prints the configuration with which the files in a path would be checked, along
+    /// with where each value comes from.
+    ShowConfig(ShowConfigCommand),
 
-fn main() -> Result<()> {
-    let (non_blocking, _guard) = tracing_appender::non_blocking(std::io::stdout());
+    /// Converts the docstrings in a file or folder to another style.
+    Convert {
+        path: String,
 
-    tracing_subscriber::fmt()
-        .without_time()
-        .with_target(false)
-        .with_writer(non_blocking)
-        .init();
+        #[arg(long, value_enum)]
+        /// Docstring style to convert to.
+        to: ConversionTarget,
 
-    let args = Args::parse();
+        #[arg(long, default_value_t = false)]
+        /// Will print the changes instead of writing them to the files.
+        dry_run: bool,
 
-    if let CompliancyChecker::TreeSitter = args.parser {
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(0)
-            .stack_size(100_000_000) // TODO: Make the algorithm non-recursive and remove the stack expansion.
-            .build_global()
-            .expect("thread pool should be possible to initialize");
-    }
-
-    let path = Path::new(&args.path);
+        #[arg(long, default_value_t = false, alias = "ah")]
+        /// Will allow hidden files.
+        allow_hidden: bool,
+    },
+}
 
-    let files_with_errors = if let Some(s) = &args.glob {
-        set_current_dir(path)?;
+/// Prefix of the environment variables that set the options, e.g. `PYSTALEDS_DOCSTYLE`.
+const ENV_PREFIX: &str = "PYSTALEDS_";
 
-        let files_with_errors = AtomicU32::new(0);
+/// Command line of the program, in which every option can also be set through the
+/// environment variable named after it, e.g. `PYSTALEDS_FORBID_NO_DOCSTRING=1`. The
+/// command line takes precedence over the environment.
+fn command() -> clap::Command {
+    with_env_variables(Args::command())
+}
 
-        let paths = glob(s).expect("glob pattern should be valid");
+/// Sets the environment variables of the options of a command and of its subcommands.
+fn with_env_variables(command: clap::Command) -> clap::Command {
+    let subcommands: Vec<_> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
 
-        paths.into_iter().par_bridge().for_each(|entry| {
-            let Ok(entry) = entry else {
-                return;
-            };
+    let command = command.mut_args(|arg| {
+        if arg.is_positional() || matches!(arg.get_action(), ArgAction::Count) {
+            return arg;
+        }
 
-            let entry = entry.as_path();
+        let env = format!("{ENV_PREFIX}{}", arg.get_id().as_str().to_uppercase());
+        let arg = arg.env(env);
 
-            assess_success(entry, &args, &files_with_errors);
-        });
+        // Flags take no value in the command line, but do in the environment.
+        if matches!(arg.get_action(), ArgAction::SetTrue) {
+            arg.value_parser(BoolishValueParser::new())
+        } else {
+            arg
+        }
+    });
 
-        files_with_errors.into_inner()
-    } else {
-        let files_with_errors = if path.is_dir() {
-            let walk = walkdir::WalkDir::new(path);
+    subcommands.iter().fold(command, |command, name| {
+        command.mut_subcommand(name, with_env_variables)
+    })
+}
 
-            let files_with_errors = AtomicU32::new(0);
+/// Points at the environment variable an invalid value comes from, if any, since the
+/// error otherwise names the option as if it had been given in the command line.
+fn with_env_variable(mut error: clap::Error) -> clap::Error {
+    let context = |kind| match error.get(kind) {
+        Some(ContextValue::String(value)) => Some(value.clone()),
+        _ => None,
+    };
 
-            walk.into_iter()
-                .filter_entry(|e| {
-                    if args.allow_hidden {
-                        true
-                    } else {
-                        !is_hidden(e)
-                    }
-                })
-                .par_bridge()
-                .for_each(|entry| {
-                    let Ok(entry) = entry else {
-                        return;
-                    };
+    let (Some(option), Some(value)) = (
+        context(ContextKind::InvalidArg),
+        context(ContextKind::InvalidValue),
+    ) else {
+        return error;
+    };
 
-                    let entry = entry.path();
+    let command = command();
+    let variable = command
+        .get_arguments()
+        .chain(
+            command
+                .get_subcommands()
+                .flat_map(clap::Command::get_arguments),
+        )
+        .filter(|arg| {
+            arg.get_long()
+                .is_some_and(|long| option.split(' ').next() == Some(&format!("--{long}")))
+        })
+        .filter_map(|arg| arg.get_env())
+        .find(|variable| std::env::var_os(variable).is_some_and(|set| set == value.as_str()));
+
+    if let Some(variable) = variable {
+        error.insert(
+            ContextKind::Suggested,
+            ContextValue::StyledStrs(vec![format!(
+                "the value comes from the environment variable {}",
+                variable.to_string_lossy()
+            )
+            .into()]),
+        );
+    }
 
-                    assess_success(entry, &args, &files_with_errors)
-                });
+    error
+}
 
-            files_with_errors.into_inner()
-        } else {
-            // In this branch, path is a file.
-
-            if args.parser.is_file_compliant(
-                path,
-                args.break_on_empty_line,
-                args.forbid_no_docstring,
-                args.forbid_no_args_in_docstring,
-                args.forbid_untyped_docstrings,
-                args.include_args_and_kwargs,
-                args.docstyle,
-            )? {
-                0
-            } else {
-                1
+/// Runs the command, exiting with 0 if no errors were found, 1 if errors were found in
+/// the checked files and 2 if the run could not be completed.
+fn main() -> ExitCode {
+    match run() {
+        Ok(Outcome::Clean) => ExitCode::SUCCESS,
+        Ok(Outcome::Errors(message)) => {
+            if let Some(message) = message {
+                eprintln!("Error: {message}");
             }
-        };
 
-        files_with_errors
-    };
+            ExitCode::from(1)
+        }
+        Err(error) => {
+            eprintln!("Error: {error:#}");
 
-    if files_with_errors == 0 {
-        println!("✅ Success!");
-        Ok(())
-    } else if files_with_errors == 1 {
-        Err(anyhow!("found errors in {} file", files_with_errors))
-    } else {
-        Err(anyhow!("found errors in {} files", files_with_errors))
+            ExitCode::from(2)
+        }
     }
 }
 
-/// Determines if the file has errors or not, increasing error count if it does.
-fn assess_success(entry: &Path, args: &Args, total_errors: &AtomicU32) {
-    if entry.is_file() && entry.extension() == Some(&std::ffi::OsString::from("py")) {
-        let Ok(success) = args.parser.is_file_compliant(
-            entry,
-            args.break_on_empty_line,
-            args.forbid_no_docstring,
-            args.forbid_no_args_in_docstring,
-            args.forbid_untyped_docstrings,
-            args.include_args_and_kwargs,
-            args.docstyle,
-        ) else {
-            return;
-        };
-
-        if !success {
-            total_errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+fn run() -> Result<Outcome> {
+    let matches = command()
+        .try_get_matches()
+        .unwrap_or_else(|error| with_env_variable(error).exit());
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+
+    // Without a command, the files are checked with the options given to the program.
+    let (command, matches) = match args.command {
+        Some(command) => {
+            let (_, matches) = matches
+                .subcommand()
+                .expect("the command should have been parsed");
+
+            (command, matches)
+        }
+        None => (Command::Check(args.check), &matches),
+    };
+
+    if let Command::Check(check) = &command {
+        // Flags always have a value, so clap takes `--stdin` as given when requiring it.
+        if check.input.stdin_filename.is_some() && !check.input.stdin {
+            clap_error(
+                ErrorKind::MissingRequiredArgument,
+                "the argument '--stdin-filename <PATH>' requires '--stdin'",
+            );
+        }
+
+        if check.watch && check.report.format != OutputFormat::Text {
+            clap_error(
+                ErrorKind::ArgumentConflict,
+                "the argument '--watch' only supports '--format text'",
+            );
+        }
+    }
+
+    if let Command::Check(CheckCommand { report, .. }) | Command::Fix(FixCommand { report, .. }) =
+        &command
+    {
+        if report.stats_by.is_some()
+            && !matches!(report.format, OutputFormat::Text | OutputFormat::Json)
+        {
+            clap_error(
+                ErrorKind::ArgumentConflict,
+                "the argument '--stats-by' only supports '--format text' and '--format json'",
+            );
         }
     }
-}
 
-/// Determines if a file is compliant to the specified rules.
-fn is_file_compliant_tree_sitter(
-    path: &Path,
-    break_on_empty_line: bool,
-    forbid_no_docstring: bool,
-    forbid_no_args_in_docstring: bool,
-    forbid_untyped_docstrings: bool,
-    args_and_kwargs: bool,
-    docstyle: DocstringStyle,
-) -> Result<bool> {
-    let mut parser = tree_sitter::Parser::new();
-    parser.set_language(&tree_sitter_python::language())?;
-
-    let contents = std::fs::read_to_string(path)?;
-
-    let success = respects_rules(
-        &mut parser,
-        &contents,
-        None,
-        Some(path),
-        break_on_empty_line,
-        !forbid_no_docstring,
-        !forbid_no_args_in_docstring,
-        !forbid_untyped_docstrings,
-        !args_and_kwargs,
-        docstyle,
-    );
-
-    Ok(success)
+    let (color, max_level) = match &command {
+        Command::Check(CheckCommand { report, .. }) | Command::Fix(FixCommand { report, .. }) => {
+            let max_level = match (report.quiet || report.count, report.verbose) {
+                (true, _) => LevelFilter::OFF,
+                (false, 0) => LevelFilter::INFO,
+                (false, 1) => LevelFilter::DEBUG,
+                (false, _) => LevelFilter::TRACE,
+            };
+
+            (report.color.enabled(), max_level)
+        }
+        _ => (ColorChoice::Auto.enabled(), LevelFilter::INFO),
+    };
+
+    // Logs go to stderr, so that they do not mix with the errors in stdout.
+    let (non_blocking, _guard) = tracing_appender::non_blocking(std::io::stderr());
+
+    tracing_subscriber::fmt()
+        .without_time()
+        .with_target(false)
+        .with_ansi(color)
+        .with_max_level(max_level)
+        .with_writer(non_blocking)
+        .init();
+
+    match command {
+        Command::Check(command) => check(command, matches),
+        Command::Fix(command) => check(command.into(), matches),
+        Command::Rules(command) => list_rules(&command, matches),
+        Command::ShowConfig(command) => show_config(&command, matches),
+        Command::Convert {
+            path,
+            to,
+            dry_run,
+            allow_hidden,
+        } => convert(Path::new(&path), to, dry_run, allow_hidden),
+    }
 }
 
-/// Determines if a file is compliant to the specified rules.
-fn is_file_compliant_lexing(
-    path: &Path,
-    break_on_empty_line: bool,
-    forbid_no_docstring: bool,
-    forbid_no_args_in_docstring: bool,
-    forbid_untyped_docstrings: bool,
-    args_and_kwargs: bool,
-    docstyle: DocstringStyle,
-) -> Result<bool> {
-    let mut parser = tree_sitter::Parser::new();
-    parser.set_language(&tree_sitter_python::language())?;
-
-    let contents = std::fs::read_to_string(path)?;
-
-    let success = respects_rules_through_lexing(
-        &contents,
-        Some(path),
-        break_on_empty_line,
-        !forbid_no_docstring,
-        !forbid_no_args_in_docstring,
-        !forbid_untyped_docstrings,
-        !args_and_kwargs,
-        docstyle,
-    );
-
-    Ok(success)
+/// Exits with a usage error of the command line that clap cannot detect by itself.
+fn clap_error(kind: ErrorKind, message: &str) -> ! {
+    command().error(kind, message).exit()
 }
@@ -0,0 +1,1265 @@
+use std::ops::Range;
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+use crate::diagnostics::{Diagnostic, Rule, Severity};
+use crate::html::to_html;
+use crate::rules_checking::{CheckOptions, SourceReport};
+use crate::stats::{directory_table, pluralize, DirectoryStats};
+
+/// Format in which the diagnostics are reported.
+#[derive(Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable lines, grouped by file.
+    #[default]
+    Text,
+    /// Reviewdog Diagnostic Format.
+    Rdjson,
+    /// A JSON array with an object per error.
+    Json,
+    /// Static Analysis Results Interchange Format, as used by GitHub's code scanning.
+    Sarif,
+    /// Workflow commands that annotate the errors in GitHub Actions.
+    Github,
+    /// JUnit XML, with a test suite per file and a test case per function.
+    Junit,
+    /// Checkstyle XML, with the errors grouped by file.
+    Checkstyle,
+    /// Code Climate JSON, as shown in GitLab's merge request widget.
+    Gitlab,
+    /// A Markdown summary with a table per file, as posted in pull request comments.
+    Markdown,
+    /// A self-contained HTML report, meant to be written with `--output`.
+    Html,
+    /// Lines of `path:line:col: CODE message`, as read by Vim's quickfix list.
+    Quickfix,
+}
+
+impl OutputFormat {
+    /// Renders the results collected in a run, or `None` if there is nothing to print,
+    /// such as for the text format, whose diagnostics are logged as they are found.
+    pub fn render(self, reports: &[SourceReport]) -> Option<String> {
+        let diagnostics: Vec<Diagnostic> = reports
+            .iter()
+            .flat_map(|report| report.diagnostics.iter().cloned())
+            .collect();
+        let diagnostics = diagnostics.as_slice();
+
+        match self {
+            OutputFormat::Text => None,
+            OutputFormat::Rdjson => Some(to_rdjson(diagnostics).to_string()),
+            OutputFormat::Json => Some(to_json(diagnostics).to_string()),
+            OutputFormat::Sarif => Some(to_sarif(diagnostics).to_string()),
+            OutputFormat::Github => {
+                Some(to_github_annotations(diagnostics)).filter(|output| !output.is_empty())
+            }
+            OutputFormat::Junit => Some(to_junit(reports)),
+            OutputFormat::Checkstyle => Some(to_checkstyle(diagnostics)),
+            OutputFormat::Gitlab => Some(to_gitlab(diagnostics).to_string()),
+            OutputFormat::Markdown => Some(to_markdown(reports)),
+            OutputFormat::Html => Some(to_html(reports)),
+            OutputFormat::Quickfix => {
+                Some(to_quickfix(diagnostics)).filter(|output| !output.is_empty())
+            }
+        }
+    }
+
+    /// Lists every rule with its code, whether it is enforced with the options and its
+    /// severity, as JSON for the json format and as text for every other one.
+    pub fn render_rules(self, options: &CheckOptions) -> String {
+        match self {
+            OutputFormat::Json => rules_to_json(options).to_string(),
+            _ => rules_to_text(options),
+        }
+    }
+
+    /// Renders the stats aggregated by directory, as a JSON array or as a table.
+    pub fn render_directories(self, directories: &[DirectoryStats]) -> String {
+        match self {
+            OutputFormat::Json => directories_to_json(directories).to_string(),
+            _ => directory_table(directories),
+        }
+    }
+}
+
+/// Lists every rule as a line with its code, identifier, whether it is enforced with the
+/// options and its severity, aligned in columns.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::rules_to_text;
+/// use pystaleds::rules_checking::CheckOptions;
+///
+/// let text = rules_to_text(&CheckOptions::default());
+///
+/// assert!(text.starts_with("PSD101  missing-docstring"));
+/// ```
+pub fn rules_to_text(options: &CheckOptions) -> String {
+    let width = Rule::ALL
+        .iter()
+        .map(|rule| rule.id().len())
+        .max()
+        .unwrap_or_default();
+
+    Rule::ALL
+        .iter()
+        .map(|rule| {
+            let enabled = if options.enables(*rule) {
+                "enabled"
+            } else {
+                "disabled"
+            };
+
+            format!(
+                "{}  {:width$}  {enabled:8}  {}",
+                rule.code(),
+                rule.id(),
+                options.rules.severity(*rule).id()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Lists every rule as a JSON array with an object per rule.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::rules_to_json;
+/// use pystaleds::rules_checking::CheckOptions;
+///
+/// let rules = rules_to_json(&CheckOptions::default());
+///
+/// assert_eq!(rules[0]["code"], "PSD101");
+/// assert_eq!(rules[0]["enabled"], false);
+/// ```
+pub fn rules_to_json(options: &CheckOptions) -> Value {
+    Rule::ALL
+        .iter()
+        .map(|rule| {
+            json!({
+                "code": rule.code(),
+                "rule": rule.id(),
+                "enabled": options.enables(*rule),
+                "severity": options.rules.severity(*rule).id(),
+                "url": rule.url(),
+            })
+        })
+        .collect()
+}
+
+/// Serializes the stats aggregated by directory into a JSON array with an object per
+/// directory, whose violations are broken down by rule.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::diagnostics::Rule;
+/// use pystaleds::output::directories_to_json;
+/// use pystaleds::stats::DirectoryStats;
+///
+/// let directories = directories_to_json(&[DirectoryStats {
+///     directory: "src".to_string(),
+///     files: 2,
+///     functions: 5,
+///     rules: vec![(Rule::MissingArg, 3)],
+/// }]);
+///
+/// assert_eq!(directories[0]["violations"], 3);
+/// assert_eq!(directories[0]["rules"][0]["code"], "PSD201");
+/// ```
+pub fn directories_to_json(directories: &[DirectoryStats]) -> Value {
+    directories
+        .iter()
+        .map(|directory| {
+            let rules: Vec<Value> = directory
+                .rules
+                .iter()
+                .map(|(rule, count)| {
+                    json!({ "code": rule.code(), "rule": rule.id(), "count": count })
+                })
+                .collect();
+
+            json!({
+                "directory": directory.directory,
+                "files": directory.files,
+                "functions": directory.functions,
+                "violations": directory.violations(),
+                "rules": rules,
+            })
+        })
+        .collect()
+}
+
+/// Serializes the diagnostics into the Reviewdog Diagnostic Format (rdjson), with 1-based
+/// lines and columns, and the severity on each diagnostic rather than on the whole result,
+/// since warnings and errors may be mixed. The diagnostics that can be fixed carry the
+/// edits of their fix as `suggestions`, each replacing its `range` with its `text`.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::to_rdjson;
+///
+/// let rdjson = to_rdjson(&[]);
+///
+/// assert_eq!(rdjson["source"]["name"], "pystaleds");
+/// assert!(rdjson["diagnostics"].as_array().unwrap().is_empty());
+/// ```
+pub fn to_rdjson(diagnostics: &[Diagnostic]) -> Value {
+    let diagnostics: Vec<Value> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut location = json!({
+                "path": diagnostic
+                    .path
+                    .as_ref()
+                    .map_or(String::new(), |path| path.to_string_lossy().to_string()),
+            });
+
+            if let Some(row) = diagnostic.row {
+                location["range"] = json!({ "start": { "line": row + 1 } });
+
+                if let Some(column) = diagnostic.column {
+                    location["range"]["start"]["column"] = json!(column + 1);
+                }
+            }
+
+            let mut rdjson = json!({
+                "message": diagnostic.text(),
+                "location": location,
+                "severity": match diagnostic.severity {
+                    Severity::Error => "ERROR",
+                    Severity::Warning => "WARNING",
+                },
+                "code": {
+                    "value": diagnostic.rule.code(),
+                    "url": diagnostic.rule.url(),
+                },
+            });
+
+            if let Some(fix) = &diagnostic.fix {
+                let position = |(row, column): (usize, usize)| {
+                    json!({ "line": row + 1, "column": column + 1 })
+                };
+
+                rdjson["suggestions"] = fix
+                    .edits
+                    .iter()
+                    .map(|edit| {
+                        json!({
+                            "range": {
+                                "start": position(edit.start),
+                                "end": position(edit.end),
+                            },
+                            "text": edit.replacement,
+                        })
+                    })
+                    .collect();
+            }
+
+            rdjson
+        })
+        .collect();
+
+    json!({
+        "source": {
+            "name": "pystaleds",
+            "url": "https://github.com/AloizioMacedo/pystaleds",
+        },
+        "diagnostics": diagnostics,
+    })
+}
+
+/// Serializes the diagnostics into a JSON array with an object per diagnostic, including
+/// the entries from the code (`expected`) and from the docstring (`actual`) for the ones
+/// that compare them, and the byte ranges (`spans`) of the signature, params and
+/// docstring of the function, as `{"start": 4, "end": 20}` or `null` if unknown.
+///
+/// The diagnostics that can be fixed also carry the `fix`, as a `title` describing it and
+/// the `edits` to make, each replacing the bytes from `start_byte` to `end_byte` of the
+/// checked source code with its `replacement`.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::to_json;
+///
+/// assert_eq!(to_json(&[]), serde_json::json!([]));
+/// ```
+pub fn to_json(diagnostics: &[Diagnostic]) -> Value {
+    let entries = |entries: &[(String, Option<String>)]| -> Value {
+        entries
+            .iter()
+            .map(|(name, typ)| json!({ "name": name, "type": typ }))
+            .collect()
+    };
+    let span = |range: &Option<Range<usize>>| -> Value {
+        range.as_ref().map_or(
+            Value::Null,
+            |range| json!({ "start": range.start, "end": range.end }),
+        )
+    };
+
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut object = json!({
+                "file": diagnostic.path.as_ref().map(|path| path.to_string_lossy()),
+                "function": diagnostic.function,
+                "line": diagnostic.row.map(|row| row + 1),
+                "column": diagnostic.column.map(|column| column + 1),
+                "rule": diagnostic.rule.id(),
+                "code": diagnostic.rule.code(),
+                "severity": diagnostic.severity.id(),
+                "message": diagnostic.message,
+                "spans": {
+                    "signature": span(&diagnostic.spans.signature),
+                    "parameters": span(&diagnostic.spans.parameters),
+                    "docstring": span(&diagnostic.spans.docstring),
+                },
+            });
+
+            if let Some(comparison) = &diagnostic.entries {
+                object["expected"] = entries(&comparison.expected);
+                object["actual"] = entries(&comparison.actual);
+            }
+
+            if let Some(fix) = &diagnostic.fix {
+                let edits: Vec<Value> = fix
+                    .edits
+                    .iter()
+                    .map(|edit| {
+                        json!({
+                            "start_byte": edit.range.start,
+                            "end_byte": edit.range.end,
+                            "replacement": edit.replacement,
+                        })
+                    })
+                    .collect();
+
+                object["fix"] = json!({ "title": fix.title, "edits": edits });
+            }
+
+            object
+        })
+        .collect()
+}
+
+/// Serializes the diagnostics into a SARIF log with a single run, whose tool describes
+/// every rule.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::to_sarif;
+///
+/// let sarif = to_sarif(&[]);
+///
+/// assert_eq!(sarif["version"], "2.1.0");
+/// assert_eq!(sarif["runs"][0]["tool"]["driver"]["name"], "pystaleds");
+/// ```
+pub fn to_sarif(diagnostics: &[Diagnostic]) -> Value {
+    let rules: Vec<Value> = Rule::ALL
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.code(),
+                "name": rule.id(),
+                "helpUri": rule.url(),
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let uri = diagnostic.path.as_ref().map_or(String::new(), |path| {
+                path.to_string_lossy().replace('\\', "/")
+            });
+
+            let mut physical_location = json!({ "artifactLocation": { "uri": uri } });
+
+            if let Some(row) = diagnostic.row {
+                physical_location["region"] = json!({ "startLine": row + 1 });
+
+                if let Some(column) = diagnostic.column {
+                    physical_location["region"]["startColumn"] = json!(column + 1);
+                }
+            }
+
+            json!({
+                "ruleId": diagnostic.rule.code(),
+                "ruleIndex": Rule::ALL.iter().position(|&rule| rule == diagnostic.rule),
+                "level": diagnostic.severity.id(),
+                "message": {
+                    "text": diagnostic.text(),
+                },
+                "locations": [{ "physicalLocation": physical_location }],
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "pystaleds",
+                    "informationUri": "https://github.com/AloizioMacedo/pystaleds",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    })
+}
+
+/// Renders the diagnostics as GitHub Actions workflow commands, one `::error` (or
+/// `::warning`) line per diagnostic, with 1-based lines.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::to_github_annotations;
+///
+/// assert_eq!(to_github_annotations(&[]), "");
+/// ```
+pub fn to_github_annotations(diagnostics: &[Diagnostic]) -> String {
+    // Data and properties of workflow commands have their own escaping.
+    let escape_data = |text: &str| {
+        text.replace('%', "%25")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A")
+    };
+    let escape_property = |text: &str| escape_data(text).replace(':', "%3A").replace(',', "%2C");
+
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut properties = Vec::new();
+
+            if let Some(path) = &diagnostic.path {
+                properties.push(format!("file={}", escape_property(&path.to_string_lossy())));
+            }
+
+            if let Some(row) = diagnostic.row {
+                properties.push(format!("line={}", row + 1));
+            }
+
+            properties.push("title=pystaleds".to_string());
+
+            format!(
+                "::{} {}::{}",
+                diagnostic.severity.id(),
+                properties.join(","),
+                escape_data(&diagnostic.text())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders the diagnostics as lines of `path:line:col: CODE message`, with 1-based lines
+/// and columns, as matched by Vim's `errorformat` of `%f:%l:%c:\ %m`. Diagnostics without
+/// a position of their own point at their function, or at the start of the file.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::to_quickfix;
+///
+/// assert_eq!(to_quickfix(&[]), "");
+/// ```
+pub fn to_quickfix(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let path = diagnostic
+                .path
+                .as_ref()
+                .map_or("-".into(), |path| path.to_string_lossy());
+            let row = diagnostic.row.or(diagnostic.function_row).unwrap_or(0);
+            let column = diagnostic.column.unwrap_or(0);
+
+            format!(
+                "{path}:{}:{}: {} {}",
+                row + 1,
+                column + 1,
+                diagnostic.rule.code(),
+                diagnostic.message.replace('\n', " ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Serializes the results into JUnit XML, with a `<testsuite>` per file and a `<testcase>`
+/// per checked function, which fails with the errors of the function. Errors that do not
+/// refer to a checked function (such as a missing module docstring) get a `<testcase>` of
+/// their own. Warnings are left out, since they do not fail the check.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::to_junit;
+///
+/// assert!(to_junit(&[]).contains(r#"<testsuites name="pystaleds" tests="0" failures="0">"#));
+/// ```
+pub fn to_junit(reports: &[SourceReport]) -> String {
+    let mut suites = Vec::new();
+    let (mut total_tests, mut total_failures) = (0, 0);
+
+    for report in reports {
+        let path = report
+            .path
+            .as_ref()
+            .map_or(String::new(), |path| path.to_string_lossy().to_string());
+
+        let mut cases: Vec<(&str, Option<usize>, Vec<&Diagnostic>)> = report
+            .functions
+            .iter()
+            .map(|function| {
+                let diagnostics = report
+                    .diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.is_error() && function.matches(diagnostic))
+                    .collect();
+
+                (function.name.as_str(), function.row, diagnostics)
+            })
+            .collect();
+
+        cases.extend(
+            report
+                .diagnostics
+                .iter()
+                .filter(|diagnostic| {
+                    diagnostic.is_error() && !report.functions.iter().any(|f| f.matches(diagnostic))
+                })
+                .map(|diagnostic| {
+                    (
+                        diagnostic.function.as_str(),
+                        diagnostic.row,
+                        vec![diagnostic],
+                    )
+                }),
+        );
+
+        let failures = cases.iter().filter(|(_, _, d)| !d.is_empty()).count();
+
+        total_tests += cases.len();
+        total_failures += failures;
+
+        let mut suite = format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+            escape_xml(&path),
+            cases.len(),
+            failures,
+        );
+
+        for (name, row, diagnostics) in cases {
+            let line = row.map_or(String::new(), |row| format!(" line=\"{}\"", row + 1));
+
+            suite.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\"{line}",
+                escape_xml(name),
+                escape_xml(&path),
+                escape_xml(&path),
+            ));
+
+            let Some(first) = diagnostics.first() else {
+                suite.push_str("/>\n");
+                continue;
+            };
+
+            let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+            let text: Vec<String> = diagnostics
+                .iter()
+                .map(|diagnostic| {
+                    let line = diagnostic
+                        .row
+                        .map_or(String::new(), |row| format!("line {}: ", row + 1));
+
+                    format!("{line}{diagnostic}")
+                })
+                .collect();
+
+            suite.push_str(&format!(
+                ">\n      <failure type=\"{}\" message=\"{}\">{}</failure>\n    </testcase>\n",
+                first.rule.code(),
+                escape_xml(&messages.join("; ")),
+                escape_xml(&text.join("\n")),
+            ));
+        }
+
+        suite.push_str("  </testsuite>\n");
+        suites.push(suite);
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites name=\"pystaleds\" tests=\"{total_tests}\" failures=\"{total_failures}\">\n\
+         {}</testsuites>",
+        suites.concat(),
+    )
+}
+
+/// Serializes the diagnostics into Checkstyle XML, with a `<file>` per path (in the order
+/// they are first found) holding an `<error>` per diagnostic, with 1-based lines and the
+/// severity of the diagnostic.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::to_checkstyle;
+///
+/// assert_eq!(
+///     to_checkstyle(&[]),
+///     "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n</checkstyle>"
+/// );
+/// ```
+pub fn to_checkstyle(diagnostics: &[Diagnostic]) -> String {
+    let mut files: Vec<(String, Vec<&Diagnostic>)> = Vec::new();
+
+    for diagnostic in diagnostics {
+        let path = diagnostic
+            .path
+            .as_ref()
+            .map_or(String::new(), |path| path.to_string_lossy().to_string());
+
+        match files.iter_mut().find(|(name, _)| *name == path) {
+            Some((_, file_diagnostics)) => file_diagnostics.push(diagnostic),
+            None => files.push((path, vec![diagnostic])),
+        }
+    }
+
+    let files: String = files
+        .into_iter()
+        .map(|(path, diagnostics)| {
+            let errors: String = diagnostics
+                .into_iter()
+                .map(|diagnostic| {
+                    let line = diagnostic
+                        .row
+                        .map_or(String::new(), |row| format!(" line=\"{}\"", row + 1));
+
+                    format!(
+                        "    <error{line} severity=\"{}\" message=\"{}\" source=\"pystaleds.{}\"/>\n",
+                        diagnostic.severity.id(),
+                        escape_xml(&diagnostic.text()),
+                        diagnostic.rule.code(),
+                    )
+                })
+                .collect();
+
+            format!("  <file name=\"{}\">\n{errors}  </file>\n", escape_xml(&path))
+        })
+        .collect();
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n{files}</checkstyle>"
+    )
+}
+
+/// Serializes the diagnostics into the Code Climate JSON format understood by GitLab,
+/// with 1-based lines (the first one if the diagnostic has none) and a [`fingerprint`]
+/// so that unchanged findings are recognized across pipelines.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::to_gitlab;
+///
+/// assert!(to_gitlab(&[]).as_array().unwrap().is_empty());
+/// ```
+pub fn to_gitlab(diagnostics: &[Diagnostic]) -> Value {
+    diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let path = diagnostic
+                .path
+                .as_ref()
+                .map_or(String::new(), |path| path.to_string_lossy().to_string());
+
+            json!({
+                "description": diagnostic.text(),
+                "check_name": diagnostic.rule.code(),
+                "fingerprint": fingerprint(&path, &diagnostic.function, diagnostic.rule),
+                "severity": match diagnostic.severity {
+                    Severity::Error => "major",
+                    Severity::Warning => "minor",
+                },
+                "location": {
+                    "path": path,
+                    "lines": {"begin": diagnostic.row.map_or(1, |row| row + 1)},
+                },
+            })
+        })
+        .collect()
+}
+
+/// Identifies a finding by its path, function and rule, but not its line, so that it
+/// stays the same when unrelated code moves it around.
+///
+/// It is a hex-encoded 64-bit FNV-1a hash, which (unlike the hashers of the standard
+/// library) is guaranteed to be the same across runs, platforms and Rust versions.
+pub fn fingerprint(path: &str, function: &str, rule: Rule) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    // The separator keeps e.g. ("ab", "c") and ("a", "bc") apart.
+    let hash = [path, function, rule.id()]
+        .join("\0")
+        .bytes()
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        });
+
+    format!("{hash:016x}")
+}
+
+/// Maximum number of entries listed in a cell of the Markdown tables before the rest are
+/// just counted.
+const MAX_MARKDOWN_ENTRIES: usize = 5;
+
+/// Renders the results as Markdown, with a header with the totals and a table per file
+/// with errors, or a single line if there are none.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::output::to_markdown;
+///
+/// assert_eq!(to_markdown(&[]), "✅ all docstrings up to date");
+/// ```
+pub fn to_markdown(reports: &[SourceReport]) -> String {
+    let violations: usize = reports.iter().map(|r| r.diagnostics.len()).sum();
+
+    if violations == 0 {
+        return "✅ all docstrings up to date".to_string();
+    }
+
+    let functions: usize = reports.iter().map(|r| r.functions.len()).sum();
+
+    let mut markdown = format!(
+        "## pystaleds\n\n\
+         Scanned {}, checked {} and found {}.\n",
+        pluralize(reports.len(), "file"),
+        pluralize(functions, "function"),
+        pluralize(violations, "violation"),
+    );
+
+    for report in reports.iter().filter(|r| !r.diagnostics.is_empty()) {
+        let path = report
+            .path
+            .as_ref()
+            .map_or(String::new(), |path| path.to_string_lossy().to_string());
+
+        markdown.push_str(&format!(
+            "\n### `{path}`\n\n\
+             | Function | Line | Problem | Expected | Documented |\n\
+             | --- | --- | --- | --- | --- |\n"
+        ));
+
+        for diagnostic in &report.diagnostics {
+            let (expected, documented) =
+                diagnostic
+                    .entries
+                    .as_ref()
+                    .map_or_else(Default::default, |entries| {
+                        (
+                            markdown_entries(&entries.expected),
+                            markdown_entries(&entries.actual),
+                        )
+                    });
+
+            markdown.push_str(&format!(
+                "| `{}` | {} | {} | {expected} | {documented} |\n",
+                escape_markdown_cell(&diagnostic.function),
+                diagnostic
+                    .row
+                    .map_or(String::new(), |row| (row + 1).to_string()),
+                escape_markdown_cell(&format!(
+                    "{} {}",
+                    diagnostic.rule.code(),
+                    diagnostic.message
+                )),
+            ));
+        }
+    }
+
+    markdown
+}
+
+/// Lists the entries for a cell of a Markdown table, truncated to
+/// [`MAX_MARKDOWN_ENTRIES`].
+fn markdown_entries(entries: &[(String, Option<String>)]) -> String {
+    let mut listed: Vec<String> = entries
+        .iter()
+        .take(MAX_MARKDOWN_ENTRIES)
+        .map(|(name, type_)| {
+            let entry = match type_ {
+                Some(type_) => format!("{name}: {type_}"),
+                None => name.to_string(),
+            };
+
+            format!("`{}`", escape_markdown_cell(&entry))
+        })
+        .collect();
+
+    if entries.len() > MAX_MARKDOWN_ENTRIES {
+        listed.push(format!(
+            "… (+{} more)",
+            entries.len() - MAX_MARKDOWN_ENTRIES
+        ));
+    }
+
+    listed.join(", ")
+}
+
+/// Escapes the pipes and line breaks that would break a row of a Markdown table.
+fn escape_markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Escapes the characters with special meaning in XML text and attributes.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::diagnostics::{EntriesComparison, Spans};
+    use crate::fixing::{Edit, Fix};
+    use crate::rules_checking::CheckedFunction;
+
+    use super::*;
+
+    fn diagnostics() -> [Diagnostic; 2] {
+        [
+            Diagnostic {
+                rule: Rule::MissingArg,
+                path: Some(PathBuf::from("src/module.py")),
+                function: "3".to_string(),
+                row: Some(3),
+                column: Some(4),
+                function_row: Some(3),
+                message: "Docstring args not matching".to_string(),
+                severity: Severity::Error,
+                spans: Spans {
+                    signature: Some(20..31),
+                    parameters: Some(25..30),
+                    docstring: Some(36..50),
+                },
+                entries: Some(EntriesComparison::new(
+                    &[("x", Some("int"))],
+                    &[("y", None)],
+                )),
+                fix: Some(Fix {
+                    row: 3,
+                    function: "f".to_string(),
+                    title: "update Args section to match signature".to_string(),
+                    rules: vec![Rule::MissingArg],
+                    edits: vec![Edit {
+                        range: 40..45,
+                        start: (5, 8),
+                        end: (5, 13),
+                        replacement: "x (int): _description_".to_string(),
+                    }],
+                }),
+            },
+            Diagnostic {
+                rule: Rule::MissingDocstring,
+                path: Some(PathBuf::from("src/module.py")),
+                function: "f".to_string(),
+                row: None,
+                column: None,
+                function_row: None,
+                message: "Docstring missing".to_string(),
+                severity: Severity::Error,
+                spans: Spans::default(),
+                entries: None,
+                fix: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn rdjson() {
+        assert_eq!(
+            to_rdjson(&diagnostics()),
+            json!({
+                "source": {
+                    "name": "pystaleds",
+                    "url": "https://github.com/AloizioMacedo/pystaleds",
+                },
+                "diagnostics": [
+                    {
+                        "message": "`3`: PSD201 Docstring args not matching",
+                        "location": {
+                            "path": "src/module.py",
+                            "range": { "start": { "line": 4, "column": 5 } },
+                        },
+                        "severity": "ERROR",
+                        "code": {
+                            "value": "PSD201",
+                            "url": "https://github.com/AloizioMacedo/pystaleds#missing-arg",
+                        },
+                        "suggestions": [{
+                            "range": {
+                                "start": { "line": 6, "column": 9 },
+                                "end": { "line": 6, "column": 14 },
+                            },
+                            "text": "x (int): _description_",
+                        }],
+                    },
+                    {
+                        "message": "`f`: PSD101 Docstring missing",
+                        "location": { "path": "src/module.py" },
+                        "severity": "ERROR",
+                        "code": {
+                            "value": "PSD101",
+                            "url": "https://github.com/AloizioMacedo/pystaleds#missing-docstring",
+                        },
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            to_json(&diagnostics()),
+            json!([
+                {
+                    "file": "src/module.py",
+                    "function": "3",
+                    "line": 4,
+                    "column": 5,
+                    "code": "PSD201",
+                    "severity": "error",
+                    "rule": "missing-arg",
+                    "message": "Docstring args not matching",
+                    "spans": {
+                        "signature": { "start": 20, "end": 31 },
+                        "parameters": { "start": 25, "end": 30 },
+                        "docstring": { "start": 36, "end": 50 },
+                    },
+                    "expected": [{ "name": "x", "type": "int" }],
+                    "actual": [{ "name": "y", "type": null }],
+                    "fix": {
+                        "title": "update Args section to match signature",
+                        "edits": [{
+                            "start_byte": 40,
+                            "end_byte": 45,
+                            "replacement": "x (int): _description_",
+                        }],
+                    },
+                },
+                {
+                    "file": "src/module.py",
+                    "function": "f",
+                    "line": null,
+                    "column": null,
+                    "code": "PSD101",
+                    "severity": "error",
+                    "rule": "missing-docstring",
+                    "message": "Docstring missing",
+                    "spans": { "signature": null, "parameters": null, "docstring": null },
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn sarif() {
+        let sarif = to_sarif(&diagnostics());
+
+        for key in ["$schema", "version", "runs"] {
+            assert!(sarif.get(key).is_some(), "missing `{key}`");
+        }
+
+        let runs = sarif["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 1);
+
+        let driver = &runs[0]["tool"]["driver"];
+        assert_eq!(driver["name"], "pystaleds");
+
+        let rules = driver["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), Rule::ALL.len());
+        assert!(rules
+            .iter()
+            .all(|rule| rule["id"].is_string() && rule["helpUri"].is_string()));
+
+        let results = runs[0]["results"].as_array().unwrap();
+
+        assert_eq!(
+            results[0],
+            json!({
+                "ruleId": "PSD201",
+                "ruleIndex": 6,
+                "level": "error",
+                "message": { "text": "`3`: PSD201 Docstring args not matching" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "src/module.py" },
+                        "region": { "startLine": 4, "startColumn": 5 },
+                    },
+                }],
+            })
+        );
+
+        assert_eq!(rules[6]["id"], results[0]["ruleId"]);
+        assert!(results[1]["locations"][0]["physicalLocation"]
+            .get("region")
+            .is_none());
+    }
+
+    #[test]
+    fn rules() {
+        let options = CheckOptions {
+            succeed_if_no_docstring: false,
+            ..Default::default()
+        };
+
+        let text = rules_to_text(&options);
+        let lines: Vec<_> = text.lines().collect();
+
+        assert_eq!(lines.len(), Rule::ALL.len());
+        assert_eq!(
+            lines[0],
+            "PSD101  missing-docstring         enabled   error"
+        );
+        assert_eq!(
+            lines[1],
+            "PSD102  missing-args              disabled  error"
+        );
+
+        let json = rules_to_json(&options);
+
+        assert_eq!(json.as_array().unwrap().len(), Rule::ALL.len());
+        assert_eq!(
+            json[6],
+            json!({
+                "code": "PSD201",
+                "rule": "missing-arg",
+                "enabled": true,
+                "severity": "error",
+                "url": "https://github.com/AloizioMacedo/pystaleds#missing-arg",
+            })
+        );
+    }
+
+    #[test]
+    fn github_annotations() {
+        let mut diagnostics = diagnostics().to_vec();
+        diagnostics[1].message = "100% wrong\nreally".to_string();
+
+        assert_eq!(
+            to_github_annotations(&diagnostics),
+            "::error file=src/module.py,line=4,title=pystaleds::`3`: PSD201 Docstring args not matching\n\
+             ::error file=src/module.py,title=pystaleds::`f`: PSD101 100%25 wrong%0Areally"
+        );
+    }
+
+    #[test]
+    fn quickfix() {
+        let mut diagnostics = diagnostics().to_vec();
+        diagnostics[1].message = "Docstring\nmissing".to_string();
+        diagnostics.push(Diagnostic {
+            path: None,
+            function_row: Some(7),
+            ..diagnostics[1].clone()
+        });
+
+        assert_eq!(
+            to_quickfix(&diagnostics),
+            "src/module.py:4:5: PSD201 Docstring args not matching\n\
+             src/module.py:1:1: PSD101 Docstring missing\n\
+             -:8:1: PSD101 Docstring missing"
+        );
+    }
+
+    #[test]
+    fn checkstyle() {
+        let mut diagnostics = diagnostics().to_vec();
+        diagnostics.push(Diagnostic {
+            path: Some(PathBuf::from("other.py")),
+            message: "<b> & \"c\"".to_string(),
+            severity: Severity::Error,
+            spans: Spans::default(),
+            ..diagnostics[1].clone()
+        });
+
+        assert_eq!(
+            to_checkstyle(&diagnostics),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<checkstyle version="4.3">
+  <file name="src/module.py">
+    <error line="4" severity="error" message="`3`: PSD201 Docstring args not matching" source="pystaleds.PSD201"/>
+    <error severity="error" message="`f`: PSD101 Docstring missing" source="pystaleds.PSD101"/>
+  </file>
+  <file name="other.py">
+    <error severity="error" message="`f`: PSD101 &lt;b&gt; &amp; &quot;c&quot;" source="pystaleds.PSD101"/>
+  </file>
+</checkstyle>"#
+        );
+    }
+
+    #[test]
+    fn gitlab() {
+        let gitlab = to_gitlab(&diagnostics());
+        let findings = gitlab.as_array().unwrap();
+
+        assert_eq!(
+            findings[0],
+            json!({
+                "description": "`3`: PSD201 Docstring args not matching",
+                "check_name": "PSD201",
+                "fingerprint": fingerprint("src/module.py", "3", Rule::MissingArg),
+                "severity": "major",
+                "location": {"path": "src/module.py", "lines": {"begin": 4}},
+            })
+        );
+        assert_eq!(findings[1]["location"]["lines"]["begin"], 1);
+    }
+
+    #[test]
+    fn fingerprint_stability() {
+        assert_eq!(
+            fingerprint("src/module.py", "f", Rule::MissingArg),
+            fingerprint("src/module.py", "f", Rule::MissingArg)
+        );
+        // Pinned, since a fingerprint changing between versions would make every finding
+        // look new.
+        assert_eq!(fingerprint("", "", Rule::MissingArg), "fbf6d6b50876ef66");
+
+        assert_ne!(
+            fingerprint("src/module.py", "f", Rule::MissingArg),
+            fingerprint("src/module.py", "g", Rule::MissingArg)
+        );
+        assert_ne!(
+            fingerprint("src/module.py", "f", Rule::MissingArg),
+            fingerprint("src/module.py", "f", Rule::MissingDocstring)
+        );
+        assert_ne!(
+            fingerprint("ab", "c", Rule::MissingArg),
+            fingerprint("a", "bc", Rule::MissingArg)
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        let mut diagnostics = diagnostics().to_vec();
+        diagnostics[1].message = "Either | or".to_string();
+        diagnostics.push(Diagnostic {
+            entries: Some(EntriesComparison::new(
+                &[
+                    ("a", None),
+                    ("b", None),
+                    ("c", None),
+                    ("d", None),
+                    ("e", None),
+                    ("f", None),
+                    ("g", None),
+                ],
+                &[],
+            )),
+            ..diagnostics[0].clone()
+        });
+
+        let reports = [
+            SourceReport {
+                path: Some(PathBuf::from("src/module.py")),
+                diagnostics,
+                functions: Vec::new(),
+                suppressed: 0,
+                definitions: 0,
+            },
+            SourceReport {
+                path: Some(PathBuf::from("src/clean.py")),
+                diagnostics: Vec::new(),
+                functions: vec![CheckedFunction {
+                    name: "g".to_string(),
+                    function: "g".to_string(),
+                    row: Some(0),
+                    end_row: None,
+                }],
+                suppressed: 0,
+                definitions: 0,
+            },
+        ];
+
+        assert_eq!(
+            to_markdown(&reports),
+            "## pystaleds
+
+Scanned 2 files, checked 1 function and found 3 violations.
+
+### `src/module.py`
+
+| Function | Line | Problem | Expected | Documented |
+| --- | --- | --- | --- | --- |
+| `3` | 4 | PSD201 Docstring args not matching | `x: int` | `y` |
+| `f` |  | PSD101 Either \\| or |  |  |
+| `3` | 4 | PSD201 Docstring args not matching | `a`, `b`, `c`, `d`, `e`, … (+2 more) |  |
+"
+        );
+
+        assert_eq!(to_markdown(&reports[1..]), "✅ all docstrings up to date");
+    }
+
+    #[test]
+    fn junit() {
+        let path = PathBuf::from("src/module.py");
+
+        let reports = [SourceReport {
+            path: Some(path.clone()),
+            diagnostics: diagnostics().to_vec(),
+            functions: vec![
+                CheckedFunction {
+                    name: "f".to_string(),
+                    function: "3".to_string(),
+                    row: Some(3),
+                    end_row: None,
+                },
+                CheckedFunction {
+                    name: "g".to_string(),
+                    function: "10".to_string(),
+                    row: Some(10),
+                    end_row: None,
+                },
+            ],
+            suppressed: 0,
+            definitions: 0,
+        }];
+
+        assert_eq!(
+            to_junit(&reports),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="pystaleds" tests="3" failures="2">
+  <testsuite name="src/module.py" tests="3" failures="2">
+    <testcase name="f" classname="src/module.py" file="src/module.py" line="4">
+      <failure type="PSD201" message="Docstring args not matching">line 4: src/module.py: `3`: PSD201 Docstring args not matching</failure>
+    </testcase>
+    <testcase name="g" classname="src/module.py" file="src/module.py" line="11"/>
+    <testcase name="f" classname="src/module.py" file="src/module.py">
+      <failure type="PSD101" message="Docstring missing">src/module.py: `f`: PSD101 Docstring missing</failure>
+    </testcase>
+  </testsuite>
+</testsuites>"#
+        );
+    }
+}
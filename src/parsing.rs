@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 /// Parses a google docstring into a Vec with the names of the args and their types.
 ///
 /// # Examples
@@ -168,7 +170,7 @@ pub fn parse_numpy_docstring(
     for line in args.lines().skip(1) {
         if line.chars().take(indentation).all(|c| c.is_whitespace())
             && line.chars().nth(indentation).map(|c| !c.is_whitespace()) == Some(true)
-            && !line.trim().trim_end_matches(&['\'', '\"']).is_empty()
+            && !line.trim().trim_end_matches(['\'', '\"']).is_empty()
         {
             let Some((arg, typ)) = line.split_once(':') else {
                 let trimmed_line = line.trim();
@@ -214,6 +216,861 @@ pub fn extract_docstring(content: &str) -> Option<&str> {
     }
 }
 
+/// Extracts the doctest statements (i.e., the ones starting with `>>>`) from the
+/// "Examples" section of a docstring, along with the line of the docstring in which each
+/// statement starts.
+///
+/// Continuation lines (starting with `...`) are joined to the statement they continue,
+/// while expected output lines are ignored.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::parsing::extract_doctest_statements;
+///
+/// let statements = extract_doctest_statements(
+///            r#""""This is my docstring!!!.
+///
+///    Examples:
+///        >>> for i in range(2):
+///        ...     print(i)
+///        0
+///        1
+///    """#,
+///        );
+///
+/// assert_eq!(statements, vec![(3, "for i in range(2):\n    print(i)".to_string())]);
+/// ```
+pub fn extract_doctest_statements(text: &str) -> Vec<(usize, String)> {
+    let lines: Vec<&str> = text.lines().collect();
+
+    let is_numpy_underline = |i: usize| {
+        lines.get(i).is_some_and(|line| {
+            let line = line.trim();
+            !line.is_empty() && line.chars().all(|c| c == '-')
+        })
+    };
+
+    let Some((header, is_numpy)) = lines.iter().enumerate().find_map(|(i, line)| {
+        match line.trim().trim_start_matches(['\'', '\"']) {
+            "Examples:" | "Example:" => Some((i, false)),
+            "Examples" | "Example" if is_numpy_underline(i + 1) => Some((i, true)),
+            _ => None,
+        }
+    }) else {
+        return Vec::new();
+    };
+
+    let header_indentation = lines[header]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .count();
+
+    let start = if is_numpy { header + 2 } else { header + 1 };
+
+    let mut statements: Vec<(usize, String)> = Vec::new();
+    let mut in_statement = false;
+
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        let trimmed = line.trim_start();
+
+        if !trimmed.is_empty() {
+            let indentation = line.len() - trimmed.len();
+
+            let section_ended = if is_numpy {
+                is_numpy_underline(i + 1)
+            } else {
+                indentation <= header_indentation
+            };
+
+            if section_ended {
+                break;
+            }
+        }
+
+        if let Some(code) = trimmed.strip_prefix(">>>") {
+            let code = code.strip_prefix(' ').unwrap_or(code);
+
+            statements.push((i, code.to_string()));
+            in_statement = true;
+        } else if let (Some(code), true) = (trimmed.strip_prefix("..."), in_statement) {
+            let code = code.strip_prefix(' ').unwrap_or(code);
+
+            if let Some((_, statement)) = statements.last_mut() {
+                statement.push('\n');
+                statement.push_str(code);
+            }
+        } else {
+            in_statement = false;
+        }
+    }
+
+    statements
+}
+
+/// Extracts the names referenced in the "See Also" section of a docstring, along with
+/// the line of the docstring in which each one of them is.
+///
+/// Both the numpy style (names at the indentation of the heading, optionally followed by
+/// ` : description`) and the google style (names indented under a `See Also:` heading)
+/// are supported. Several names can be listed in the same line, separated by commas.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::parsing::parse_see_also_section;
+///
+/// let names = parse_see_also_section(
+///            r#""""This is my docstring!!!.
+///
+///    See Also
+///    --------
+///    other_module.helper : Does something else.
+///        And keeps on doing it.
+///    first, second
+///    """#,
+///        );
+///
+/// assert_eq!(
+///     names,
+///     vec![(4, "other_module.helper"), (6, "first"), (6, "second")],
+/// );
+/// ```
+pub fn parse_see_also_section(text: &str) -> Vec<(usize, &str)> {
+    let lines: Vec<&str> = text.lines().collect();
+
+    let is_numpy_underline = |i: usize| {
+        lines.get(i).is_some_and(|line| {
+            let line = line.trim();
+            !line.is_empty() && line.chars().all(|c| c == '-')
+        })
+    };
+
+    let Some((header, is_numpy)) = lines.iter().enumerate().find_map(|(i, line)| {
+        match line.trim().trim_start_matches(['\'', '\"']) {
+            "See Also:" => Some((i, false)),
+            "See Also" if is_numpy_underline(i + 1) => Some((i, true)),
+            _ => None,
+        }
+    }) else {
+        return Vec::new();
+    };
+
+    let header_indentation = indentation_of(lines[header]);
+
+    let start = if is_numpy { header + 2 } else { header + 1 };
+
+    let mut entry_indentation = None;
+    let mut names = Vec::new();
+
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        let trimmed = line.trim().trim_end_matches(['\'', '\"']).trim_end();
+
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let indentation = indentation_of(line);
+
+        let section_ended = if is_numpy {
+            indentation < header_indentation || is_numpy_underline(i + 1)
+        } else {
+            indentation <= header_indentation
+        };
+
+        if section_ended {
+            break;
+        }
+
+        let entry_indentation = *entry_indentation.get_or_insert(indentation);
+
+        if indentation > entry_indentation {
+            continue; // Description of the previous entry.
+        }
+
+        let references = if is_numpy {
+            trimmed
+                .split_once(" : ")
+                .map_or(trimmed, |(references, _)| references)
+        } else {
+            trimmed
+                .split_once(':')
+                .map_or(trimmed, |(references, _)| references)
+        };
+
+        names.extend(
+            references
+                .split(',')
+                .map(|name| name.trim().trim_matches('`'))
+                .filter(|name| !name.is_empty())
+                .map(|name| (i, name)),
+        );
+    }
+
+    names
+}
+
+/// Recognized section headings, in their google form without the colon, along with
+/// their aliases in either style.
+const SECTION_HEADINGS: [(&str, &[&str]); 10] = [
+    ("Args", &["Args", "Arguments", "Parameters"]),
+    ("Attributes", &["Attributes"]),
+    ("Returns", &["Returns"]),
+    ("Yields", &["Yields"]),
+    ("Raises", &["Raises"]),
+    ("Warns", &["Warns"]),
+    ("See Also", &["See Also"]),
+    ("Notes", &["Notes", "Note"]),
+    ("References", &["References"]),
+    ("Examples", &["Examples", "Example"]),
+];
+
+/// Extracts the recognized section headings of a docstring, along with the byte offset
+/// within the docstring at which each one of them is.
+///
+/// Both the google (`Args:`) and the numpy (`Parameters` followed by an underline) forms
+/// are recognized, and the headings are normalized to their google names without the
+/// colon, e.g. `Parameters` becomes `Args`.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::parsing::section_headings;
+///
+/// let headings = section_headings(
+///            r#""""This is my docstring!!!.
+///
+///    Returns
+///    -------
+///    int
+///        Something.
+///
+///    Parameters
+///    ----------
+///    x : int
+///        This is my first parameter.
+///    """"#,
+///        );
+///
+/// assert_eq!(headings, vec![(32, "Returns"), (80, "Args")]);
+/// ```
+pub fn section_headings(text: &str) -> Vec<(usize, &'static str)> {
+    let lines = lines_with_offsets(text);
+
+    let is_numpy_underline = |i: usize| {
+        lines.get(i).is_some_and(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && line.chars().all(|c| c == '-')
+        })
+    };
+
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &(offset, line))| {
+            let trimmed = line.trim().trim_start_matches(['\'', '\"']);
+
+            let heading = match trimmed.strip_suffix(':') {
+                Some(heading) => heading,
+                None if is_numpy_underline(i + 1) => trimmed,
+                None => return None,
+            };
+
+            let (name, _) = SECTION_HEADINGS
+                .iter()
+                .find(|(_, aliases)| aliases.contains(&heading))?;
+
+            Some((offset + indentation_of(line), *name))
+        })
+        .collect()
+}
+
+/// Determines if a docstring starts with a summary, i.e. if its first non-empty line is
+/// not a section heading.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::parsing::has_summary;
+///
+/// assert!(has_summary(r#""""This is my docstring!!!.""""#));
+/// assert!(!has_summary(""));
+/// assert!(!has_summary(
+///            r#""""
+///    Args:
+///        x: This is my first parameter.
+///    """"#,
+///        ));
+/// ```
+pub fn has_summary(text: &str) -> bool {
+    let (offset, body) = docstring_body(text);
+
+    let Some((start, line)) = lines_with_offsets(body)
+        .into_iter()
+        .find(|(_, line)| !line.trim().is_empty())
+    else {
+        return false;
+    };
+
+    let first_heading = section_headings(text).first().map(|&(offset, _)| offset);
+
+    first_heading != Some(offset + start + indentation_of(line))
+}
+
+/// Kinds of docstring sections whose entries can be parsed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SectionKind {
+    Args,
+    Returns,
+    Yields,
+    Raises,
+    Attributes,
+}
+
+impl SectionKind {
+    fn from_google_heading(heading: &str) -> Option<Self> {
+        match heading {
+            "Args:" | "Arguments:" => Some(SectionKind::Args),
+            "Returns:" => Some(SectionKind::Returns),
+            "Yields:" => Some(SectionKind::Yields),
+            "Raises:" => Some(SectionKind::Raises),
+            "Attributes:" => Some(SectionKind::Attributes),
+            _ => None,
+        }
+    }
+
+    fn from_numpy_heading(heading: &str) -> Option<Self> {
+        match heading {
+            "Parameters" => Some(SectionKind::Args),
+            "Returns" => Some(SectionKind::Returns),
+            "Yields" => Some(SectionKind::Yields),
+            "Raises" => Some(SectionKind::Raises),
+            "Attributes" => Some(SectionKind::Attributes),
+            _ => None,
+        }
+    }
+
+    /// Heading of the section in a google docstring.
+    pub fn google_heading(self) -> &'static str {
+        match self {
+            SectionKind::Args => "Args:",
+            SectionKind::Returns => "Returns:",
+            SectionKind::Yields => "Yields:",
+            SectionKind::Raises => "Raises:",
+            SectionKind::Attributes => "Attributes:",
+        }
+    }
+
+    /// Heading of the section in a numpy docstring.
+    pub fn numpy_heading(self) -> &'static str {
+        match self {
+            SectionKind::Args => "Parameters",
+            SectionKind::Returns => "Returns",
+            SectionKind::Yields => "Yields",
+            SectionKind::Raises => "Raises",
+            SectionKind::Attributes => "Attributes",
+        }
+    }
+
+    /// Whether the entries of the section are identified by a name (such as args are)
+    /// instead of by a type (such as returns and exceptions are).
+    pub fn has_named_entries(self) -> bool {
+        matches!(self, SectionKind::Args | SectionKind::Attributes)
+    }
+
+    /// Whether the section can hold more than one entry in a google docstring.
+    fn has_multiple_google_entries(self) -> bool {
+        matches!(
+            self,
+            SectionKind::Args | SectionKind::Raises | SectionKind::Attributes
+        )
+    }
+}
+
+/// An entry of a docstring section, such as a single arg.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SectionEntry<'a> {
+    pub name: Option<&'a str>,
+    pub typ: Option<&'a str>,
+    /// Trimmed lines of the description. Blank lines in between are kept as empty
+    /// strings.
+    pub description: Vec<&'a str>,
+}
+
+impl SectionEntry<'_> {
+    /// Whether the type of the entry is marked as optional, as in `int, optional`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pystaleds::parsing::parse_google_sections;
+    ///
+    /// let sections = parse_google_sections(
+    ///            r#""""This is my docstring!!!.
+    ///
+    ///    Args:
+    ///        x (int, optional): This is my first parameter.
+    ///        y (int): And this is my second.
+    ///    """"#,
+    ///        );
+    ///
+    /// assert!(sections[0].entries[0].is_optional());
+    /// assert!(!sections[0].entries[1].is_optional());
+    /// ```
+    pub fn is_optional(&self) -> bool {
+        self.typ.is_some_and(|typ| {
+            typ.rsplit(',')
+                .next()
+                .is_some_and(|last| last.trim() == "optional")
+        })
+    }
+}
+
+/// A section of a docstring, such as "Args" or "Returns".
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Section<'a> {
+    pub kind: SectionKind,
+    /// Byte range of the section within the docstring, from the start of the line of
+    /// its heading until the end of its last non-empty line.
+    pub range: Range<usize>,
+    /// Indentation of the heading of the section.
+    pub indentation: usize,
+    pub entries: Vec<SectionEntry<'a>>,
+}
+
+/// Extracts the exceptions listed in the "Raises" section out of the parsed sections of
+/// a docstring, or `None` if there is no such section.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::parsing::{parse_google_sections, parse_raises_section};
+///
+/// let sections = parse_google_sections(
+///            r#""""This is my docstring!!!.
+///
+///    Raises:
+///        ValueError: If something is off.
+///        errors.CustomError: If something else is off.
+///    """"#,
+///        );
+///
+/// assert_eq!(
+///     parse_raises_section(&sections),
+///     Some(vec!["ValueError", "errors.CustomError"]),
+/// );
+/// ```
+pub fn parse_raises_section<'a>(sections: &[Section<'a>]) -> Option<Vec<&'a str>> {
+    let section = sections
+        .iter()
+        .find(|section| section.kind == SectionKind::Raises)?;
+
+    Some(
+        section
+            .entries
+            .iter()
+            .filter_map(|entry| entry.typ)
+            .collect(),
+    )
+}
+
+/// Extracts the names and types of the attributes listed in the "Attributes" section out
+/// of the parsed sections of a class docstring, or `None` if there is no such section.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::parsing::{parse_numpy_sections, parse_attributes_section};
+///
+/// let sections = parse_numpy_sections(
+///            r#""""This is my class!!!.
+///
+///    Attributes
+///    ----------
+///    x : int
+///        This is my first attribute.
+///    y
+///        And this is my second.
+///    """"#,
+///        );
+///
+/// assert_eq!(
+///     parse_attributes_section(&sections),
+///     Some(vec![("x", Some("int")), ("y", None)]),
+/// );
+/// ```
+pub fn parse_attributes_section<'a>(
+    sections: &[Section<'a>],
+) -> Option<Vec<(&'a str, Option<&'a str>)>> {
+    let section = sections
+        .iter()
+        .find(|section| section.kind == SectionKind::Attributes)?;
+
+    Some(
+        section
+            .entries
+            .iter()
+            .filter_map(|entry| Some((entry.name?, entry.typ)))
+            .collect(),
+    )
+}
+
+/// Returns the contents of a docstring between its quotes, along with the offset at
+/// which they start.
+fn docstring_body(text: &str) -> (usize, &str) {
+    let is_quotes = |s: &str| s == r#"""""# || s == "'''";
+
+    let start = if text.get(..3).is_some_and(is_quotes) {
+        3
+    } else {
+        0
+    };
+
+    let end = if text.len() >= start + 3 && text.get(text.len() - 3..).is_some_and(is_quotes) {
+        text.len() - 3
+    } else {
+        text.len()
+    };
+
+    (start, &text[start..end])
+}
+
+/// Determines the byte offset of `part` within `text`, if `part` is a slice of `text`, as
+/// are the names and types returned by the parsers.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::parsing::{offset_within, parse_google_docstring};
+///
+/// let docstring = r#""""Hey.
+///
+///    Args:
+///        x (int): This is my first parameter.
+///    """"#;
+///
+/// let args = parse_google_docstring(docstring, false, true).unwrap();
+///
+/// assert_eq!(offset_within(docstring, args[0].0), Some(25));
+/// assert_eq!(offset_within(docstring, "x"), None);
+/// ```
+pub fn offset_within(text: &str, part: &str) -> Option<usize> {
+    let offset = (part.as_ptr() as usize).checked_sub(text.as_ptr() as usize)?;
+
+    (offset + part.len() <= text.len()).then_some(offset)
+}
+
+/// Splits the text into lines, along with the offset at which each one of them starts.
+fn lines_with_offsets(text: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+
+    text.split_inclusive('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len();
+
+            (start, line.trim_end_matches(['\n', '\r']))
+        })
+        .collect()
+}
+
+fn indentation_of(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count()
+}
+
+/// Splits a line such as `int: The result.` into its type and description, if the text
+/// before the colon looks like a type.
+fn split_type_prefix(text: &str) -> Option<(&str, &str)> {
+    let (typ, description) = text.split_once(':')?;
+
+    let mut depth = 0;
+    let mut outside_brackets = String::new();
+
+    for c in typ.chars() {
+        match c {
+            '[' | '(' | '{' => depth += 1,
+            ']' | ')' | '}' => depth -= 1,
+            _ if depth == 0 => outside_brackets.push(c),
+            _ => (),
+        }
+    }
+
+    let looks_like_type = depth == 0
+        && outside_brackets.split('|').all(|part| {
+            let part = part.trim();
+
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        });
+
+    looks_like_type.then(|| (typ.trim(), description.trim()))
+}
+
+fn parse_google_entry(text: &str, kind: SectionKind) -> SectionEntry<'_> {
+    let (name, typ, description) = if kind.has_named_entries() {
+        let (arg, description) = text.split_once(':').unwrap_or((text, ""));
+        let arg = arg.trim();
+
+        match arg.split_once(' ') {
+            Some((name, typ)) => {
+                let typ = typ.trim().trim_start_matches('(').trim_end_matches(')');
+                (Some(name), Some(typ), description.trim())
+            }
+            None => (Some(arg), None, description.trim()),
+        }
+    } else {
+        match split_type_prefix(text) {
+            Some((typ, description)) => (None, Some(typ), description),
+            None => (None, None, text),
+        }
+    };
+
+    SectionEntry {
+        name,
+        typ,
+        description: if description.is_empty() {
+            Vec::new()
+        } else {
+            vec![description]
+        },
+    }
+}
+
+fn parse_numpy_entry(text: &str, kind: SectionKind) -> SectionEntry<'_> {
+    let separated = if kind.has_named_entries() {
+        text.split_once(':')
+    } else {
+        text.split_once(" : ")
+    };
+
+    let (name, typ) = match separated {
+        Some((name, typ)) => (
+            Some(name.trim()),
+            Some(typ.trim()).filter(|t| !t.is_empty()),
+        ),
+        None if kind.has_named_entries() => (Some(text), None),
+        None => (None, Some(text)),
+    };
+
+    SectionEntry {
+        name,
+        typ,
+        description: Vec::new(),
+    }
+}
+
+/// Adds a description line to the last entry, along with the blank lines preceding it.
+fn push_description<'a>(
+    entries: &mut [SectionEntry<'a>],
+    pending_blank_lines: &mut usize,
+    line: &'a str,
+) {
+    if let Some(entry) = entries.last_mut() {
+        if !entry.description.is_empty() {
+            entry
+                .description
+                .extend(std::iter::repeat_n("", *pending_blank_lines));
+        }
+
+        entry.description.push(line);
+    }
+
+    *pending_blank_lines = 0;
+}
+
+/// Parses the sections of a google docstring, keeping the descriptions of each entry
+/// and the position of each section.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::parsing::{parse_google_sections, SectionKind};
+///
+/// let sections = parse_google_sections(
+///            r#""""This is my docstring!!!.
+///
+///    Args:
+///        x (int): This is my first parameter.
+///            It spans two lines.
+///
+///    Returns:
+///        int: Something.
+///    """#,
+///        );
+///
+/// assert_eq!(sections[0].kind, SectionKind::Args);
+/// assert_eq!(sections[0].entries[0].name, Some("x"));
+/// assert_eq!(
+///     sections[0].entries[0].description,
+///     vec!["This is my first parameter.", "It spans two lines."]
+/// );
+/// assert_eq!(sections[1].kind, SectionKind::Returns);
+/// assert_eq!(sections[1].entries[0].typ, Some("int"));
+/// ```
+pub fn parse_google_sections(text: &str) -> Vec<Section<'_>> {
+    let (offset, body) = docstring_body(text);
+    let lines = lines_with_offsets(body);
+
+    let mut sections = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let (start, line) = lines[i];
+        i += 1;
+
+        let Some(kind) = SectionKind::from_google_heading(line.trim()) else {
+            continue;
+        };
+
+        let indentation = indentation_of(line);
+
+        let mut end = start + line.len();
+        let mut entries = Vec::new();
+        let mut entry_indentation = None;
+        let mut pending_blank_lines = 0;
+
+        while let Some(&(line_start, line)) = lines.get(i) {
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() {
+                pending_blank_lines += 1;
+                i += 1;
+                continue;
+            }
+
+            let line_indentation = indentation_of(line);
+
+            if line_indentation <= indentation {
+                break;
+            }
+
+            let entry_indentation = *entry_indentation.get_or_insert(line_indentation);
+
+            if line_indentation <= entry_indentation
+                && (kind.has_multiple_google_entries() || entries.is_empty())
+            {
+                entries.push(parse_google_entry(trimmed, kind));
+                pending_blank_lines = 0;
+            } else {
+                push_description(&mut entries, &mut pending_blank_lines, trimmed);
+            }
+
+            end = line_start + line.len();
+            i += 1;
+        }
+
+        sections.push(Section {
+            kind,
+            range: offset + start..offset + end,
+            indentation,
+            entries,
+        });
+    }
+
+    sections
+}
+
+/// Parses the sections of a numpy docstring, keeping the descriptions of each entry
+/// and the position of each section.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::parsing::{parse_numpy_sections, SectionKind};
+///
+/// let sections = parse_numpy_sections(
+///            r#""""This is my docstring!!!.
+///
+///    Parameters
+///    ----------
+///    x : int
+///        This is my first parameter.
+///
+///    Returns
+///    -------
+///    int
+///        Something.
+///    """#,
+///        );
+///
+/// assert_eq!(sections[0].kind, SectionKind::Args);
+/// assert_eq!(sections[0].entries[0].name, Some("x"));
+/// assert_eq!(sections[0].entries[0].typ, Some("int"));
+/// assert_eq!(sections[1].kind, SectionKind::Returns);
+/// assert_eq!(sections[1].entries[0].description, vec!["Something."]);
+/// ```
+pub fn parse_numpy_sections(text: &str) -> Vec<Section<'_>> {
+    let (offset, body) = docstring_body(text);
+    let lines = lines_with_offsets(body);
+
+    let is_heading = |i: usize| {
+        lines.get(i + 1).is_some_and(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && line.chars().all(|c| c == '-')
+        })
+    };
+
+    let mut sections = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let (start, line) = lines[i];
+
+        let Some(kind) = SectionKind::from_numpy_heading(line.trim()).filter(|_| is_heading(i))
+        else {
+            i += 1;
+            continue;
+        };
+
+        let indentation = indentation_of(line);
+
+        // Skipping the heading and its underline.
+        let (underline_start, underline) = lines[i + 1];
+        let mut end = underline_start + underline.len();
+        i += 2;
+
+        let mut entries = Vec::new();
+        let mut pending_blank_lines = 0;
+
+        while let Some(&(line_start, line)) = lines.get(i) {
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() {
+                pending_blank_lines += 1;
+                i += 1;
+                continue;
+            }
+
+            let line_indentation = indentation_of(line);
+
+            if line_indentation < indentation || is_heading(i) {
+                break;
+            }
+
+            if line_indentation == indentation {
+                entries.push(parse_numpy_entry(trimmed, kind));
+                pending_blank_lines = 0;
+            } else {
+                push_description(&mut entries, &mut pending_blank_lines, trimmed);
+            }
+
+            end = line_start + line.len();
+            i += 1;
+        }
+
+        sections.push(Section {
+            kind,
+            range: offset + start..offset + end,
+            indentation,
+            entries,
+        });
+    }
+
+    sections
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -355,4 +1212,54 @@ mod tests {
 
         assert_eq!(parsed, vec![("x", None), ("y", None), ("**kwargs", None)]);
     }
+
+    #[test]
+    fn test_doctest_statements() {
+        let docstring = r#""""Hey.
+
+            Args:
+                x (int): First var.
+
+            Examples:
+                >>> f(1)
+                2
+                >>> def g(x):
+                ...     return x
+                >>> g(2)  # doctest: +SKIP
+
+            Returns:
+                >>> not_an_example()
+            """"#;
+
+        assert_eq!(
+            extract_doctest_statements(docstring),
+            vec![
+                (6, "f(1)".to_string()),
+                (8, "def g(x):\n    return x".to_string()),
+                (10, "g(2)  # doctest: +SKIP".to_string())
+            ]
+        );
+
+        let docstring = r#""""Hey.
+
+            Examples
+            --------
+            >>> f(1)
+            2
+            >>> [1,
+            ...  2]
+            [1, 2]
+
+            Notes
+            -----
+            >>> not_an_example()
+            """"#;
+
+        assert_eq!(
+            extract_doctest_statements(docstring),
+            vec![(4, "f(1)".to_string()), (6, "[1,\n 2]".to_string())]
+        );
+
+        assert!(extract_doctest_statements(r#""""No examples here.""""#).is_empty());
+    }
 }
@@ -1,15 +1,35 @@
-use std::path::Path;
+use std::fmt::Display;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
 
 use clap::ValueEnum;
 use logos::Lexer;
-use tracing::Level;
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use tree_sitter::{Node, Parser, Tree, TreeCursor};
 
-use crate::ast_parsing::{get_function_signature, FunctionInfo};
-use crate::lexing::get_next_function_info;
-use crate::parsing::{parse_google_docstring, parse_numpy_docstring};
-
-#[derive(Default, Clone, Copy, ValueEnum)]
+use crate::ast_parsing::{
+    body_line_count, generated_marker, get_class_info, get_enclosing_class_docstring,
+    get_function_signature, get_raised_exceptions, has_module_docstring, has_skip_file_comment,
+    is_generator, ClassInfo, FunctionInfo, Suppression, HEADER_LINES,
+};
+use crate::cancellation::CancellationToken;
+use crate::diagnostics::{
+    Diagnostic, EntriesComparison, Rule, RuleSelection, RuleSelector, Severity, Spans,
+};
+use crate::fixing::plan_fixes;
+use crate::lexing::{self, get_next_function_info};
+use crate::parsing::{
+    extract_doctest_statements, has_summary, offset_within, parse_attributes_section,
+    parse_google_docstring, parse_google_sections, parse_numpy_docstring, parse_numpy_sections,
+    parse_raises_section, section_headings, Section, SectionKind,
+};
+use crate::stats::{is_trivial_init, pluralize};
+
+#[derive(Debug, Default, Clone, Copy, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum DocstringStyle {
     Google,
     Numpy,
@@ -17,8 +37,18 @@ pub enum DocstringStyle {
     AutoDetect,
 }
 
+impl Display for DocstringStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocstringStyle::Google => f.write_str("Google"),
+            DocstringStyle::Numpy => f.write_str("Numpy"),
+            DocstringStyle::AutoDetect => f.write_str("auto-detected"),
+        }
+    }
+}
+
 /// Walks recursively through a tree applying a closure on each node.
-fn walk_rec<F>(cursor: &mut TreeCursor, closure: &mut F)
+pub(crate) fn walk_rec<F>(cursor: &mut TreeCursor, closure: &mut F)
 where
     for<'a> F: FnMut(&Node),
 {
@@ -37,352 +67,5095 @@ where
     cursor.goto_parent();
 }
 
-/// Checks if the source code respects the specified rules.
-#[allow(clippy::too_many_arguments)]
-pub fn respects_rules(
+/// Placeholders left by docstring generators (or by people) that are flagged by default.
+pub const DEFAULT_PLACEHOLDERS: [&str; 5] =
+    ["_description_", "_summary_", "_type_", "TODO", "FIXME"];
+
+/// Pattern that, by default, the descriptions of parameters with default values should
+/// match.
+pub const DEFAULT_MENTION_PATTERN: &str = "(?i)default";
+
+/// Markers in the first lines of the files written by code generators, such as
+/// `# Generated by Django 5.0` or `# @generated`, with which they are skipped by default.
+pub const DEFAULT_GENERATED_MARKERS: [&str; 3] = ["@generated", "DO NOT EDIT", "Generated by"];
+
+/// Order of the sections that is expected by default.
+pub const DEFAULT_SECTION_ORDER: [&str; 5] = ["Args", "Returns", "Yields", "Raises", "Examples"];
+
+/// Options that determine which rules are enforced when checking source code.
+#[derive(Clone)]
+pub struct CheckOptions {
+    /// Considers that an "Args" section breaks on an empty line.
+    pub break_on_empty_line: bool,
+    /// Succeeds if a function has no docstring.
+    pub succeed_if_no_docstring: bool,
+    /// Succeeds if a docstring has no "Args" or "Parameters" section.
+    pub succeed_if_no_args_in_docstring: bool,
+    /// Succeeds if the args in the docstring are untyped, as long as the names match.
+    pub succeed_if_docstrings_are_not_typed: bool,
+    /// Completely ignores *args and **kwargs.
+    pub skip_args_and_kwargs: bool,
+    /// Docstring style to consider for parsing.
+    pub docstyle: DocstringStyle,
+    /// Fails if the docstring is written in a style other than the configured one.
+    pub forbid_style_mismatch: bool,
+    /// Fails if the doctest statements in an "Examples" section are not valid Python.
+    pub check_examples_style: bool,
+    /// Fails if a function with a non-`None` return annotation has no "Returns" section.
+    pub forbid_missing_returns: bool,
+    /// Fails if the type in the "Returns" section differs from the return annotation.
+    pub check_return_type: bool,
+    /// Fails if a name referenced in a "See Also" section is not defined in any of the
+    /// checked files. Since this needs every file, it is enforced by the CLI through
+    /// [`crate::see_also`] rather than when checking a source.
+    pub check_see_also: bool,
+    /// Fails if the exceptions in the "Raises" section differ from the ones raised in the
+    /// function. Only supported by the tree-sitter parser.
+    pub check_raises: bool,
+    /// Fails if a generator has no "Yields" section, or if a function that is not a
+    /// generator has one. Only supported by the tree-sitter parser.
+    pub forbid_missing_yields: bool,
+    /// Fails if the type in the "Yields" section differs from the one in the return
+    /// annotation of a generator, e.g. `Foo` in `Iterator[Foo]`.
+    pub check_yield_type: bool,
+    /// Fails if the "Attributes" section of a class docstring does not match the annotated
+    /// attributes of the class. Only supported by the tree-sitter parser.
+    pub check_attributes: bool,
+    /// Validates the args of an `__init__` against the docstring of its class when its own
+    /// docstring has no args section. Only supported by the tree-sitter parser.
+    pub init_args_in_class_docstring: bool,
+    /// Fails if a file does not start with a docstring.
+    pub forbid_no_module_docstring: bool,
+    /// Succeeds if an empty file (or one with only comments) has no module docstring.
+    pub succeed_if_empty_module: bool,
+    /// Exempts the `__init__.py` files whose only statements are imports and assignments
+    /// (such as `__all__`) from the module docstring rule.
+    pub skip_trivial_init: bool,
+    /// Fails if the description of a docstring, or the type or description of any of its
+    /// entries, contains one of the `placeholders`.
+    pub forbid_placeholders: bool,
+    /// Tokens considered placeholders by `forbid_placeholders`.
+    pub placeholders: Vec<String>,
+    /// Fails if an arg in the docstring has an empty description.
+    pub forbid_empty_descriptions: bool,
+    /// Fails if the description in the docstring of a parameter with a default value does
+    /// not match `default_mention_pattern`.
+    pub check_default_mentions: bool,
+    /// Pattern that the description of a parameter with a default value should match for
+    /// `check_default_mentions`.
+    pub default_mention_pattern: Regex,
+    /// Fails if an arg is documented as optional but has no default value, or if it has a
+    /// default value but is not documented as optional.
+    pub check_optional_consistency: bool,
+    /// Succeeds if the args in the docstring are the same as the ones in the signature,
+    /// but in another order.
+    pub allow_out_of_order_args: bool,
+    /// Fails if the sections of a docstring are not in the order of `section_order`.
+    pub check_section_order: bool,
+    /// Expected order of the sections for `check_section_order`, by their google names
+    /// (e.g., "Args" for numpy's "Parameters"). Sections not listed can be anywhere.
+    pub section_order: Vec<String>,
+    /// Fails if a docstring is empty or starts with a section instead of a summary.
+    pub forbid_missing_summary: bool,
+    /// Succeeds if the docstring documents `self` or `cls`, which are not expected to be.
+    pub allow_documented_self: bool,
+    /// Fails if a parameter has no type annotation, or if an arg in the docstring has no
+    /// type, in addition to the types being compared.
+    pub require_full_typing: bool,
+    /// Succeeds if params with default values are not documented, as long as the
+    /// documented ones match.
+    pub allow_omitting_defaulted_args: bool,
+    /// Succeeds if the docstring documents args that are not in the signature, as long as
+    /// it has a `**` param (such as `**kwargs`), whose accepted keys may be documented.
+    pub allow_kwargs_docs: bool,
+    /// Succeeds if a function whose body is just `...` or `pass` (possibly after its
+    /// docstring) has no docstring, or no args in it. Docstrings with args are still
+    /// checked.
+    pub skip_stub_bodies: bool,
+    /// Skips the source code with any of [`CheckOptions::generated_markers`] in its first
+    /// lines, as written by code generators.
+    pub skip_generated: bool,
+    /// Markers of generated source code, such as `@generated`.
+    pub generated_markers: Vec<String>,
+    /// Succeeds if the names of the args in the docstring differ from the ones in the
+    /// signature only by case.
+    pub ignore_param_case: bool,
+    /// Skips the functions that are not of the selected kind.
+    pub only: FunctionSelection,
+    /// Skips the functions whose body spans fewer lines than this. Only supported by the
+    /// tree-sitter parser.
+    pub min_function_lines: usize,
+    /// Skips the functions whose names start with an underscore, except for dunders.
+    pub ignore_private: bool,
+    /// Skips the functions whose names start and end with two underscores, such as
+    /// `__init__`.
+    pub ignore_dunder: bool,
+    /// Skips the functions with a decorator whose dotted name ends with any of these, such
+    /// as `command` or `cli.command` for `@cli.command("x")`. Only supported by the
+    /// tree-sitter parser.
+    pub skip_decorated: Vec<String>,
+    /// Skips the functions whose names match any of these patterns.
+    pub exclude_functions: Vec<Regex>,
+    /// Names of the params that, besides the receivers of methods, are never expected to be
+    /// documented. They are left out of both the signatures and the docstrings.
+    pub ignore_params: Vec<String>,
+    /// Which of the rules enabled by the other options are reported.
+    pub rules: RuleSelection,
+    /// Attaches to the diagnostics that can be fixed the edits that fix them, planned with
+    /// [`crate::fixing::plan_fixes`] on the checked source code.
+    pub attach_fixes: bool,
+    /// Selectors of the rules whose violations are fixed, whether they are reported or
+    /// not. The reported rules are fixed if empty.
+    pub fix_only: Vec<RuleSelector>,
+}
+
+impl Default for CheckOptions {
+    fn default() -> Self {
+        Self {
+            break_on_empty_line: false,
+            succeed_if_no_docstring: true,
+            succeed_if_no_args_in_docstring: true,
+            succeed_if_docstrings_are_not_typed: true,
+            skip_args_and_kwargs: true,
+            docstyle: DocstringStyle::default(),
+            forbid_style_mismatch: false,
+            check_examples_style: false,
+            forbid_missing_returns: false,
+            check_return_type: false,
+            check_see_also: false,
+            check_raises: false,
+            forbid_missing_yields: false,
+            check_yield_type: false,
+            check_attributes: false,
+            init_args_in_class_docstring: false,
+            forbid_no_module_docstring: false,
+            succeed_if_empty_module: true,
+            skip_trivial_init: true,
+            forbid_placeholders: false,
+            placeholders: DEFAULT_PLACEHOLDERS.map(String::from).to_vec(),
+            forbid_empty_descriptions: false,
+            check_default_mentions: false,
+            default_mention_pattern: Regex::new(DEFAULT_MENTION_PATTERN)
+                .expect("default pattern should be valid"),
+            check_optional_consistency: false,
+            allow_out_of_order_args: false,
+            check_section_order: false,
+            section_order: DEFAULT_SECTION_ORDER.map(String::from).to_vec(),
+            forbid_missing_summary: false,
+            allow_documented_self: false,
+            require_full_typing: false,
+            allow_omitting_defaulted_args: false,
+            allow_kwargs_docs: false,
+            skip_stub_bodies: false,
+            skip_generated: true,
+            generated_markers: DEFAULT_GENERATED_MARKERS.map(String::from).to_vec(),
+            ignore_param_case: false,
+            only: FunctionSelection::default(),
+            min_function_lines: 0,
+            ignore_private: false,
+            ignore_dunder: false,
+            skip_decorated: Vec::new(),
+            exclude_functions: Vec::new(),
+            ignore_params: Vec::new(),
+            rules: RuleSelection::default(),
+            attach_fixes: false,
+            fix_only: Vec::new(),
+        }
+    }
+}
+
+impl CheckOptions {
+    /// Determines if the rule is enforced with these options, i.e. if it is enabled and
+    /// selected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pystaleds::diagnostics::Rule;
+    /// use pystaleds::rules_checking::CheckOptions;
+    ///
+    /// let options = CheckOptions::default();
+    ///
+    /// assert!(options.enables(Rule::MissingArg));
+    /// assert!(!options.enables(Rule::MissingDocstring));
+    /// ```
+    pub fn enables(&self, rule: Rule) -> bool {
+        let enabled = match rule {
+            Rule::MissingDocstring => !self.succeed_if_no_docstring,
+            Rule::MissingArgs => !self.succeed_if_no_args_in_docstring,
+            Rule::MissingReturns => self.forbid_missing_returns,
+            Rule::MissingYields => self.forbid_missing_yields,
+            Rule::MissingModuleDocstring => self.forbid_no_module_docstring,
+            Rule::MissingSummary => self.forbid_missing_summary,
+            Rule::MissingArg
+            | Rule::ExtraArg
+            | Rule::ArgTypeMismatch
+            | Rule::DuplicateArg
+            | Rule::TypeCommentMismatch => true,
+            Rule::ArgsOrder => !self.allow_out_of_order_args,
+            Rule::ParamCaseMismatch => !self.ignore_param_case,
+            Rule::DocumentedSelf => !self.allow_documented_self,
+            Rule::UntypedDocstringArg | Rule::MissingAnnotation => self.require_full_typing,
+            Rule::OptionalMismatch => self.check_optional_consistency,
+            Rule::MissingDefaultMention => self.check_default_mentions,
+            Rule::ReturnTypeMismatch => self.check_return_type,
+            Rule::YieldTypeMismatch => self.check_yield_type,
+            Rule::RaisesMismatch => self.check_raises,
+            Rule::AttributesMismatch => self.check_attributes,
+            Rule::StaleSeeAlso => self.check_see_also,
+            Rule::InvalidExample => self.check_examples_style,
+            Rule::StyleMismatch => self.forbid_style_mismatch,
+            Rule::SectionOrder => self.check_section_order,
+            Rule::PlaceholderText => self.forbid_placeholders,
+            Rule::EmptyDescription => self.forbid_empty_descriptions,
+        };
+
+        enabled && self.rules.enables(rule)
+    }
+
+    /// Determines if the violations of the rule are fixed, which is up to `fix_only` if it
+    /// is given and to whether the rule is enforced otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pystaleds::diagnostics::Rule;
+    /// use pystaleds::rules_checking::CheckOptions;
+    ///
+    /// let options = CheckOptions {
+    ///     fix_only: vec!["PSD202".parse().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert!(options.fixes(Rule::ExtraArg));
+    /// assert!(!options.fixes(Rule::MissingArg));
+    /// ```
+    pub fn fixes(&self, rule: Rule) -> bool {
+        if self.fix_only.is_empty() {
+            self.enables(rule)
+        } else {
+            self.fix_only.iter().any(|selector| selector.matches(rule))
+        }
+    }
+
+    /// Checks if the options can be enforced with the chosen parser.
+    pub fn validate_for(&self, checker: CompliancyChecker) -> Result<(), CheckError> {
+        if let CompliancyChecker::Lexer = checker {
+            if self.check_raises {
+                return Err(CheckError::RequiresTreeSitter(
+                    "checking the \"Raises\" section",
+                ));
+            }
+
+            if self.forbid_missing_yields {
+                return Err(CheckError::RequiresTreeSitter(
+                    "checking the \"Yields\" section",
+                ));
+            }
+
+            if self.check_attributes {
+                return Err(CheckError::RequiresTreeSitter(
+                    "checking the \"Attributes\" section",
+                ));
+            }
+
+            if self.init_args_in_class_docstring {
+                return Err(CheckError::RequiresTreeSitter(
+                    "documenting `__init__` args in the class docstring",
+                ));
+            }
+
+            if self.min_function_lines > 0 {
+                return Err(CheckError::RequiresTreeSitter(
+                    "skipping functions by their number of lines",
+                ));
+            }
+
+            if !self.skip_decorated.is_empty() {
+                return Err(CheckError::RequiresTreeSitter(
+                    "skipping functions by their decorators",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Which functions are checked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FunctionSelection {
+    /// Only the functions that are not defined directly in the body of a class.
+    Functions,
+    /// Only the functions defined directly in the body of a class.
+    Methods,
+    #[default]
+    All,
+}
+
+/// Parser used to find the functions in the source code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompliancyChecker {
+    TreeSitter,
+
+    #[default]
+    Lexer,
+}
+
+/// Error of a check that could not be completed.
+#[derive(Debug, Error)]
+pub enum CheckError {
+    /// The check was cancelled through its [`CancellationToken`]. No partial results are
+    /// returned in this case.
+    #[error("check was cancelled")]
+    Cancelled,
+    /// The options include a rule that the chosen parser cannot enforce.
+    #[error("{0} requires the tree-sitter parser (`--parser tree-sitter`)")]
+    RequiresTreeSitter(&'static str),
+    /// The source code starts with a `# pystaleds: skip-file` comment, so it was not
+    /// parsed.
+    #[error("has a `# pystaleds: skip-file` comment")]
+    SkippedFile,
+    /// The source code has one of the markers of generated code in its first lines (which
+    /// is given), so it was not parsed.
+    #[error("is generated (has `{0}` in its first lines)")]
+    Generated(String),
+    #[error("could not read {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// A function that was checked, i.e. that was not skipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedFunction {
+    pub name: String,
+    /// Function of the diagnostics of the function, i.e. its name or the row in which it
+    /// is defined, depending on the parser.
+    pub function: String,
+    /// Row (0-based) in which the function is defined, if known.
+    pub row: Option<usize>,
+    /// Row (0-based) in which the body of the function ends, if known, which it is only
+    /// with the tree-sitter parser.
+    pub end_row: Option<usize>,
+}
+
+impl CheckedFunction {
+    /// Whether the diagnostic refers to this function.
+    pub fn matches(&self, diagnostic: &Diagnostic) -> bool {
+        diagnostic.function == self.function && diagnostic.function_row == self.row
+    }
+}
+
+/// Results of checking a source code.
+#[derive(Debug, Default, Clone)]
+pub struct SourceReport {
+    /// Path of the source code, if any.
+    pub path: Option<PathBuf>,
+    /// Violations found, in the order they were found.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Functions that were checked, in the order they were found.
+    pub functions: Vec<CheckedFunction>,
+    /// Number of functions whose checks were suppressed by a `# pystaleds: ignore`
+    /// comment.
+    pub suppressed: usize,
+    /// Number of functions defined in the source code, whether they were checked or not.
+    pub definitions: usize,
+}
+
+impl SourceReport {
+    /// Sorts the diagnostics and the checked functions by row, keeping the order of the
+    /// ones in the same row.
+    pub fn sort(&mut self) {
+        self.diagnostics.sort_by_key(|diagnostic| diagnostic.row);
+        self.functions.sort_by_key(|function| function.row);
+    }
+
+    /// Whether the source code defines no functions, in which case it has nothing to be
+    /// checked.
+    pub fn is_trivial(&self) -> bool {
+        self.definitions == 0
+    }
+
+    /// Checks a function unless it is skipped, recording it along with its violations.
+    fn check_function(&mut self, info: &FunctionInfo, options: &CheckOptions) {
+        let function = describe_function(self.path.as_deref(), info);
+
+        match &info.suppression {
+            Some(Suppression::All) => {
+                tracing::debug!("{function}: suppressed by a `# pystaleds: ignore` comment");
+                self.suppressed += 1;
+                return;
+            }
+            Some(Suppression::Rules { unknown, .. }) => {
+                for code in unknown {
+                    tracing::warn!(
+                        "{function}: `{code}` in `# pystaleds: ignore[...]` is not a rule code"
+                    );
+                }
+            }
+            None => (),
+        }
+
+        if let Some(reason) = skip_reason(info, options) {
+            tracing::debug!("{function}: skipped: {reason}");
+            return;
+        }
+
+        self.functions.push(CheckedFunction {
+            name: info.name.to_string(),
+            function: info.function_name.to_string(),
+            row: info.row,
+            end_row: info.end_row,
+        });
+
+        let diagnostics = check_function_info(info, options);
+
+        if diagnostics.is_empty() {
+            tracing::debug!("{function}: ok");
+        } else {
+            tracing::debug!(
+                "{function}: failed with {}",
+                pluralize(diagnostics.len(), "error")
+            );
+        }
+
+        self.diagnostics.extend(diagnostics);
+    }
+}
+
+/// Same as [`check_source`] with the tree-sitter parser, but reusing the given parser and
+/// the tree of a previous version of the source code.
+pub fn check_rules(
     parser: &mut Parser,
     source_code: &str,
     old_tree: Option<&Tree>,
     path: Option<&Path>,
-    break_on_empty_line: bool,
-    succeed_if_no_docstring: bool,
-    succeed_if_no_args_in_docstring: bool,
-    succeed_if_docstrings_are_not_typed: bool,
-    skip_args_and_kwargs: bool,
-    docstyle: DocstringStyle,
-) -> bool {
+    options: &CheckOptions,
+) -> Result<Vec<Diagnostic>, CheckError> {
+    check_with_parser(
+        parser,
+        old_tree,
+        source_code,
+        path,
+        CompliancyChecker::TreeSitter,
+        options,
+        None,
+    )
+    .map(|report| report.diagnostics)
+}
+
+/// Same as [`check_source`] with the lexer.
+pub fn check_rules_through_lexing(
+    source_code: &str,
+    path: Option<&Path>,
+    options: &CheckOptions,
+) -> Result<Vec<Diagnostic>, CheckError> {
+    check_source(source_code, path, CompliancyChecker::Lexer, options, None)
+}
+
+/// Checks the source code against the specified rules with the chosen parser, returning
+/// the violations found.
+///
+/// If a cancellation token is given, it is polled as the functions are visited, and
+/// [`CheckError::Cancelled`] is returned as soon as it is triggered. Source code with a
+/// `# pystaleds: skip-file` comment in its first lines is not parsed at all, returning
+/// [`CheckError::SkippedFile`], and neither is generated source code, returning
+/// [`CheckError::Generated`].
+pub fn check_source(
+    source_code: &str,
+    path: Option<&Path>,
+    checker: CompliancyChecker,
+    options: &CheckOptions,
+    cancellation: Option<&CancellationToken>,
+) -> Result<Vec<Diagnostic>, CheckError> {
+    check_source_report(source_code, path, checker, options, cancellation)
+        .map(|report| report.diagnostics)
+}
+
+/// Same as [`check_source`], but also returns the functions that were checked.
+pub fn check_source_report(
+    source_code: &str,
+    path: Option<&Path>,
+    checker: CompliancyChecker,
+    options: &CheckOptions,
+    cancellation: Option<&CancellationToken>,
+) -> Result<SourceReport, CheckError> {
+    check_with_parser(
+        &mut python_parser(),
+        None,
+        source_code,
+        path,
+        checker,
+        options,
+        cancellation,
+    )
+}
+
+/// Same as [`check_source_report`], with the tree-sitter parser to use, along with the
+/// tree of a previous version of the source code.
+fn check_with_parser(
+    parser: &mut Parser,
+    old_tree: Option<&Tree>,
+    source_code: &str,
+    path: Option<&Path>,
+    checker: CompliancyChecker,
+    options: &CheckOptions,
+    cancellation: Option<&CancellationToken>,
+) -> Result<SourceReport, CheckError> {
+    options.validate_for(checker)?;
+    check_header(source_code, options)?;
+
+    let mut report = match checker {
+        CompliancyChecker::TreeSitter => {
+            check_tree(parser, source_code, old_tree, path, options, cancellation)
+        }
+        CompliancyChecker::Lexer => check_lexing(source_code, path, options, cancellation),
+    }?;
+
+    if options.attach_fixes {
+        // The fixes are planned with tree-sitter, whichever parser checked the source.
+        let fixes = plan_fixes(parser, source_code, path, options);
+
+        for diagnostic in &mut report.diagnostics {
+            diagnostic.fix = fixes
+                .iter()
+                .find(|fix| {
+                    diagnostic.function_row == Some(fix.row) && fix.rules.contains(&diagnostic.rule)
+                })
+                .cloned();
+        }
+    }
+
+    Ok(report)
+}
+
+fn python_parser() -> Parser {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_python::language())
+        .expect("should be able to load Python grammar");
+
+    parser
+}
+
+/// Reads the first [`HEADER_LINES`] lines of a file, which are enough for
+/// [`check_header`] to decide whether it is skipped without reading it whole.
+pub fn read_header(reader: impl BufRead) -> std::io::Result<String> {
+    let lines: Vec<_> = reader
+        .lines()
+        .take(HEADER_LINES)
+        .collect::<Result<_, _>>()?;
+
+    Ok(lines.join("\n"))
+}
+
+/// Determines from the first lines of source code whether it is skipped, returning
+/// [`CheckError::SkippedFile`] for a `# pystaleds: skip-file` comment and
+/// [`CheckError::Generated`] for generated source code.
+pub fn check_header(header: &str, options: &CheckOptions) -> Result<(), CheckError> {
+    if has_skip_file_comment(header) {
+        return Err(CheckError::SkippedFile);
+    }
+
+    if !options.skip_generated {
+        return Ok(());
+    }
+
+    match generated_marker(header, &options.generated_markers) {
+        Some(marker) => Err(CheckError::Generated(marker.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Checks the files against the specified rules with the chosen parser, in parallel,
+/// returning the violations found.
+///
+/// If a cancellation token is given, it is polled before each file and while each one of
+/// them is checked, and [`CheckError::Cancelled`] is returned as soon as it is triggered.
+pub fn check_paths(
+    paths: &[PathBuf],
+    checker: CompliancyChecker,
+    options: &CheckOptions,
+    cancellation: Option<&CancellationToken>,
+) -> Result<Vec<Diagnostic>, CheckError> {
+    let diagnostics = paths
+        .par_iter()
+        .map(|path| {
+            if cancellation.is_some_and(CancellationToken::is_cancelled) {
+                return Err(CheckError::Cancelled);
+            }
+
+            let source_code = std::fs::read_to_string(path).map_err(|source| CheckError::Io {
+                path: path.clone(),
+                source,
+            })?;
+
+            match check_source(&source_code, Some(path), checker, options, cancellation) {
+                Err(CheckError::SkippedFile | CheckError::Generated(_)) => Ok(Vec::new()),
+                result => result,
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(diagnostics.into_iter().flatten().collect())
+}
+
+fn check_tree(
+    parser: &mut Parser,
+    source_code: &str,
+    old_tree: Option<&Tree>,
+    path: Option<&Path>,
+    options: &CheckOptions,
+    cancellation: Option<&CancellationToken>,
+) -> Result<SourceReport, CheckError> {
     let tree = parser
         .parse(source_code, old_tree)
         .expect("parser should be ready to parse");
 
-    let mut cursor = tree.walk();
+    let mut cursor = tree.walk();
+
+    let mut report = SourceReport {
+        path: path.map(Path::to_path_buf),
+        ..Default::default()
+    };
+    let mut params = Vec::with_capacity(8);
+    let mut cancelled = false;
+
+    if options.forbid_no_module_docstring {
+        report.diagnostics.extend(check_module_docstring(
+            has_module_docstring(&tree.root_node()),
+            path,
+            source_code,
+            Some(&tree),
+            options,
+        ));
+    }
+
+    walk_rec(&mut cursor, &mut |node| {
+        // The walk itself cannot be interrupted, but it is cheap compared to the checks.
+        if cancelled || cancellation.is_some_and(CancellationToken::is_cancelled) {
+            cancelled = true;
+            return;
+        }
+
+        if node.kind() == "function_definition" {
+            report.definitions += 1;
+        }
+
+        let is_short = options.min_function_lines > 0
+            && body_line_count(node).is_some_and(|lines| lines < options.min_function_lines);
+
+        let fs = get_function_signature(
+            node,
+            source_code,
+            &mut params,
+            options.skip_args_and_kwargs,
+            &options.ignore_params,
+        )
+        .filter(|info| {
+            if is_short {
+                tracing::debug!(
+                    "{}: skipped: body shorter than {} lines",
+                    describe_function(path, info),
+                    options.min_function_lines
+                );
+            }
+
+            !is_short
+        });
+        if let Some(mut info) = fs {
+            if options.check_raises {
+                info.raises = Some(get_raised_exceptions(node, source_code));
+            }
+
+            if options.forbid_missing_yields {
+                info.is_generator = Some(is_generator(node));
+            }
+
+            if options.init_args_in_class_docstring && info.name == "__init__" {
+                info.class_docstring = get_enclosing_class_docstring(node, source_code);
+            }
+
+            report.check_function(&info, options);
+        }
+
+        if options.check_attributes {
+            if let Some(info) = get_class_info(node, source_code) {
+                report.diagnostics.extend(check_class_info(&info, options));
+            }
+        }
+    });
+
+    if cancelled {
+        return Err(CheckError::Cancelled);
+    }
+
+    report.diagnostics = with_path(options.rules.apply(report.diagnostics), path);
+
+    Ok(report)
+}
+
+fn check_lexing(
+    source_code: &str,
+    path: Option<&Path>,
+    options: &CheckOptions,
+    cancellation: Option<&CancellationToken>,
+) -> Result<SourceReport, CheckError> {
+    let mut lexer = Lexer::new(source_code);
+
+    let mut report = SourceReport {
+        path: path.map(Path::to_path_buf),
+        ..Default::default()
+    };
+    let mut params = Vec::with_capacity(8);
+
+    if options.forbid_no_module_docstring {
+        report.diagnostics.extend(check_module_docstring(
+            lexing::has_module_docstring(source_code),
+            path,
+            source_code,
+            None,
+            options,
+        ));
+    }
+
+    while let Some(info) = get_next_function_info(
+        &mut lexer,
+        &mut params,
+        options.skip_args_and_kwargs,
+        &options.ignore_params,
+    ) {
+        if cancellation.is_some_and(CancellationToken::is_cancelled) {
+            return Err(CheckError::Cancelled);
+        }
+
+        report.definitions += 1;
+        report.check_function(&info, options);
+    }
+
+    report.diagnostics = with_path(options.rules.apply(report.diagnostics), path);
+
+    Ok(report)
+}
+
+/// Checks if a file has a module docstring, given whether it starts with one (or `None`
+/// if it is empty) and its tree, if it was parsed with tree-sitter.
+fn check_module_docstring(
+    has_docstring: Option<bool>,
+    path: Option<&Path>,
+    source_code: &str,
+    tree: Option<&Tree>,
+    options: &CheckOptions,
+) -> Option<Diagnostic> {
+    let is_exempt = match has_docstring {
+        Some(has_docstring) => has_docstring,
+        None => options.succeed_if_empty_module,
+    };
+
+    if is_exempt
+        || (options.skip_trivial_init
+            && path.is_some_and(|path| is_trivial_init(path, source_code, tree)))
+    {
+        return None;
+    }
+
+    Some(Diagnostic {
+        rule: Rule::MissingModuleDocstring,
+        path: None,
+        function: "<module>".to_string(),
+        row: Some(0),
+        column: None,
+        function_row: None,
+        message: "Module docstring missing".to_string(),
+        severity: Severity::Error,
+        spans: Spans::default(),
+        entries: None,
+        fix: None,
+    })
+}
+
+/// Checks if the source code respects the specified rules, logging the violations found.
+pub fn respects_rules(
+    parser: &mut Parser,
+    source_code: &str,
+    old_tree: Option<&Tree>,
+    path: Option<&Path>,
+    options: &CheckOptions,
+) -> Result<bool, CheckError> {
+    let diagnostics = check_rules(parser, source_code, old_tree, path, options)?;
+
+    diagnostics.iter().for_each(Diagnostic::log);
+
+    Ok(diagnostics.is_empty())
+}
+
+/// Checks if the source code respects the specified rules, logging the violations found.
+pub fn respects_rules_through_lexing(
+    source_code: &str,
+    path: Option<&Path>,
+    options: &CheckOptions,
+) -> Result<bool, CheckError> {
+    let diagnostics = check_rules_through_lexing(source_code, path, options)?;
+
+    diagnostics.iter().for_each(Diagnostic::log);
+
+    Ok(diagnostics.is_empty())
+}
+
+fn with_path(mut diagnostics: Vec<Diagnostic>, path: Option<&Path>) -> Vec<Diagnostic> {
+    if let Some(path) = path {
+        for diagnostic in &mut diagnostics {
+            diagnostic.path = Some(path.to_path_buf());
+        }
+    }
+
+    diagnostics
+}
+
+/// Creates a diagnostic pointing to the function.
+fn diagnostic(info: &FunctionInfo, rule: Rule, message: String) -> Diagnostic {
+    Diagnostic {
+        rule,
+        path: None,
+        function: info.function_name.to_string(),
+        row: info.row,
+        column: info.column,
+        function_row: info.row,
+        message,
+        severity: Severity::Error,
+        entries: None,
+        spans: info.spans.clone(),
+        fix: None,
+    }
+}
+
+/// Parses the args of a docstring according to a specific style.
+fn parse_docstring_args(
+    docstring: &str,
+    docstyle: DocstringStyle,
+    break_on_empty_line: bool,
+    skip_args_and_kwargs: bool,
+) -> Option<Vec<(&str, Option<&str>)>> {
+    match docstyle {
+        DocstringStyle::Google => {
+            parse_google_docstring(docstring, break_on_empty_line, skip_args_and_kwargs)
+        }
+        DocstringStyle::Numpy => {
+            parse_numpy_docstring(docstring, break_on_empty_line, skip_args_and_kwargs)
+        }
+        DocstringStyle::AutoDetect => {
+            parse_google_docstring(docstring, break_on_empty_line, skip_args_and_kwargs).or(
+                parse_numpy_docstring(docstring, break_on_empty_line, skip_args_and_kwargs),
+            )
+        }
+    }
+}
+
+/// Describes where a function is for the logs, as in "src/module.py:3 `f`".
+pub(crate) fn describe_function(path: Option<&Path>, info: &FunctionInfo) -> String {
+    let path = path.map_or(String::new(), |path| path.to_string_lossy().to_string());
+
+    match info.row {
+        Some(row) => format!("{path}:{} `{}`", row + 1, info.name),
+        None => format!("{path} `{}`", info.name),
+    }
+}
+
+/// Checks a function against the enabled rules, leaving out the diagnostics suppressed
+/// by its comments.
+pub(crate) fn check_function_info(info: &FunctionInfo, options: &CheckOptions) -> Vec<Diagnostic> {
+    let mut diagnostics = function_diagnostics(info, options);
+    diagnostics.retain(|diagnostic| !info.is_suppressed(diagnostic.rule));
+    diagnostics
+}
+
+fn function_diagnostics(info: &FunctionInfo, options: &CheckOptions) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if skip_reason(info, options).is_some() {
+        return diagnostics;
+    }
+
+    if let Some((types, params)) = info.type_comment_mismatch {
+        diagnostics.push(diagnostic(
+            info,
+            Rule::TypeCommentMismatch,
+            format!("Type comment has {types} types, but the signature has {params} params"),
+        ));
+    }
+
+    if options.require_full_typing {
+        let unannotated = info.params.iter().filter(|param| {
+            param.typ.is_none() && !(options.skip_args_and_kwargs && param.name.starts_with('*'))
+        });
+
+        diagnostics.extend(unannotated.map(|param| {
+            diagnostic(
+                info,
+                Rule::MissingAnnotation,
+                format!("Parameter `{}` has no type annotation", param.name),
+            )
+        }));
+    }
+
+    let Some(docstring) = info.docstring else {
+        let class_docstring = info.class_docstring.filter(|&class_docstring| {
+            parse_docstring_args(
+                class_docstring,
+                options.docstyle,
+                options.break_on_empty_line,
+                options.skip_args_and_kwargs,
+            )
+            .is_some()
+        });
+
+        if let Some(class_docstring) = class_docstring {
+            check_docstring_args(info, class_docstring, options, &mut diagnostics);
+        } else if !options.succeed_if_no_docstring && !is_skipped_stub(info, options) {
+            diagnostics.push(diagnostic(
+                info,
+                Rule::MissingDocstring,
+                "Docstring missing".to_string(),
+            ));
+        }
+
+        return diagnostics;
+    };
+
+    check_docstring_args(info, docstring, options, &mut diagnostics);
+
+    if options.check_examples_style {
+        check_examples(info, docstring, &mut diagnostics);
+    }
+
+    if options.forbid_missing_returns {
+        check_returns(info, docstring, options, &mut diagnostics);
+    }
+
+    if options.check_return_type {
+        check_return_type(info, docstring, options, &mut diagnostics);
+    }
+
+    if options.check_raises {
+        check_raises(info, docstring, options, &mut diagnostics);
+    }
+
+    if options.forbid_missing_yields {
+        check_yields(info, docstring, options, &mut diagnostics);
+    }
+
+    if options.check_yield_type {
+        check_yield_type(info, docstring, options, &mut diagnostics);
+    }
+
+    if options.forbid_placeholders {
+        check_placeholders(info, docstring, options, &mut diagnostics);
+    }
+
+    if options.forbid_empty_descriptions {
+        check_empty_descriptions(info, docstring, options, &mut diagnostics);
+    }
+
+    if options.check_default_mentions {
+        check_default_mentions(info, docstring, options, &mut diagnostics);
+    }
+
+    if options.check_optional_consistency {
+        check_optional_consistency(info, docstring, options, &mut diagnostics);
+    }
+
+    if options.check_section_order {
+        check_section_order(info, docstring, options, &mut diagnostics);
+    }
+
+    if options.forbid_missing_summary && !has_summary(docstring) {
+        diagnostics.push(diagnostic(
+            info,
+            Rule::MissingSummary,
+            "Docstring has no summary".to_string(),
+        ));
+    }
+
+    diagnostics
+}
+
+/// Checks if the descriptions in the docstring of the parameters with default values
+/// mention them. Parameters that are not documented are left to the args rules.
+fn check_default_mentions(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let sections = parse_docstring_sections(docstring, options.docstyle);
+
+    let args: Vec<_> = sections
+        .iter()
+        .filter(|section| section.kind == SectionKind::Args)
+        .flat_map(|section| &section.entries)
+        .collect();
+
+    for param in info.params.iter().filter(|param| param.has_default) {
+        let Some(entry) = args.iter().find(|entry| entry.name == Some(param.name)) else {
+            continue;
+        };
+
+        if !options
+            .default_mention_pattern
+            .is_match(&entry.description.join(" "))
+        {
+            diagnostics.push(diagnostic(
+                info,
+                Rule::MissingDefaultMention,
+                format!(
+                    "Default value of `{}` is not mentioned in its description",
+                    param.name
+                ),
+            ));
+        }
+    }
+}
+
+/// Checks if the sections of a docstring that are in the configured order follow it.
+fn check_section_order(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let position = |heading: &str| {
+        options
+            .section_order
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(heading))
+    };
+
+    let mut previous: Option<(usize, &str)> = None;
+
+    for (_, heading) in section_headings(docstring) {
+        let Some(current) = position(heading) else {
+            continue;
+        };
+
+        match previous {
+            Some((latest, latest_heading)) if current < latest => {
+                diagnostics.push(diagnostic(
+                    info,
+                    Rule::SectionOrder,
+                    format!("Section `{heading}` should come before section `{latest_heading}`"),
+                ));
+            }
+            _ => previous = Some((current, heading)),
+        }
+    }
+}
+
+/// Checks if the args documented as optional are the ones with default values. Only the
+/// args with a type in the docstring are considered, since the marker is part of it.
+fn check_optional_consistency(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let sections = parse_docstring_sections(docstring, options.docstyle);
+
+    let args: Vec<_> = sections
+        .iter()
+        .filter(|section| section.kind == SectionKind::Args)
+        .flat_map(|section| &section.entries)
+        .filter(|entry| entry.typ.is_some())
+        .collect();
+
+    for param in info.params {
+        let Some(entry) = args.iter().find(|entry| entry.name == Some(param.name)) else {
+            continue;
+        };
+
+        let message = match (entry.is_optional(), param.has_default) {
+            (true, false) => format!(
+                "Arg `{}` is documented as optional, but has no default value",
+                param.name
+            ),
+            (false, true) => format!(
+                "Arg `{}` has a default value, but is not documented as optional",
+                param.name
+            ),
+            _ => continue,
+        };
+
+        diagnostics.push(diagnostic(info, Rule::OptionalMismatch, message));
+    }
+}
+
+/// Checks if every arg in the docstring has a description, which may start in a
+/// continuation line.
+fn check_empty_descriptions(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let sections = parse_docstring_sections(docstring, options.docstyle);
+
+    let args = sections
+        .iter()
+        .filter(|section| section.kind == SectionKind::Args)
+        .flat_map(|section| &section.entries);
+
+    for entry in args {
+        let Some(name) = entry.name else {
+            continue;
+        };
+
+        if options.skip_args_and_kwargs && name.starts_with('*') {
+            continue;
+        }
+
+        if entry.description.iter().all(|line| line.trim().is_empty()) {
+            diagnostics.push(diagnostic(
+                info,
+                Rule::EmptyDescription,
+                format!("Arg `{name}` has an empty description"),
+            ));
+        }
+    }
+}
+
+/// Checks if the description of a docstring, or the type or description of any of the
+/// entries of its sections, contains a placeholder.
+fn check_placeholders(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let find_placeholder = |text: &str| {
+        options
+            .placeholders
+            .iter()
+            .find(|placeholder| !placeholder.is_empty() && text.contains(placeholder.as_str()))
+    };
+
+    let mut report = |placeholder: &str, location: String| {
+        diagnostics.push(diagnostic(
+            info,
+            Rule::PlaceholderText,
+            format!("Placeholder `{placeholder}` found in {location}"),
+        ));
+    };
+
+    let sections = parse_docstring_sections(docstring, options.docstyle);
+
+    let description = &docstring[..sections
+        .first()
+        .map_or(docstring.len(), |section| section.range.start)];
+
+    if let Some(placeholder) = find_placeholder(description) {
+        report(placeholder, "the docstring description".to_string());
+    }
+
+    for section in &sections {
+        let heading = section.kind.google_heading().trim_end_matches(':');
+
+        for entry in &section.entries {
+            let owner = match entry.name {
+                Some(name) => format!("`{name}`"),
+                None => format!("the {heading} section"),
+            };
+
+            if let Some(placeholder) = entry.typ.and_then(find_placeholder) {
+                report(placeholder, format!("the type of {owner}"));
+            }
+
+            if let Some(placeholder) = entry
+                .description
+                .iter()
+                .find_map(|&line| find_placeholder(line))
+            {
+                report(placeholder, format!("the description of {owner}"));
+            }
+        }
+    }
+}
+
+/// Checks if a function is exempt from all the rules, returning why.
+///
+/// `@overload` stubs are always exempt, since only the implementation that follows them
+/// is expected to be documented.
+fn skip_reason(info: &FunctionInfo, options: &CheckOptions) -> Option<&'static str> {
+    let is_decorated_with = |name: &str| {
+        info.decorators.iter().any(|&decorator| {
+            decorator
+                .strip_suffix(name)
+                .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('.'))
+        })
+    };
+
+    if is_decorated_with("overload") {
+        return Some("`@overload` stub");
+    }
+
+    if options
+        .skip_decorated
+        .iter()
+        .any(|name| is_decorated_with(name))
+    {
+        return Some("skipped decorator");
+    }
+
+    if options
+        .exclude_functions
+        .iter()
+        .any(|pattern| pattern.is_match(info.name))
+    {
+        return Some("excluded name");
+    }
+
+    let is_selected = match options.only {
+        FunctionSelection::Functions => !info.is_method,
+        FunctionSelection::Methods => info.is_method,
+        FunctionSelection::All => true,
+    };
+
+    if !is_selected {
+        return Some("not selected by `--only`");
+    }
+
+    let is_dunder = info.name.len() > 4 && info.name.starts_with("__") && info.name.ends_with("__");
+
+    let is_private = info.name.starts_with('_') && !is_dunder;
+
+    if options.ignore_private && is_private {
+        Some("private")
+    } else if options.ignore_dunder && is_dunder {
+        Some("dunder")
+    } else {
+        None
+    }
+}
+
+/// Whether the function is a stub that is not required to be documented.
+fn is_skipped_stub(info: &FunctionInfo, options: &CheckOptions) -> bool {
+    options.skip_stub_bodies && info.is_stub
+}
+
+/// Parses the sections of a docstring according to the style.
+///
+/// If the style is to be auto-detected, tries google style first and then numpy style.
+fn parse_docstring_sections(docstring: &str, style: DocstringStyle) -> Vec<Section<'_>> {
+    match style {
+        DocstringStyle::Google => parse_google_sections(docstring),
+        DocstringStyle::Numpy => parse_numpy_sections(docstring),
+        DocstringStyle::AutoDetect => {
+            let sections = parse_google_sections(docstring);
+
+            if sections.is_empty() {
+                parse_numpy_sections(docstring)
+            } else {
+                sections
+            }
+        }
+    }
+}
+
+/// Checks if a function that returns something other than `None` documents it in a
+/// "Returns" section.
+fn check_returns(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(return_type) = info.return_type.filter(|&typ| typ != "None") else {
+        return;
+    };
+
+    if info.name == "__init__" {
+        return;
+    }
+
+    let has_returns = parse_docstring_sections(docstring, options.docstyle)
+        .iter()
+        .any(|section| section.kind == SectionKind::Returns);
+
+    if !has_returns {
+        diagnostics.push(diagnostic(
+            info,
+            Rule::MissingReturns,
+            format!("Return annotation `{return_type}` is not documented in a Returns section"),
+        ));
+    }
+}
+
+/// Checks if the doctest statements in the "Examples" section of a docstring are
+/// syntactically valid Python.
+fn check_examples(info: &FunctionInfo, docstring: &str, diagnostics: &mut Vec<Diagnostic>) {
+    let statements = extract_doctest_statements(docstring);
+
+    if statements.is_empty() {
+        return;
+    }
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_python::language())
+        .expect("should be able to load Python grammar");
+
+    for (line, statement) in statements {
+        if statement.contains("doctest: +SKIP") {
+            continue;
+        }
+
+        let has_error = parser
+            .parse(&statement, None)
+            .is_none_or(|tree| tree.root_node().has_error());
+
+        if has_error {
+            diagnostics.push(diagnostic(
+                info,
+                Rule::InvalidExample,
+                format!(
+                    "Example at line {} of the docstring is not valid Python",
+                    line + 1
+                ),
+            ));
+        }
+    }
+}
+
+/// Checks if the attributes documented in a class docstring match the annotated ones of
+/// the class, with the same semantics as the args of a function.
+fn check_class_info(info: &ClassInfo, options: &CheckOptions) -> Vec<Diagnostic> {
+    let Some(docstring) = info.docstring else {
+        return Vec::new();
+    };
+
+    let sections = parse_docstring_sections(docstring, options.docstyle);
+
+    let Some(attributes_from_docstring) = parse_attributes_section(&sections) else {
+        return Vec::new();
+    };
+
+    if info.is_suppressed(Rule::AttributesMismatch) {
+        tracing::debug!(
+            "`{}`: suppressed by a `# pystaleds: ignore` comment",
+            info.name
+        );
+        return Vec::new();
+    }
+
+    let differences = entry_differences(
+        &attributes_from_docstring,
+        &info.attributes,
+        options.succeed_if_docstrings_are_not_typed,
+        false,
+    );
+
+    let entries = EntriesComparison::new(&info.attributes, &attributes_from_docstring);
+
+    let attributes_heading = section_headings(docstring)
+        .into_iter()
+        .find(|&(_, name)| name == "Attributes")
+        .map(|(offset, _)| offset);
+
+    // As with the args, each difference is reported on its own, pointed at the documented
+    // entry or at the heading of the attributes.
+    differences
+        .into_iter()
+        .map(|difference| {
+            let offset = match difference {
+                EntryDifference::Extra(name)
+                | EntryDifference::Case {
+                    documented: name, ..
+                }
+                | EntryDifference::Type { name, .. } => offset_within(docstring, name),
+                EntryDifference::Missing(_) | EntryDifference::Order => attributes_heading,
+            };
+
+            let (row, column) = offset
+                .zip(info.docstring_start)
+                .and_then(|(offset, start)| docstring_position(docstring, start, offset))
+                .unwrap_or((info.row, info.column));
+
+            Diagnostic {
+                rule: Rule::AttributesMismatch,
+                path: None,
+                function: info.name.to_string(),
+                row: Some(row),
+                column: Some(column),
+                function_row: Some(info.row),
+                message: difference.message("class"),
+                severity: Severity::Error,
+                spans: Spans::default(),
+                entries: Some(entries.clone()),
+                fix: None,
+            }
+        })
+        .collect()
+}
+
+/// Checks if a docstring has a "Yields" section if and only if the function is a
+/// generator.
+fn check_yields(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(is_generator) = info.is_generator else {
+        return;
+    };
+
+    let has_yields = parse_docstring_sections(docstring, options.docstyle)
+        .iter()
+        .any(|section| section.kind == SectionKind::Yields);
+
+    let message = match (is_generator, has_yields) {
+        (true, false) => "Function is a generator, but its docstring has no Yields section",
+        (false, true) => "Docstring has a Yields section, but the function is not a generator",
+        _ => return,
+    };
+
+    diagnostics.push(diagnostic(info, Rule::MissingYields, message.to_string()));
+}
+
+/// Extracts the type yielded by a generator from its return annotation, i.e. the first
+/// type argument of annotations such as `Iterator[Foo]` or `Generator[Foo, None, None]`.
+fn yield_type(annotation: &str) -> Option<&str> {
+    let (base, arguments) = annotation.trim().split_once('[')?;
+    let arguments = arguments.strip_suffix(']')?;
+
+    let base = base.trim().rsplit('.').next()?;
+
+    if !matches!(
+        base,
+        "Iterator"
+            | "Iterable"
+            | "Generator"
+            | "AsyncIterator"
+            | "AsyncIterable"
+            | "AsyncGenerator"
+    ) {
+        return None;
+    }
+
+    let mut depth = 0;
+
+    for (i, c) in arguments.char_indices() {
+        match c {
+            '[' | '(' | '{' => depth += 1,
+            ']' | ')' | '}' => depth -= 1,
+            ',' if depth == 0 => return Some(arguments[..i].trim()),
+            _ => (),
+        }
+    }
+
+    Some(arguments.trim())
+}
+
+/// Checks if the type documented in the "Yields" section of a docstring matches the one
+/// in the return annotation of the function.
+fn check_yield_type(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(yielded_type) = info.return_type.and_then(yield_type) else {
+        return;
+    };
+
+    let sections = parse_docstring_sections(docstring, options.docstyle);
+
+    let documented_type = sections
+        .iter()
+        .find(|section| section.kind == SectionKind::Yields)
+        .and_then(|section| section.entries.first())
+        .and_then(|entry| entry.typ);
+
+    let Some(documented_type) = documented_type else {
+        return;
+    };
+
+    if !types_match(yielded_type, documented_type) {
+        diagnostics.push(diagnostic(
+            info,
+            Rule::YieldTypeMismatch,
+            format!(
+                "Yield type from function: `{yielded_type}`. Yield type from docstring: `{documented_type}`"
+            ),
+        ));
+    }
+}
+
+/// Checks if the exceptions documented in the "Raises" section of a docstring match the
+/// ones raised in the function, comparing only the last component of dotted names.
+fn check_raises(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(raised) = &info.raises else {
+        return;
+    };
+
+    let sections = parse_docstring_sections(docstring, options.docstyle);
+    let documented = parse_raises_section(&sections).unwrap_or_default();
+
+    let last_component = |name: &str| name.rsplit('.').next().unwrap_or(name).to_string();
+
+    let raised_names: Vec<String> = raised.iter().map(|name| last_component(name)).collect();
+    let documented_names: Vec<String> =
+        documented.iter().map(|name| last_component(name)).collect();
+
+    for (name, short_name) in raised.iter().zip(&raised_names) {
+        if !documented_names.contains(short_name) {
+            diagnostics.push(diagnostic(
+                info,
+                Rule::RaisesMismatch,
+                format!("Exception `{name}` is raised but not documented in a Raises section"),
+            ));
+        }
+    }
+
+    for (name, short_name) in documented.iter().zip(&documented_names) {
+        if !raised_names.contains(short_name) {
+            diagnostics.push(diagnostic(
+                info,
+                Rule::RaisesMismatch,
+                format!("Exception `{name}` is documented in the Raises section but never raised"),
+            ));
+        }
+    }
+}
+
+/// Checks if the args documented in a docstring match the ones of the function.
+fn check_docstring_args(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let parse = |docstring| {
+        parse_docstring_args(
+            docstring,
+            options.docstyle,
+            options.break_on_empty_line,
+            options.skip_args_and_kwargs,
+        )
+    };
+
+    // The args of an `__init__` may be documented in the docstring of its class instead.
+    let args_from_docstring = parse(docstring).or_else(|| info.class_docstring.and_then(parse));
+
+    tracing::trace!(
+        "`{}`: signature params: {:?}, docstring params: {:?}",
+        info.name,
+        info.params
+            .iter()
+            .map(|param| (param.name, param.typ))
+            .collect::<Vec<_>>(),
+        args_from_docstring,
+    );
+
+    let Some(mut args_from_docstring) = args_from_docstring else {
+        if options.forbid_style_mismatch {
+            let other_style = match options.docstyle {
+                DocstringStyle::Google => Some(DocstringStyle::Numpy),
+                DocstringStyle::Numpy => Some(DocstringStyle::Google),
+                DocstringStyle::AutoDetect => None,
+            };
+
+            let detected_style = other_style.filter(|&style| {
+                parse_docstring_args(
+                    docstring,
+                    style,
+                    options.break_on_empty_line,
+                    options.skip_args_and_kwargs,
+                )
+                .is_some()
+            });
+
+            if let Some(detected_style) = detected_style {
+                diagnostics.push(diagnostic(
+                    info,
+                    Rule::StyleMismatch,
+                    format!(
+                        "Docstring is written in {} style, but {} style is expected",
+                        detected_style, options.docstyle,
+                    ),
+                ));
+
+                return;
+            }
+        }
+
+        if !options.succeed_if_no_args_in_docstring && !is_skipped_stub(info, options) {
+            diagnostics.push(diagnostic(
+                info,
+                Rule::MissingArgs,
+                "Args missing from docstring".to_string(),
+            ));
+        }
+
+        return;
+    };
+
+    // Left out before anything else, so that they are ignored even if documented twice.
+    args_from_docstring
+        .retain(|(name, _)| !options.ignore_params.iter().any(|ignored| ignored == name));
+
+    let mut duplicates: Vec<&str> = Vec::new();
+
+    for (i, (name, _)) in args_from_docstring.iter().enumerate() {
+        let is_repeated = args_from_docstring[..i]
+            .iter()
+            .any(|(other, _)| other == name);
+
+        if is_repeated && !duplicates.contains(name) {
+            duplicates.push(name);
+        }
+    }
+
+    if !duplicates.is_empty() {
+        diagnostics.extend(duplicates.into_iter().map(|name| {
+            diagnostic(
+                info,
+                Rule::DuplicateArg,
+                format!("Parameter `{name}` documented multiple times"),
+            )
+        }));
+
+        return;
+    }
+
+    let documented_receivers: Vec<&str> = args_from_docstring
+        .iter()
+        .map(|&(name, _)| name)
+        .filter(|&name| {
+            matches!(name, "self" | "cls") && !info.params.iter().any(|param| param.name == name)
+        })
+        .collect();
+
+    if !documented_receivers.is_empty() {
+        if options.allow_documented_self {
+            args_from_docstring.retain(|(name, _)| !documented_receivers.contains(name));
+        } else {
+            diagnostics.extend(documented_receivers.into_iter().map(|name| {
+                diagnostic(
+                    info,
+                    Rule::DocumentedSelf,
+                    format!("Do not document `{name}`"),
+                )
+            }));
+
+            return;
+        }
+    }
+
+    if options.require_full_typing {
+        let untyped = args_from_docstring.iter().filter(|(name, typ)| {
+            typ.is_none() && !(options.skip_args_and_kwargs && name.starts_with('*'))
+        });
+
+        diagnostics.extend(untyped.map(|(name, _)| {
+            diagnostic(
+                info,
+                Rule::UntypedDocstringArg,
+                format!("Parameter `{name}` has no type in the docstring"),
+            )
+        }));
+    }
+
+    // The keys accepted by `**kwargs` may be documented as if they were args.
+    if options.allow_kwargs_docs && info.has_var_kwargs {
+        args_from_docstring.retain(|&(name, _)| info.params.iter().any(|param| param.name == name));
+    }
+
+    let args_from_function: Vec<_> = info
+        .params
+        .iter()
+        .map(|param| (param.name, param.typ))
+        .collect();
+
+    // The params with defaults that are not documented are left out of the comparison,
+    // which still validates the order and types of the documented ones.
+    let expected_args: Vec<_> = if options.allow_omitting_defaulted_args {
+        info.params
+            .iter()
+            .filter(|param| {
+                !param.has_default
+                    || args_from_docstring
+                        .iter()
+                        .any(|&(name, _)| name.eq_ignore_ascii_case(param.name))
+            })
+            .map(|param| (param.name, param.typ))
+            .collect()
+    } else {
+        args_from_function.clone()
+    };
+
+    let differences = entry_differences(
+        &args_from_docstring,
+        &expected_args,
+        options.succeed_if_docstrings_are_not_typed,
+        options.allow_out_of_order_args,
+    );
+
+    let entries = EntriesComparison::new(&args_from_function, &args_from_docstring);
+
+    let args_heading = info.docstring.and_then(|docstring| {
+        section_headings(docstring)
+            .into_iter()
+            .find(|&(_, name)| name == "Args")
+            .map(|(offset, _)| offset)
+    });
+
+    // Each difference is reported on its own, with names differing only by case (e.g.,
+    // after a rename) under a rule of their own.
+    for difference in differences {
+        let message = difference.message("signature");
+
+        // The differences are pointed at the documented entry, or at the heading of the
+        // args if there is no such entry.
+        let offset = match difference {
+            EntryDifference::Extra(name)
+            | EntryDifference::Case {
+                documented: name, ..
+            }
+            | EntryDifference::Type { name, .. } => info
+                .docstring
+                .and_then(|docstring| offset_within(docstring, name)),
+            EntryDifference::Missing(_) | EntryDifference::Order => args_heading,
+        };
+
+        let rule = match difference {
+            EntryDifference::Case { .. } if options.ignore_param_case => continue,
+            EntryDifference::Case { .. } => Rule::ParamCaseMismatch,
+            EntryDifference::Missing(_) => Rule::MissingArg,
+            EntryDifference::Extra(_) => Rule::ExtraArg,
+            EntryDifference::Type { .. } => Rule::ArgTypeMismatch,
+            EntryDifference::Order => Rule::ArgsOrder,
+        };
+
+        let mut diagnostic = Diagnostic {
+            entries: (rule != Rule::ParamCaseMismatch).then(|| entries.clone()),
+            ..diagnostic(info, rule, message)
+        };
+
+        let position = offset
+            .zip(info.docstring.zip(info.docstring_start))
+            .and_then(|(offset, (docstring, start))| docstring_position(docstring, start, offset));
+
+        if let Some((row, column)) = position {
+            diagnostic.row = Some(row);
+            diagnostic.column = Some(column);
+        }
+
+        diagnostics.push(diagnostic);
+    }
+}
+
+/// Determines the position (0-based row and column) in the file of an offset within a
+/// docstring, given the position in which the docstring starts.
+fn docstring_position(
+    docstring: &str,
+    (row, column): (usize, usize),
+    offset: usize,
+) -> Option<(usize, usize)> {
+    let before = docstring.get(..offset)?;
+
+    match before.rfind('\n') {
+        Some(newline) => Some((row + before.matches('\n').count(), offset - newline - 1)),
+        None => Some((row, column + offset)),
+    }
+}
+
+/// A difference between the entries documented in a docstring (such as args) and the
+/// ones from the code.
+#[derive(Debug, PartialEq, Eq)]
+enum EntryDifference<'a> {
+    /// An entry of the code that is not documented.
+    Missing(&'a str),
+    /// A documented entry that is not in the code.
+    Extra(&'a str),
+    /// A documented entry whose name differs from the one in the code only by case.
+    Case {
+        documented: &'a str,
+        expected: &'a str,
+    },
+    /// An entry of the code documented with another type.
+    Type {
+        name: &'a str,
+        expected: Option<&'a str>,
+        documented: Option<&'a str>,
+    },
+    /// The entries are not documented in the order of the code.
+    Order,
+}
+
+impl EntryDifference<'_> {
+    /// Describes the difference, referring to the entries of the code as being from the
+    /// `source` (e.g., "signature").
+    fn message(&self, source: &str) -> String {
+        match self {
+            EntryDifference::Missing(name) => format!("`{name}` missing from docstring"),
+            EntryDifference::Extra(name) => {
+                format!("`{name}` documented, but not in the {source}")
+            }
+            EntryDifference::Case {
+                documented,
+                expected,
+            } => format!("Docstring documents `{documented}`, {source} has `{expected}`"),
+            EntryDifference::Type {
+                name,
+                expected,
+                documented,
+            } => {
+                let describe = |typ: Option<&str>| match typ {
+                    Some(typ) => format!("says `{typ}`"),
+                    None => "has no type".to_string(),
+                };
+
+                format!(
+                    "`{name}`: {source} {}, docstring {}",
+                    describe(*expected),
+                    describe(*documented),
+                )
+            }
+            EntryDifference::Order => {
+                format!("Docstring does not follow the order of the {source}")
+            }
+        }
+    }
+}
+
+/// Compares the entries documented in a docstring (such as args) with the ones from the
+/// code, in order unless `in_any_order`. If `allow_untyped`, the types are only compared
+/// when both are present.
+///
+/// Each entry of the code can only be documented once, so repeated entries are extra.
+fn entry_differences<'a>(
+    from_docstring: &[(&'a str, Option<&'a str>)],
+    from_code: &[(&'a str, Option<&'a str>)],
+    allow_untyped: bool,
+    in_any_order: bool,
+) -> Vec<EntryDifference<'a>> {
+    let mut differences = Vec::new();
+
+    let mut is_documented = vec![false; from_code.len()];
+    let mut documented_order = Vec::new();
+
+    for &(name, typ) in from_docstring {
+        let undocumented = |i: &usize| !is_documented[*i];
+
+        let exact = (0..from_code.len())
+            .filter(undocumented)
+            .find(|&i| from_code[i].0 == name);
+
+        let Some(i) = exact.or_else(|| {
+            (0..from_code.len())
+                .filter(undocumented)
+                .find(|&i| from_code[i].0.eq_ignore_ascii_case(name))
+        }) else {
+            differences.push(EntryDifference::Extra(name));
+            continue;
+        };
+
+        is_documented[i] = true;
+        documented_order.push(i);
+
+        let (expected, expected_type) = from_code[i];
+
+        if exact.is_none() {
+            differences.push(EntryDifference::Case {
+                documented: name,
+                expected,
+            });
+        }
+
+        let is_type_matching = match (typ, expected_type) {
+            (Some(typ), Some(expected_type)) => types_match(typ, expected_type),
+            (None, None) => true,
+            (_, _) => allow_untyped,
+        };
+
+        if !is_type_matching {
+            differences.push(EntryDifference::Type {
+                name,
+                expected: expected_type,
+                documented: typ,
+            });
+        }
+    }
+
+    differences.extend(
+        from_code
+            .iter()
+            .zip(is_documented)
+            .filter(|(_, is_documented)| !is_documented)
+            .map(|(&(name, _), _)| EntryDifference::Missing(name)),
+    );
+
+    if !in_any_order && documented_order.windows(2).any(|pair| pair[0] > pair[1]) {
+        differences.push(EntryDifference::Order);
+    }
+
+    differences
+}
+
+/// Checks if two type annotations are the same, disregarding whitespace.
+fn types_match(type1: &str, type2: &str) -> bool {
+    let normalize = |typ: &str| {
+        typ.chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+    };
+
+    normalize(type1) == normalize(type2)
+}
+
+/// Checks if the type documented in the "Returns" section of a docstring matches the
+/// return annotation of the function.
+fn check_return_type(
+    info: &FunctionInfo,
+    docstring: &str,
+    options: &CheckOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(return_type) = info.return_type else {
+        return;
+    };
+
+    let sections = parse_docstring_sections(docstring, options.docstyle);
+
+    let documented_type = sections
+        .iter()
+        .find(|section| section.kind == SectionKind::Returns)
+        .and_then(|section| section.entries.first())
+        .and_then(|entry| entry.typ);
+
+    let Some(documented_type) = documented_type else {
+        return;
+    };
+
+    if !types_match(return_type, documented_type) {
+        diagnostics.push(diagnostic(
+            info,
+            Rule::ReturnTypeMismatch,
+            format!(
+                "Return type from function: `{return_type}`. Return type from docstring: `{documented_type}`"
+            ),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+
+    use crate::ast_parsing::{FunctionLocation, Param};
+
+    use super::*;
+
+    fn get_parser() -> Parser {
+        let mut parser = Parser::new();
+
+        parser
+            .set_language(&tree_sitter_python::language())
+            .expect("should be able to load Python grammar");
+
+        parser
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_success_no_docstring() {
+        let function_info = FunctionInfo {
+            params: &[
+                Param::new("x", Some("int"), false),
+                Param::new("y", Some("str"), false),
+            ],
+            docstring: None,
+            function_name: FunctionLocation::Name(""),
+            ..Default::default()
+        };
+
+        assert!(check_function_info(&function_info, &CheckOptions::default(),).is_empty());
+
+        assert!(!check_function_info(
+            &function_info,
+            &CheckOptions {
+                succeed_if_no_docstring: false,
+                ..Default::default()
+            },
+        )
+        .is_empty());
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_out_of_order() {
+        let function_info = FunctionInfo {
+            params: &[
+                Param::new("x", Some("int"), false),
+                Param::new("y", Some("str"), false),
+            ],
+            docstring: Some(
+                r#"
+                """
+                Hello!
+
+                Args:
+                    y: Nope.
+                    x: Hehehe.
+                """"#,
+            ),
+            function_name: FunctionLocation::Name(""),
+            ..Default::default()
+        };
+
+        assert!(!check_function_info(
+            &function_info,
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .is_empty());
+
+        let function_info = FunctionInfo {
+            params: &[
+                Param::new("x", Some("int"), false),
+                Param::new("y", Some("str"), false),
+            ],
+            docstring: Some(
+                r#"
+                """
+                Hello!
+
+                Args:
+                    x: Hehehe.
+                    y: Nope.
+                """"#,
+            ),
+            function_name: FunctionLocation::Name(""),
+            ..Default::default()
+        };
+
+        assert!(check_function_info(
+            &function_info,
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .is_empty());
+
+        let function_info = FunctionInfo {
+            params: &[
+                Param::new("x", Some("int"), false),
+                Param::new("y", Some("str"), false),
+            ],
+            docstring: Some(
+                r#"
+                """
+                Hello!
+
+                Parameters
+                ----------
+                y
+                    Nope
+                x
+                    Hehehe
+                """"#,
+            ),
+            function_name: FunctionLocation::Name(""),
+            ..Default::default()
+        };
+
+        assert!(!check_function_info(
+            &function_info,
+            &CheckOptions {
+                docstyle: DocstringStyle::Numpy,
+                ..Default::default()
+            },
+        )
+        .is_empty());
+
+        let function_info = FunctionInfo {
+            params: &[
+                Param::new("x", Some("int"), false),
+                Param::new("y", Some("str"), false),
+            ],
+            docstring: Some(
+                r#"
+                """
+                Hello!
+
+                Parameters
+                ----------
+                x
+                    Hehehe
+                y
+                    Nope.
+
+                Returns
+                ------
+                ...
+                """"#,
+            ),
+            function_name: FunctionLocation::Name(""),
+            ..Default::default()
+        };
+
+        assert!(check_function_info(
+            &function_info,
+            &CheckOptions {
+                docstyle: DocstringStyle::Numpy,
+                ..Default::default()
+            },
+        )
+        .is_empty());
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_style_mismatch() {
+        let function_info = FunctionInfo {
+            params: &[
+                Param::new("x", Some("int"), false),
+                Param::new("y", Some("str"), false),
+            ],
+            docstring: Some(
+                r#"
+                """
+                Hello!
+
+                Parameters
+                ----------
+                x
+                    Hehehe
+                y
+                    Nope.
+                """"#,
+            ),
+            function_name: FunctionLocation::Name(""),
+            ..Default::default()
+        };
+
+        assert!(check_function_info(
+            &function_info,
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .is_empty());
+
+        let diagnostics = check_function_info(
+            &function_info,
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                forbid_style_mismatch: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, Rule::StyleMismatch);
+        assert_eq!(
+            diagnostics[0].message,
+            "Docstring is written in Numpy style, but Google style is expected"
+        );
+
+        assert!(check_function_info(
+            &function_info,
+            &CheckOptions {
+                docstyle: DocstringStyle::Numpy,
+                forbid_style_mismatch: true,
+                ..Default::default()
+            },
+        )
+        .is_empty());
+
+        assert!(check_function_info(
+            &function_info,
+            &CheckOptions {
+                docstyle: DocstringStyle::AutoDetect,
+                forbid_style_mismatch: true,
+                ..Default::default()
+            },
+        )
+        .is_empty());
+    }
+
+    #[test]
+    #[traced_test]
+    fn test_examples_style() {
+        let valid = FunctionInfo {
+            params: &[Param::new("x", None, false)],
+            docstring: Some(
+                r#""""Hello!
+
+                Args:
+                    x: Hehehe.
+
+                Examples:
+                    >>> f(
+                    ...     2,
+                    ... )
+                    2
+                """"#,
+            ),
+            function_name: FunctionLocation::Name("f"),
+            ..Default::default()
+        };
+
+        let options = CheckOptions {
+            break_on_empty_line: true,
+            check_examples_style: true,
+            ..Default::default()
+        };
+
+        assert!(check_function_info(&valid, &options).is_empty());
+
+        let broken = FunctionInfo {
+            params: &[Param::new("x", None, false)],
+            docstring: Some(
+                r#""""Hello!
+
+                Parameters
+                ----------
+                x
+                    Hehehe.
+
+                Examples
+                --------
+                >>> f(2
+                2
+                """"#,
+            ),
+            function_name: FunctionLocation::Name("f"),
+            ..Default::default()
+        };
+
+        assert!(check_function_info(
+            &broken,
+            &CheckOptions {
+                check_examples_style: false,
+                ..options.clone()
+            }
+        )
+        .is_empty());
+
+        let diagnostics = check_function_info(&broken, &options);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, Rule::InvalidExample);
+        assert_eq!(
+            diagnostics[0].message,
+            "Example at line 10 of the docstring is not valid Python"
+        );
+
+        let skipped = FunctionInfo {
+            params: &[Param::new("x", None, false)],
+            docstring: Some(
+                r#""""Hello!
+
+                Args:
+                    x: Hehehe.
+
+                Examples:
+                    >>> f(2  # doctest: +SKIP
+                """"#,
+            ),
+            function_name: FunctionLocation::Name("f"),
+            ..Default::default()
+        };
+
+        assert!(check_function_info(&skipped, &options).is_empty());
+    }
+
+    #[test]
+    fn test_missing_returns() {
+        let source_code = r#"def f(x) -> Dict[str, int]:
+    """Hello!
+
+    Args:
+        x: Hehehe.
+    """
+    return {}
+
+def g(x) -> int:
+    """Hello!
+
+    Parameters
+    ----------
+    x
+        Hehehe.
+
+    Returns
+    -------
+    int
+        Something.
+    """
+    return 1
+
+def h(x) -> None:
+    """Hello!
+
+    Args:
+        x: Hehehe.
+    """
+
+class A:
+    def __init__(self, x) -> "A":
+        """Hello!
+
+        Args:
+            x: Hehehe.
+        """
+"#;
+
+        let options = CheckOptions {
+            forbid_missing_returns: true,
+            ..Default::default()
+        };
+
+        let mut parser = get_parser();
+
+        let from_tree_sitter = check_rules(&mut parser, source_code, None, None, &options).unwrap();
+        let from_lexing = check_rules_through_lexing(source_code, None, &options).unwrap();
+
+        for diagnostics in [from_tree_sitter, from_lexing] {
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].rule, Rule::MissingReturns);
+            assert_eq!(
+                diagnostics[0].message,
+                "Return annotation `Dict[str, int]` is not documented in a Returns section"
+            );
+        }
+
+        assert!(
+            check_rules_through_lexing(source_code, None, &CheckOptions::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_return_type() {
+        let source_code = r#"def f(x) -> Dict[str,int]:
+    """Hello!
+
+    Args:
+        x: Hehehe.
+
+    Returns:
+        Dict[str, int]: Something.
+    """
+    return {}
+
+def g(x) -> int:
+    """Hello!
+
+    Parameters
+    ----------
+    x
+        Hehehe.
+
+    Returns
+    -------
+    str
+        Something.
+    """
+    return 1
+
+def h(x) -> int:
+    """Hello!
+
+    Args:
+        x: Hehehe.
+
+    Returns:
+        Something.
+    """
+    return 1
+"#;
+
+        let options = CheckOptions {
+            check_return_type: true,
+            ..Default::default()
+        };
+
+        let mut parser = get_parser();
+
+        let from_tree_sitter = check_rules(&mut parser, source_code, None, None, &options).unwrap();
+        let from_lexing = check_rules_through_lexing(source_code, None, &options).unwrap();
+
+        for diagnostics in [from_tree_sitter, from_lexing] {
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].rule, Rule::ReturnTypeMismatch);
+            assert_eq!(
+                diagnostics[0].message,
+                "Return type from function: `int`. Return type from docstring: `str`"
+            );
+        }
+
+        assert!(
+            check_rules_through_lexing(source_code, None, &CheckOptions::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_check_rules_errors() {
+        let source_code = "# pystaleds: skip-file\ndef f(x):\n    pass\n";
+        let options = CheckOptions::default();
+
+        assert!(matches!(
+            check_rules(&mut get_parser(), source_code, None, None, &options),
+            Err(CheckError::SkippedFile)
+        ));
+        assert!(matches!(
+            check_rules_through_lexing(source_code, None, &options),
+            Err(CheckError::SkippedFile)
+        ));
+
+        let options = CheckOptions {
+            check_raises: true,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            respects_rules_through_lexing("def f(x):\n    pass\n", None, &options),
+            Err(CheckError::RequiresTreeSitter(_))
+        ));
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let source_code = r#"def f(x):
+    """Hello!
+
+    Args:
+        y: Hehehe.
+    """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics =
+                check_source(source_code, None, checker, &CheckOptions::default(), None).unwrap();
+
+            assert_eq!(diagnostics.len(), 2);
+
+            let cancellation = CancellationToken::new();
+            cancellation.cancel();
+
+            let result = check_source(
+                source_code,
+                None,
+                checker,
+                &CheckOptions::default(),
+                Some(&cancellation),
+            );
+
+            assert!(matches!(result, Err(CheckError::Cancelled)));
+
+            let result = check_paths(
+                &[PathBuf::from("nonexistent.py")],
+                checker,
+                &CheckOptions::default(),
+                Some(&cancellation),
+            );
+
+            assert!(matches!(result, Err(CheckError::Cancelled)));
+        }
+    }
+
+    #[test]
+    fn test_cancellation_from_another_thread() {
+        let function = r#"def f(x: int, y: str):
+    """Hello!
+
+    Args:
+        x (int): Hehehe.
+        y (str): Nope.
+
+    Examples:
+        >>> f(1, "a")
+    """
+"#;
+
+        let source_code = function.repeat(200_000);
+
+        let options = CheckOptions {
+            check_examples_style: true,
+            ..Default::default()
+        };
+
+        let cancellation = CancellationToken::new();
+
+        let canceller = {
+            let cancellation = cancellation.clone();
+
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                cancellation.cancel();
+                std::time::Instant::now()
+            })
+        };
+
+        let result = check_source(
+            &source_code,
+            None,
+            CompliancyChecker::Lexer,
+            &options,
+            Some(&cancellation),
+        );
+        let returned_at = std::time::Instant::now();
+
+        let cancelled_at = canceller.join().unwrap();
+
+        assert!(matches!(result, Err(CheckError::Cancelled)));
+        assert!(returned_at.duration_since(cancelled_at) < std::time::Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_raises() {
+        let source_code = r#"def f(x):
+    """Hello!
+
+    Args:
+        x: Hehehe.
+
+    Raises:
+        ValueError: If x is off.
+        KeyError: If x is missing.
+    """
+    if x < 0:
+        raise ValueError("negative")
+
+    try:
+        return x[0]
+    except IndexError as e:
+        raise errors.EmptyError from e
+    except Exception as e:
+        raise e
+    except:
+        raise
+
+    def g():
+        raise TypeError
+
+def h(x):
+    """Hello!
+
+    Parameters
+    ----------
+    x
+        Hehehe.
+
+    Raises
+    ------
+    ValueError
+        If x is off.
+    """
+    raise ValueError
+"#;
+
+        let options = CheckOptions {
+            break_on_empty_line: true,
+            check_raises: true,
+            ..Default::default()
+        };
+
+        let diagnostics = check_source(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &options,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.rule == Rule::RaisesMismatch));
+        assert_eq!(
+            diagnostics[0].message,
+            "Exception `errors.EmptyError` is raised but not documented in a Raises section"
+        );
+        assert_eq!(
+            diagnostics[1].message,
+            "Exception `KeyError` is documented in the Raises section but never raised"
+        );
+
+        assert!(matches!(
+            check_source(source_code, None, CompliancyChecker::Lexer, &options, None),
+            Err(CheckError::RequiresTreeSitter(_))
+        ));
+    }
+
+    #[test]
+    fn test_yields() {
+        let source_code = r#"def f(x):
+    """Hello!
+
+    Args:
+        x: Hehehe.
+
+    Yields:
+        int: Something.
+    """
+    yield from range(x)
+
+def g(x):
+    """Hello!
+
+    Args:
+        x: Hehehe.
+    """
+    def inner():
+        yield x
+
+    return inner
+
+def h(x):
+    """Hello!
+
+    Args:
+        x: Hehehe.
+    """
+    received = yield x
+    return received
+
+def i(x):
+    """Hello!
+
+    Args:
+        x: Hehehe.
+
+    Yields:
+        int: Something.
+    """
+    return lambda: x
+"#;
+
+        let options = CheckOptions {
+            forbid_missing_yields: true,
+            ..Default::default()
+        };
+
+        let diagnostics = check_source(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &options,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.rule == Rule::MissingYields));
+        assert_eq!(diagnostics[0].row, Some(22));
+        assert_eq!(
+            diagnostics[0].message,
+            "Function is a generator, but its docstring has no Yields section"
+        );
+        assert_eq!(diagnostics[1].row, Some(31));
+        assert_eq!(
+            diagnostics[1].message,
+            "Docstring has a Yields section, but the function is not a generator"
+        );
+
+        assert!(matches!(
+            check_source(source_code, None, CompliancyChecker::Lexer, &options, None),
+            Err(CheckError::RequiresTreeSitter(_))
+        ));
+    }
+
+    #[test]
+    fn test_yield_type() {
+        assert_eq!(yield_type("Iterator[Foo]"), Some("Foo"));
+        assert_eq!(
+            yield_type("typing.Generator[Dict[str, int], None, None]"),
+            Some("Dict[str, int]")
+        );
+        assert_eq!(yield_type("AsyncIterator[ int ]"), Some("int"));
+        assert_eq!(yield_type("List[int]"), None);
+        assert_eq!(yield_type("int"), None);
+
+        let source_code = r#"def f(x) -> Generator[Dict[str,int], None, None]:
+    """Hello!
+
+    Args:
+        x: Hehehe.
+
+    Yields:
+        Dict[str, int]: Something.
+    """
+    yield {}
+
+def g(x) -> Iterator[int]:
+    """Hello!
+
+    Parameters
+    ----------
+    x
+        Hehehe.
+
+    Yields
+    ------
+    str
+        Something.
+    """
+    yield 1
+
+def h(x) -> int:
+    """Hello!
+
+    Args:
+        x: Hehehe.
+
+    Yields:
+        str: Something.
+    """
+    return 1
+"#;
+
+        let options = CheckOptions {
+            break_on_empty_line: true,
+            check_yield_type: true,
+            ..Default::default()
+        };
+
+        let mut parser = get_parser();
+
+        let from_tree_sitter = check_rules(&mut parser, source_code, None, None, &options).unwrap();
+        let from_lexing = check_rules_through_lexing(source_code, None, &options).unwrap();
+
+        for diagnostics in [from_tree_sitter, from_lexing] {
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].rule, Rule::YieldTypeMismatch);
+            assert_eq!(
+                diagnostics[0].message,
+                "Yield type from function: `int`. Yield type from docstring: `str`"
+            );
+        }
+    }
+
+    #[test]
+    fn test_attributes() {
+        let source_code = r#"@dataclass
+class A:
+    """Hello!
+
+    Attributes:
+        x (int): Hehehe.
+        y: Nope.
+    """
+
+    x: int
+    y: Dict[str, int] = field(default_factory=dict)
+    _cache: int = 0
+    z = 2
+
+    def f(self):
+        pass
+
+class B:
+    """Hello!
+
+    Attributes
+    ----------
+    y : int
+        Nope.
+    x : int
+        Hehehe.
+    """
+
+    x: int
+    y: int
+
+class C:
+    """Hello!"""
+
+    x: int
+"#;
+
+        let options = CheckOptions {
+            check_attributes: true,
+            ..Default::default()
+        };
+
+        let diagnostics = check_source(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &options,
+            None,
+        )
+        .unwrap();
+
+        // The difference in order is pointed at the heading of the attributes.
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, Rule::AttributesMismatch);
+        assert_eq!(diagnostics[0].function, "B");
+        assert_eq!(diagnostics[0].function_row, Some(17));
+        assert_eq!(
+            (diagnostics[0].row, diagnostics[0].column),
+            (Some(20), Some(4))
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "Docstring does not follow the order of the class"
+        );
+
+        let diagnostics = check_source(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &CheckOptions {
+                succeed_if_docstrings_are_not_typed: false,
+                ..options.clone()
+            },
+            None,
+        )
+        .unwrap();
+
+        // The difference in type is pointed at the documented entry.
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].function, "A");
+        assert_eq!(
+            (diagnostics[0].row, diagnostics[0].column),
+            (Some(6), Some(8))
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "`y`: class says `Dict[str, int]`, docstring has no type"
+        );
+
+        let suppressed = source_code
+            .replace("class B:", "class B:  # pystaleds: ignore[PSD304]")
+            .replace("@dataclass", "@dataclass\n# pystaleds: ignore");
+
+        // Classes are suppressed as functions are, by a comment on or above their line.
+        assert!(check_source(
+            &suppressed,
+            None,
+            CompliancyChecker::TreeSitter,
+            &CheckOptions {
+                succeed_if_docstrings_are_not_typed: false,
+                ..options.clone()
+            },
+            None
+        )
+        .unwrap()
+        .is_empty());
+
+        assert!(matches!(
+            check_source(source_code, None, CompliancyChecker::Lexer, &options, None),
+            Err(CheckError::RequiresTreeSitter(_))
+        ));
+    }
+
+    #[test]
+    fn test_init_args_in_class_docstring() {
+        let source_code = r#"class A:
+    """Documented at class level.
+
+    Args:
+        x (int): Hehehe.
+    """
+
+    def __init__(self, x: int):
+        """Constructor."""
+        self.x = x
+
+class B:
+    """Documented at class level, but stale.
+
+    Parameters
+    ----------
+    x : int
+        Hehehe.
+    """
+
+    @some_decorator
+    def __init__(self, x: int, y: int):
+        self.x = x
+
+class C:
+    """Documented in __init__."""
+
+    def __init__(self, x: int):
+        """Constructor.
+
+        Args:
+            x (int): Hehehe.
+        """
+        self.x = x
+
+class D:
+    """Documented in neither."""
+
+    def __init__(self, x: int):
+        """Constructor."""
+        self.x = x
+"#;
+
+        let options = CheckOptions {
+            succeed_if_no_args_in_docstring: false,
+            init_args_in_class_docstring: true,
+            ..Default::default()
+        };
+
+        let diagnostics = check_source(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &options,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].rule, Rule::MissingArg);
+        assert_eq!(diagnostics[0].row, Some(21));
+        assert_eq!(diagnostics[1].rule, Rule::MissingArgs);
+        assert_eq!(diagnostics[1].row, Some(38));
+
+        let diagnostics = check_source(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &CheckOptions {
+                init_args_in_class_docstring: false,
+                ..options.clone()
+            },
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].row, Some(7));
+        assert_eq!(diagnostics[1].row, Some(38));
+
+        assert!(matches!(
+            check_source(source_code, None, CompliancyChecker::Lexer, &options, None),
+            Err(CheckError::RequiresTreeSitter(_))
+        ));
+    }
+
+    #[test]
+    fn test_module_docstring() {
+        let options = CheckOptions {
+            forbid_no_module_docstring: true,
+            ..Default::default()
+        };
+
+        let with_docstring = r#"#!/usr/bin/env python
+# A comment.
+from __future__ import annotations
+
+r"""Module docstring."""
+
+import os
+"#;
+
+        let without_docstring = r#"import os
+
+"""Not a module docstring."""
+"#;
+
+        let shim_init = "from .a import f\n\n__all__ = [\"f\"]\n";
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let check = |source_code, path: &str, options| {
+                check_source(source_code, Some(Path::new(path)), checker, options, None).unwrap()
+            };
+
+            assert!(check(with_docstring, "module.py", &options).is_empty());
+            assert!(check("# Just a comment.\n", "module.py", &options).is_empty());
+            assert!(check(shim_init, "pkg/__init__.py", &options).is_empty());
+
+            let diagnostics = check(without_docstring, "module.py", &options);
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].rule, Rule::MissingModuleDocstring);
+            assert_eq!(diagnostics[0].path, Some(PathBuf::from("module.py")));
+
+            let strict_options = CheckOptions {
+                succeed_if_empty_module: false,
+                skip_trivial_init: false,
+                ..options.clone()
+            };
+
+            assert_eq!(check("", "module.py", &strict_options).len(), 1);
+            assert_eq!(
+                check(shim_init, "pkg/__init__.py", &strict_options).len(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let options = CheckOptions {
+            forbid_placeholders: true,
+            break_on_empty_line: true,
+            ..Default::default()
+        };
+
+        let google = r#"def f(x, y):
+    """_summary_
+
+    Args:
+        x (int): The first value.
+        y (_type_): _description_
+
+    Returns:
+        int: TODO
+    """
+    return x + y
+"#;
+
+        let numpy = r#"def f(x, y):
+    """Adds the values.
+
+    Parameters
+    ----------
+    x : int
+        The first value.
+    y : float
+        The second value. FIXME: should be an int.
+    """
+    return x + y
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(google, None, checker, &options, None).unwrap();
+
+            let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+
+            assert!(diagnostics
+                .iter()
+                .all(|diagnostic| diagnostic.rule == Rule::PlaceholderText));
+            assert_eq!(
+                messages,
+                vec![
+                    "Placeholder `_summary_` found in the docstring description",
+                    "Placeholder `_type_` found in the type of `y`",
+                    "Placeholder `_description_` found in the description of `y`",
+                    "Placeholder `TODO` found in the description of the Returns section",
+                ]
+            );
+
+            let diagnostics = check_source(numpy, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(
+                diagnostics[0].message,
+                "Placeholder `FIXME` found in the description of `y`"
+            );
+
+            let custom_options = CheckOptions {
+                placeholders: vec!["XXX".to_string()],
+                ..options.clone()
+            };
+
+            assert!(check_source(numpy, None, checker, &custom_options, None)
+                .unwrap()
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_empty_descriptions() {
+        let options = CheckOptions {
+            forbid_empty_descriptions: true,
+            ..Default::default()
+        };
+
+        let google = r#"def f(x, timeout, retries, *args):
+    """Hey.
+
+    Args:
+        x:
+        timeout (float):
+            Seconds to wait for,
+            spanning two lines.
+        retries (int):
+        *args:
+
+    Returns:
+        int: Something.
+    """
+    return 1
+"#;
+
+        let numpy = r#"def f(x, timeout, retries):
+    """Hey.
+
+    Parameters
+    ----------
+    x : int
+    timeout : float
+        Seconds to wait for.
+    retries
+        How many times to retry.
+
+    Returns
+    -------
+    int
+        Something.
+    """
+    return 1
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(google, None, checker, &options, None).unwrap();
+
+            let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+
+            assert!(diagnostics
+                .iter()
+                .all(|diagnostic| diagnostic.rule == Rule::EmptyDescription));
+            assert_eq!(
+                messages,
+                vec![
+                    "Arg `x` has an empty description",
+                    "Arg `retries` has an empty description",
+                ]
+            );
+
+            let diagnostics = check_source(numpy, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].message, "Arg `x` has an empty description");
+        }
+    }
+
+    #[test]
+    fn test_default_mentions() {
+        let options = CheckOptions {
+            check_default_mentions: true,
+            ..Default::default()
+        };
+
+        let google = r#"def f(x, timeout: float = 1.0, retries=3):
+    """Hey.
+
+    Args:
+        x: The value.
+        timeout (float): Seconds to wait for.
+            Defaults to 1.0.
+        retries: How many times to retry.
+    """
+    return x
+"#;
+
+        let numpy = r#"def f(x, timeout: float = 1.0, retries=3):
+    """Hey.
+
+    Parameters
+    ----------
+    x : int
+        The value.
+    timeout : float
+        Seconds to wait for. Default is 1.0.
+    retries : int
+        How many times to retry.
+    """
+    return x
+"#;
+
+        let custom_options = CheckOptions {
+            default_mention_pattern: Regex::new(r"Defaults to .+\.").unwrap(),
+            ..options.clone()
+        };
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(google, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].rule, Rule::MissingDefaultMention);
+            assert_eq!(
+                diagnostics[0].message,
+                "Default value of `retries` is not mentioned in its description"
+            );
+
+            let diagnostics = check_source(numpy, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(
+                diagnostics[0].message,
+                "Default value of `retries` is not mentioned in its description"
+            );
+
+            let diagnostics = check_source(google, None, checker, &custom_options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_optional_consistency() {
+        let options = CheckOptions {
+            check_optional_consistency: true,
+            break_on_empty_line: true,
+            ..Default::default()
+        };
+
+        let google = r#"def f(a, b=1, c=2, *, d):
+    """Hey.
+
+    Args:
+        a (int): Required and documented as such.
+        b (int, optional): Defaulted and documented as such.
+        c (int): Defaulted, but documented as required.
+        d (int, optional): Required, but documented as optional.
+    """
+"#;
+
+        let numpy = r#"def f(a, b=1, c=2, *, d):
+    """Hey.
+
+    Parameters
+    ----------
+    a : int
+        Required and documented as such.
+    b : int, optional
+        Defaulted and documented as such.
+    c : int
+        Defaulted, but documented as required.
+    d : int, optional
+        Required, but documented as optional.
+    """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            for source_code in [google, numpy] {
+                let diagnostics = check_source(source_code, None, checker, &options, None)
+                    .unwrap()
+                    .into_iter()
+                    .filter(|diagnostic| diagnostic.rule == Rule::OptionalMismatch)
+                    .map(|diagnostic| diagnostic.message)
+                    .collect::<Vec<_>>();
+
+                assert_eq!(
+                    diagnostics,
+                    vec![
+                        "Arg `c` has a default value, but is not documented as optional",
+                        "Arg `d` is documented as optional, but has no default value",
+                    ]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_allow_out_of_order_args() {
+        let options = CheckOptions {
+            allow_out_of_order_args: true,
+            ..Default::default()
+        };
+
+        let reordered = r#"def f(x: int, y: str):
+    """Hey.
+
+    Args:
+        y (str): Second var.
+        x (int): First var.
+    """
+"#;
+
+        let wrong_type = r#"def f(x: int, y: str):
+    """Hey.
+
+    Args:
+        y (int): Second var.
+        x (int): First var.
+    """
+"#;
+
+        let duplicated = r#"def f(x: int, y: str):
+    """Hey.
+
+    Args:
+        x (int): First var.
+        x (int): First var again.
+    """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let check = |source_code, options| {
+                check_source(source_code, None, checker, options, None).unwrap()
+            };
+
+            assert!(check(reordered, &options).is_empty());
+            assert_eq!(check(wrong_type, &options)[0].rule, Rule::ArgTypeMismatch);
+            assert_eq!(check(duplicated, &options)[0].rule, Rule::DuplicateArg);
+
+            assert_eq!(
+                check(reordered, &CheckOptions::default())[0].rule,
+                Rule::ArgsOrder
+            );
+        }
+    }
+
+    #[test]
+    fn test_duplicate_args() {
+        let google = r#"def f(x: int, y: str):
+    """Hey.
+
+    Args:
+        x (int): First var.
+        x (str): First var again.
+    """
+"#;
+
+        let numpy = r#"def f(x: int, y: str, z: float):
+    """Hey.
+
+    Parameters
+    ----------
+    x : int
+        First var.
+    y : str
+        Second var.
+    x : str
+        First var again.
+    """
+"#;
+
+        for options in [
+            CheckOptions::default(),
+            CheckOptions {
+                succeed_if_docstrings_are_not_typed: false,
+                ..Default::default()
+            },
+        ] {
+            for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+                for source_code in [google, numpy] {
+                    let diagnostics =
+                        check_source(source_code, None, checker, &options, None).unwrap();
+
+                    assert_eq!(diagnostics.len(), 1);
+                    assert_eq!(diagnostics[0].rule, Rule::DuplicateArg);
+                    assert_eq!(
+                        diagnostics[0].message,
+                        "Parameter `x` documented multiple times"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_section_order() {
+        let options = CheckOptions {
+            check_section_order: true,
+            break_on_empty_line: true,
+            ..Default::default()
+        };
+
+        let google = r#"def f(x):
+    """Hey.
+
+    Raises:
+        ValueError: If something is off.
+
+    Notes:
+        Anything goes here.
+
+    Args:
+        x: First var.
+
+    Returns:
+        Something.
+    """
+"#;
+
+        let numpy = r#"def f(x):
+    """Hey.
+
+    Parameters
+    ----------
+    x
+        First var.
+
+    Examples
+    --------
+    >>> f(1)
+
+    Returns
+    -------
+    int
+        Something.
+    """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let messages = |source_code, options| {
+                check_source(source_code, None, checker, options, None)
+                    .unwrap()
+                    .into_iter()
+                    .filter(|diagnostic| diagnostic.rule == Rule::SectionOrder)
+                    .map(|diagnostic| diagnostic.message)
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(
+                messages(google, &options),
+                vec![
+                    "Section `Args` should come before section `Raises`",
+                    "Section `Returns` should come before section `Raises`",
+                ]
+            );
+            assert_eq!(
+                messages(numpy, &options),
+                vec!["Section `Returns` should come before section `Examples`"]
+            );
+
+            let custom_options = CheckOptions {
+                section_order: vec!["raises".to_string(), "args".to_string()],
+                ..options.clone()
+            };
+
+            assert!(messages(google, &custom_options).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_missing_summary() {
+        let options = CheckOptions {
+            forbid_missing_summary: true,
+            ..Default::default()
+        };
+
+        let source_code = r#"def f(x):
+    """Hey."""
+
+
+def g(x):
+    '''
+
+    Args:
+        x: First var.
+    '''
+
+
+def h(x):
+    """
+    Parameters
+    ----------
+    x
+        First var.
+    """
+
+
+def i(x):
+    """   """
+
+
+def j(x):
+    '''Hey.
+
+    Args:
+        x: First var.
+    '''
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(source_code, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 3);
+            assert!(diagnostics.iter().all(|diagnostic| {
+                diagnostic.rule == Rule::MissingSummary
+                    && diagnostic.message == "Docstring has no summary"
+            }));
+        }
+
+        let diagnostics = check_source(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &options,
+            None,
+        )
+        .unwrap();
+
+        let rows: Vec<_> = diagnostics.iter().map(|d| d.row).collect();
+
+        assert_eq!(rows, vec![Some(4), Some(12), Some(21)]);
+    }
+
+    #[test]
+    fn test_documented_self() {
+        let google = r#"class A:
+    def f(self, x):
+        """Hey.
+
+        Args:
+            self: The instance.
+            x: First var.
+        """
+"#;
+
+        let numpy = r#"class A:
+    def f(self, x):
+        """Hey.
+
+        Parameters
+        ----------
+        self
+            The instance.
+        x
+            First var.
+        """
+"#;
+
+        let allowing_options = CheckOptions {
+            allow_documented_self: true,
+            ..Default::default()
+        };
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            for source_code in [google, numpy] {
+                let diagnostics =
+                    check_source(source_code, None, checker, &CheckOptions::default(), None)
+                        .unwrap();
+
+                assert_eq!(diagnostics.len(), 1);
+                assert_eq!(diagnostics[0].rule, Rule::DocumentedSelf);
+                assert_eq!(diagnostics[0].message, "Do not document `self`");
+
+                assert!(
+                    check_source(source_code, None, checker, &allowing_options, None)
+                        .unwrap()
+                        .is_empty()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_require_full_typing() {
+        let options = CheckOptions {
+            require_full_typing: true,
+            ..Default::default()
+        };
+
+        let source_code = r#"def f(x: int, y, z: str, *args):
+    """Hey.
+
+    Args:
+        x (int): First var.
+        y (float): Second var.
+        z: Third var.
+        *args: Anything else.
+    """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(source_code, None, checker, &options, None).unwrap();
+
+            let found: Vec<_> = diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.rule, diagnostic.message.as_str()))
+                .collect();
+
+            assert_eq!(
+                found,
+                vec![
+                    (
+                        Rule::MissingAnnotation,
+                        "Parameter `y` has no type annotation"
+                    ),
+                    (
+                        Rule::UntypedDocstringArg,
+                        "Parameter `z` has no type in the docstring"
+                    ),
+                ]
+            );
+        }
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(
+                source_code,
+                None,
+                checker,
+                &CheckOptions {
+                    skip_args_and_kwargs: false,
+                    ..options.clone()
+                },
+                None,
+            )
+            .unwrap();
+
+            assert!(diagnostics.iter().any(|diagnostic| {
+                diagnostic.message == "Parameter `*args` has no type in the docstring"
+            }));
+        }
+    }
+
+    #[test]
+    fn test_typed_args_and_kwargs() {
+        let typed_google = |args: &str, kwargs: &str| {
+            format!(
+                r#"def f(x: int, *args: int, **kwargs: str):
+    """Hey.
+
+    Args:
+        x (int): First var.
+        {args}: Positional vars.
+        {kwargs}: Keyword vars.
+    """
+"#
+            )
+        };
+
+        let typed_numpy = r#"def f(x: int, *args: int, **kwargs: str):
+    """Hey.
+
+    Parameters
+    ----------
+    x : int
+        First var.
+    *args : int
+        Positional vars.
+    **kwargs : str
+        Keyword vars.
+    """
+"#;
+
+        let untyped = r#"def f(x, *args, **kwargs):
+    """Hey.
+
+    Args:
+        x: First var.
+        *args: Positional vars.
+        **kwargs: Keyword vars.
+    """
+"#;
+
+        let cases = [
+            (typed_google("*args (int)", "**kwargs (str)"), true, true),
+            (typed_numpy.to_string(), true, true),
+            (typed_google("*args (str)", "**kwargs (str)"), false, false),
+            (typed_google("*args", "**kwargs"), true, false),
+            (untyped.to_string(), true, true),
+        ];
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            for (source_code, passes_lenient, passes_strict) in &cases {
+                for (succeed_if_docstrings_are_not_typed, passes) in
+                    [(true, passes_lenient), (false, passes_strict)]
+                {
+                    let options = CheckOptions {
+                        skip_args_and_kwargs: false,
+                        succeed_if_docstrings_are_not_typed,
+                        break_on_empty_line: true,
+                        ..Default::default()
+                    };
+
+                    let diagnostics =
+                        check_source(source_code, None, checker, &options, None).unwrap();
+
+                    assert_eq!(diagnostics.is_empty(), *passes, "{source_code}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_allow_kwargs_docs() {
+        let options = CheckOptions {
+            allow_kwargs_docs: true,
+            ..Default::default()
+        };
+
+        let source_code = |signature: &str, args: &str| {
+            format!(
+                r#"def f({signature}):
+    """Hey.
+
+    Args:
+{args}
+    """
+"#
+            )
+        };
+
+        let with_keys = "        x (int): First var.\n        verbose (bool): Whether to log.\n        retries (int): Attempts.";
+        let missing_x = "        verbose (bool): Whether to log.\n        retries (int): Attempts.";
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            for include_args_and_kwargs in [false, true] {
+                let options = CheckOptions {
+                    skip_args_and_kwargs: !include_args_and_kwargs,
+                    ..options.clone()
+                };
+
+                let args = if include_args_and_kwargs {
+                    format!("{with_keys}\n        **options: Other options.")
+                } else {
+                    with_keys.to_string()
+                };
+
+                let passing = source_code("x: int, **options", &args);
+
+                assert!(check_source(&passing, None, checker, &options, None)
+                    .unwrap()
+                    .is_empty());
+                assert!(!check_source(
+                    &passing,
+                    None,
+                    checker,
+                    &CheckOptions {
+                        allow_kwargs_docs: false,
+                        ..options.clone()
+                    },
+                    None
+                )
+                .unwrap()
+                .is_empty());
+            }
+
+            let missing_param = source_code("x: int, **kwargs", missing_x);
+            let no_kwargs = source_code("x: int", with_keys);
+
+            for failing in [missing_param, no_kwargs] {
+                assert!(!check_source(&failing, None, checker, &options, None)
+                    .unwrap()
+                    .is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_skip_stub_bodies() {
+        let source_code = r#"from typing import Protocol
+
+
+class Store(Protocol):
+    def get(self, key: str) -> bytes: ...
+
+    def put(self, key: str, value: bytes) -> None:
+        ...
+
+    def delete(self, key: str) -> None:
+        # Removes the key.
+        pass
+
+    def clear(self) -> None:
+        """Removes every key."""
+        ...
+
+    def size(self, unit):
+        """Size of the store.
+
+        Args:
+            units: Unit of the size.
+        """
+        ...
+
+
+def real(x):
+    return x
+"#;
+
+        let options = CheckOptions {
+            succeed_if_no_docstring: false,
+            succeed_if_no_args_in_docstring: false,
+            skip_stub_bodies: true,
+            ..Default::default()
+        };
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(source_code, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 3);
+            assert_eq!(diagnostics[0].rule, Rule::ExtraArg);
+            assert_eq!(diagnostics[1].rule, Rule::MissingArg);
+            assert_eq!(diagnostics[2].rule, Rule::MissingDocstring);
+
+            let diagnostics = check_source(
+                source_code,
+                None,
+                checker,
+                &CheckOptions {
+                    skip_stub_bodies: false,
+                    ..options.clone()
+                },
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(diagnostics.len(), 7);
+        }
+    }
+
+    #[test]
+    fn test_arg_differences() {
+        let source_code = r#"def f(host: str, port: int, timeout: float, retries: int):
+    """Hey.
+
+    Args:
+        port (int): Port.
+        host (str): Host.
+        retries (str): Retries.
+        verbose (bool): Verbosity.
+    """
+"#;
+
+        let options = CheckOptions {
+            succeed_if_docstrings_are_not_typed: false,
+            ..Default::default()
+        };
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(source_code, None, checker, &options, None).unwrap();
+
+            let messages: Vec<_> = diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.message.as_str())
+                .collect();
+
+            assert_eq!(
+                messages,
+                vec![
+                    "`retries`: signature says `int`, docstring says `str`",
+                    "`verbose` documented, but not in the signature",
+                    "`timeout` missing from docstring",
+                    "Docstring does not follow the order of the signature",
+                ]
+            );
+            let rules: Vec<_> = diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.rule)
+                .collect();
+
+            assert_eq!(
+                rules,
+                vec![
+                    Rule::ArgTypeMismatch,
+                    Rule::ExtraArg,
+                    Rule::MissingArg,
+                    Rule::ArgsOrder,
+                ]
+            );
+            assert!(diagnostics
+                .iter()
+                .all(|diagnostic| diagnostic.entries.is_some()));
+        }
+    }
+
+    #[test]
+    fn test_param_case() {
+        let source_code = r#"def f(df: int, x: str):
+    """Hey.
+
+    Args:
+        DF (int): First var.
+        X (str): Second var.
+    """
+"#;
+
+        let ignoring_options = CheckOptions {
+            ignore_param_case: true,
+            ..Default::default()
+        };
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics =
+                check_source(source_code, None, checker, &CheckOptions::default(), None).unwrap();
+
+            let found: Vec<_> = diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.rule, diagnostic.message.as_str()))
+                .collect();
+
+            assert_eq!(
+                found,
+                vec![
+                    (
+                        Rule::ParamCaseMismatch,
+                        "Docstring documents `DF`, signature has `df`"
+                    ),
+                    (
+                        Rule::ParamCaseMismatch,
+                        "Docstring documents `X`, signature has `x`"
+                    ),
+                ]
+            );
+
+            assert!(
+                check_source(source_code, None, checker, &ignoring_options, None)
+                    .unwrap()
+                    .is_empty()
+            );
+
+            for (other_difference, rule) in [
+                (
+                    source_code.replace("X (str)", "X (int)"),
+                    Rule::ArgTypeMismatch,
+                ),
+                (
+                    source_code.replace("        X (str): Second var.\n", ""),
+                    Rule::MissingArg,
+                ),
+            ] {
+                let diagnostics = check_source(
+                    &other_difference,
+                    None,
+                    checker,
+                    &CheckOptions {
+                        succeed_if_docstrings_are_not_typed: false,
+                        ..ignoring_options.clone()
+                    },
+                    None,
+                )
+                .unwrap();
+
+                assert_eq!(diagnostics.len(), 1);
+                assert_eq!(diagnostics[0].rule, rule);
+            }
+        }
+    }
+
+    #[test]
+    fn test_source_report() {
+        let source_code = r#"def f(x):
+    """Hey.
+
+    Args:
+        y: Nope.
+    """
+
+
+def _g(x):
+    pass
+
+
+def h(x):
+    """Hey.
+
+    Args:
+        x: Yes.
+    """
+"#;
+
+        let options = CheckOptions {
+            ignore_private: true,
+            ..Default::default()
+        };
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let report = check_source_report(
+                source_code,
+                Some(Path::new("module.py")),
+                checker,
+                &options,
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(report.path.as_deref(), Some(Path::new("module.py")));
+
+            let names: Vec<_> = report
+                .functions
+                .iter()
+                .map(|function| (function.name.as_str(), function.row))
+                .collect();
+            assert_eq!(names, vec![("f", Some(0)), ("h", Some(12))]);
+
+            assert_eq!(report.diagnostics.len(), 2);
+            assert!(report.functions[0].matches(&report.diagnostics[0]));
+            assert!(!report.functions[1].matches(&report.diagnostics[0]));
+        }
+    }
+
+    #[test]
+    fn test_columns() {
+        let source_code = r#"def f(x):
+    """Hey.
+
+    Args:
+        y: Nope.
+    """
+
+
+class A:
+    """A class."""
+
+    def g(self, x):
+        """Hey.
+
+        Args:
+            y: Nope.
+        """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics =
+                check_source(source_code, None, checker, &CheckOptions::default(), None).unwrap();
+
+            let positions: Vec<_> = diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.function_row, diagnostic.row, diagnostic.column))
+                .collect();
+
+            // Extra entries are pointed at, and missing ones at the heading of the args.
+            assert_eq!(
+                positions,
+                vec![
+                    (Some(0), Some(4), Some(8)),
+                    (Some(0), Some(3), Some(4)),
+                    (Some(11), Some(15), Some(12)),
+                    (Some(11), Some(14), Some(8)),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_entry_positions() {
+        let source_code = r#"class A:
+    """A class."""
+
+    def f(self, x: int, Y, z):
+        """Hey.
+
+        Args:
+            x (str): First.
+            y: Second.
+        """
+
+
+def g(x, y):
+    """Hey.
+
+    Parameters
+    ----------
+    x : int
+        First.
+    """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics =
+                check_source(source_code, None, checker, &CheckOptions::default(), None).unwrap();
+
+            let positions: Vec<_> = diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.rule, diagnostic.row, diagnostic.column))
+                .collect();
+
+            // Mismatching entries are pointed at, and missing ones at the heading of the
+            // args, whichever its style.
+            assert_eq!(
+                positions,
+                vec![
+                    (Rule::ArgTypeMismatch, Some(7), Some(12)),
+                    (Rule::ParamCaseMismatch, Some(8), Some(12)),
+                    (Rule::MissingArg, Some(6), Some(8)),
+                    (Rule::MissingArg, Some(15), Some(4)),
+                ],
+                "{checker:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_only() {
+        let source_code = r#"def free(x):
+    return x
+
+
+class A:
+    def method(self, x):
+        return x
+
+    @staticmethod
+    def static(x):
+        return x
+
+    def nested_function(self):
+        def inner(y):
+            return y
+
+        return inner
+
+
+def other_free(x):
+    return x
+"#;
+
+        let options = |only| CheckOptions {
+            succeed_if_no_docstring: false,
+            only,
+            ..Default::default()
+        };
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let names = |only| -> Vec<String> {
+                check_source(source_code, None, checker, &options(only), None)
+                    .unwrap()
+                    .into_iter()
+                    .map(|diagnostic| match checker {
+                        CompliancyChecker::TreeSitter => diagnostic.row.unwrap().to_string(),
+                        CompliancyChecker::Lexer => diagnostic.function,
+                    })
+                    .collect()
+            };
+
+            let (free, methods, inner) = match checker {
+                CompliancyChecker::TreeSitter => (vec!["0", "19"], vec!["5", "9", "12"], "13"),
+                CompliancyChecker::Lexer => (
+                    vec!["free", "other_free"],
+                    vec!["method", "static", "nested_function"],
+                    "inner",
+                ),
+            };
+
+            let mut expected_functions = free.clone();
+            expected_functions.insert(1, inner);
+
+            assert_eq!(names(FunctionSelection::Functions), expected_functions);
+            assert_eq!(names(FunctionSelection::Methods), methods);
+            assert_eq!(names(FunctionSelection::All).len(), 6);
+        }
+    }
+
+    #[test]
+    fn test_allow_omitting_defaulted_args() {
+        let options = CheckOptions {
+            allow_omitting_defaulted_args: true,
+            succeed_if_docstrings_are_not_typed: false,
+            ..Default::default()
+        };
+
+        let docstring = |args: &str| {
+            format!(
+                r#"def f(x: int, y: str, z: float = 1.0, w: bool = False):
+    """Hey.
+
+    Args:
+{args}
+    """
+"#
+            )
+        };
+
+        let only_required = docstring("        x (int): First.\n        y (str): Second.");
+        let with_one_default = docstring(
+            "        x (int): First.\n        y (str): Second.\n        w (bool): Fourth.",
+        );
+        let missing_required = docstring("        x (int): First.\n        z (float): Third.");
+        let out_of_order = docstring(
+            "        x (int): First.\n        w (bool): Fourth.\n        y (str): Second.",
+        );
+        let wrong_type =
+            docstring("        x (int): First.\n        y (str): Second.\n        z (int): Third.");
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let check = |source_code: &str, options| {
+                check_source(source_code, None, checker, options, None).unwrap()
+            };
+
+            assert!(check(&only_required, &options).is_empty());
+            assert!(check(&with_one_default, &options).is_empty());
+            assert_eq!(check(&missing_required, &options).len(), 1);
+            assert_eq!(check(&out_of_order, &options).len(), 1);
+            assert_eq!(check(&wrong_type, &options).len(), 1);
+
+            assert_eq!(
+                check(&only_required, &CheckOptions::default())[0].rule,
+                Rule::MissingArg
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_function_lines() {
+        let options = CheckOptions {
+            succeed_if_no_docstring: false,
+            min_function_lines: 3,
+            ..Default::default()
+        };
+
+        let source_code = r#"def short(x):
+    return x
+
+
+def long(x):
+    y = x + 1
+    z = y + 1
+    return z
+
+
+def documented(x):
+    """Hey.
+
+    Args:
+        y: Wrong var.
+    """
+    return x
+"#;
+
+        let diagnostics = check_source(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &options,
+            None,
+        )
+        .unwrap();
+
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.rule, diagnostic.row))
+            .collect();
+
+        assert_eq!(
+            found,
+            vec![
+                (Rule::MissingDocstring, Some(4)),
+                (Rule::ExtraArg, Some(14)),
+                (Rule::MissingArg, Some(13)),
+            ]
+        );
+
+        assert!(matches!(
+            check_source(source_code, None, CompliancyChecker::Lexer, &options, None),
+            Err(CheckError::RequiresTreeSitter(_))
+        ));
+    }
+
+    #[test]
+    fn test_ignore_private() {
+        let options = CheckOptions {
+            succeed_if_no_docstring: false,
+            ignore_private: true,
+            ..Default::default()
+        };
+
+        let source_code = r#"def _helper(x):
+    return x
+
+
+def public(x):
+    """Hey.
+
+    Args:
+        x: First var.
+    """
+    def _nested(y):
+        return y
+
+    return _nested(x)
+
+
+class A:
+    def __init__(self, x):
+        self.x = x
+
+    def _method(self, y):
+        """Hey.
+
+        Args:
+            z: Wrong var.
+        """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(source_code, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].rule, Rule::MissingDocstring);
+            assert!(diagnostics[0].function == "__init__" || diagnostics[0].row == Some(17));
+
+            let diagnostics = check_source(
+                source_code,
+                None,
+                checker,
+                &CheckOptions {
+                    ignore_private: false,
+                    ..options.clone()
+                },
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(diagnostics.len(), 5);
+        }
+    }
+
+    #[test]
+    fn test_ignore_dunder() {
+        let options = CheckOptions {
+            succeed_if_no_docstring: false,
+            ignore_dunder: true,
+            ..Default::default()
+        };
+
+        let source_code = r#"class A:
+    def __init__(self, x):
+        self.x = x
+
+    def __call__(self, y, z):
+        """Hey.
+
+        Args:
+            w: Wrong var.
+        """
+        return y + z
+
+    def _helper(self, x):
+        return x
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let check = |options: &CheckOptions| {
+                check_source(source_code, None, checker, options, None).unwrap()
+            };
+
+            let diagnostics = check(&options);
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].rule, Rule::MissingDocstring);
+
+            let with_private = CheckOptions {
+                ignore_private: true,
+                ..options.clone()
+            };
+
+            assert!(check(&with_private).is_empty());
+
+            let only_private = CheckOptions {
+                ignore_dunder: false,
+                ..with_private
+            };
+
+            assert_eq!(check(&only_private).len(), 4);
+        }
+    }
+
+    #[test]
+    fn test_overloads() {
+        let options = CheckOptions {
+            succeed_if_no_docstring: false,
+            ..Default::default()
+        };
+
+        let source_code = |implementation_docstring: &str| {
+            format!(
+                r#"from typing import overload
+import typing
+
+
+@overload
+def f(x: int) -> int: ...
+
+
+@typing.overload
+def f(x: str) -> str: ...
+
+
+@overload
+def f(x: bytes) -> bytes:
+    pass
+
+
+def f(x):
+    {implementation_docstring}
+    return x
+"#
+            )
+        };
+
+        let correct = source_code(
+            r#""""Hey.
+
+    Args:
+        x: First var.
+    """"#,
+        );
+
+        let incorrect = source_code(
+            r#""""Hey.
+
+    Args:
+        y: Wrong var.
+    """"#,
+        );
+
+        let check = |source_code: &str| {
+            check_source(
+                source_code,
+                None,
+                CompliancyChecker::TreeSitter,
+                &options,
+                None,
+            )
+            .unwrap()
+        };
+
+        assert!(check(&correct).is_empty());
+
+        let diagnostics = check(&incorrect);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].rule, Rule::ExtraArg);
+        assert_eq!(diagnostics[0].function_row, Some(17));
+        assert_eq!(diagnostics[0].row, Some(21));
+        assert_eq!(
+            diagnostics[0].message,
+            "`y` documented, but not in the signature"
+        );
+        assert_eq!(diagnostics[1].message, "`x` missing from docstring");
+    }
+
+    #[test]
+    fn test_skip_decorated() {
+        let options = CheckOptions {
+            succeed_if_no_docstring: false,
+            skip_decorated: vec!["task".to_string(), "cli.command".to_string()],
+            ..Default::default()
+        };
+
+        let source_code = r#"@app.task
+def f(x):
+    return x
+
+
+@deprecated
+@cli.command("g")
+def g(x):
+    return x
+
+
+@subtask
+def h(x):
+    return x
+
+
+@other_cli.command
+def i(x):
+    return x
+"#;
+
+        let diagnostics = check_source(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &options,
+            None,
+        )
+        .unwrap();
+
+        let rows: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.row)
+            .collect();
+
+        assert_eq!(rows, vec![Some(12), Some(17)]);
+
+        assert!(matches!(
+            check_source(source_code, None, CompliancyChecker::Lexer, &options, None),
+            Err(CheckError::RequiresTreeSitter(_))
+        ));
+    }
+
+    #[test]
+    fn test_exclude_functions() {
+        let options = CheckOptions {
+            succeed_if_no_docstring: false,
+            exclude_functions: vec![
+                Regex::new("^test_").unwrap(),
+                Regex::new("^_generated_").unwrap(),
+            ],
+            ..Default::default()
+        };
+
+        let source_code = r#"def test_something(x):
+    return x
+
+
+def _generated_helper(x):
+    return x
+
+
+def attest(x):
+    return x
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(source_code, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].rule, Rule::MissingDocstring);
+            assert!(diagnostics[0].function == "attest" || diagnostics[0].row == Some(8));
+        }
+    }
+
+    #[test]
+    fn test_positional_only() {
+        let source_code = r#"def f(a: int, b, /, c: str, *, d=1):
+    """Hey.
+
+    Args:
+        a (int): First var.
+        b: Second var.
+        c (str): Third var.
+        d: Fourth var.
+    """
+"#;
+
+        let options = CheckOptions {
+            succeed_if_docstrings_are_not_typed: false,
+            ..Default::default()
+        };
+
+        let mut parser = get_parser();
+
+        assert!(respects_rules(&mut parser, source_code, None, None, &options).unwrap());
+        assert!(respects_rules_through_lexing(source_code, None, &options).unwrap());
+
+        let missing_c = source_code.replace("        c (str): Third var.\n", "");
+
+        assert!(!respects_rules(&mut parser, &missing_c, None, None, &options).unwrap());
+        assert!(!respects_rules_through_lexing(&missing_c, None, &options).unwrap());
+    }
+
+    #[test]
+    fn test_keyword_only() {
+        let google = r#"def f(a: int, *, b: str, c: float = 1.0):
+    """Hey.
+
+    Args:
+        a (int): First var.
+        b (str): Second var.
+        c (float): Third var.
+    """
+"#;
+
+        let numpy = r#"def f(a: int, *, b: str, c: float = 1.0):
+    """Hey.
+
+    Parameters
+    ----------
+    a : int
+        First var.
+    b : str
+        Second var.
+    c : float
+        Third var.
+    """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            for source_code in [google, numpy] {
+                for skip_args_and_kwargs in [true, false] {
+                    let options = CheckOptions {
+                        skip_args_and_kwargs,
+                        succeed_if_docstrings_are_not_typed: false,
+                        ..Default::default()
+                    };
+
+                    assert!(check_source(source_code, None, checker, &options, None)
+                        .unwrap()
+                        .is_empty());
+
+                    let missing_b = source_code
+                        .replace("        b (str): Second var.\n", "")
+                        .replace("    b : str\n        Second var.\n", "");
+
+                    assert!(!check_source(&missing_b, None, checker, &options, None)
+                        .unwrap()
+                        .is_empty());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_type_comments() {
+        let options = CheckOptions {
+            succeed_if_docstrings_are_not_typed: false,
+            check_return_type: true,
+            ..Default::default()
+        };
+
+        let source_code = r#"def f(x, y, *args):  # type: (int, Dict[str, int], *str) -> bool
+    """Hey.
+
+    Args:
+        x (int): First var.
+        y (Dict[str, int]): Second var.
+
+    Returns:
+        bool: Whether it worked.
+    """
+
+
+class A:
+    def g(self, x):  # type: (...) -> None
+        """Hey.
+
+        Args:
+            x: First var.
+        """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics = check_source(source_code, None, checker, &options, None).unwrap();
+            assert!(diagnostics.is_empty());
+
+            let wrong_type = source_code.replace("x (int)", "x (str)");
+            let diagnostics = check_source(&wrong_type, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].rule, Rule::ArgTypeMismatch);
+            assert_eq!(
+                diagnostics[0].message,
+                "`x`: signature says `int`, docstring says `str`"
+            );
+
+            let wrong_arity = source_code.replace("*str) -> bool", "*str, int) -> bool");
+            let diagnostics = check_source(&wrong_arity, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 3);
+            assert_eq!(diagnostics[0].rule, Rule::TypeCommentMismatch);
+            assert_eq!(
+                diagnostics[0].message,
+                "Type comment has 4 types, but the signature has 3 params"
+            );
+            assert_eq!(diagnostics[1].rule, Rule::ArgTypeMismatch);
+            assert_eq!(
+                diagnostics[1].message,
+                "`x`: signature has no type, docstring says `int`"
+            );
+            assert_eq!(diagnostics[2].rule, Rule::ArgTypeMismatch);
+        }
+    }
+
+    #[test]
+    fn test_receivers() {
+        let source_code = r#"class A:
+    def f(self, x):
+        """Hey.
+
+        Args:
+            x: First var.
+        """
+
+    @classmethod
+    def g(cls, x):
+        """Hey.
+
+        Args:
+            x: First var.
+        """
+
+    @staticmethod
+    def h(x, y):
+        """Hey.
+
+        Args:
+            x: First var.
+            y: Second var.
+        """
+
+    @staticmethod
+    def i(x, y):
+        """Hey.
 
-    let mut success = true;
-    let mut params = Vec::with_capacity(8);
+        Args:
+            y: Second var.
+        """
 
-    walk_rec(&mut cursor, &mut |node| {
-        let fs = get_function_signature(node, source_code, &mut params);
-        if let Some(info) = fs {
-            if !is_function_info_valid(
-                &info,
-                path,
-                break_on_empty_line,
-                succeed_if_no_docstring,
-                succeed_if_no_args_in_docstring,
-                succeed_if_docstrings_are_not_typed,
-                skip_args_and_kwargs,
-                docstyle,
-            ) {
-                success = false;
-            }
-        }
-    });
 
-    success
-}
+def j(self, x):
+    """Hey.
 
-/// Checks if the source code respects the specified rules.
-#[allow(clippy::too_many_arguments)]
-pub fn respects_rules_through_lexing(
-    source_code: &str,
-    path: Option<&Path>,
-    break_on_empty_line: bool,
-    succeed_if_no_docstring: bool,
-    succeed_if_no_args_in_docstring: bool,
-    succeed_if_docstrings_are_not_typed: bool,
-    skip_args_and_kwargs: bool,
-    docstyle: DocstringStyle,
-) -> bool {
-    let mut lexer = Lexer::new(source_code);
+    Args:
+        x: First var.
+    """
+"#;
 
-    let mut success = true;
-    let mut params = Vec::with_capacity(8);
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics =
+                check_source(source_code, None, checker, &CheckOptions::default(), None).unwrap();
 
-    while let Some(info) = get_next_function_info(&mut lexer, &mut params, skip_args_and_kwargs) {
-        if !is_function_info_valid(
-            &info,
-            path,
-            break_on_empty_line,
-            succeed_if_no_docstring,
-            succeed_if_no_args_in_docstring,
-            succeed_if_docstrings_are_not_typed,
-            skip_args_and_kwargs,
-            docstyle,
-        ) {
-            success = false;
+            assert_eq!(diagnostics.len(), 2);
+            assert_eq!(diagnostics[0].rule, Rule::MissingArg);
+            assert_eq!(diagnostics[0].message, "`x` missing from docstring");
+            assert_eq!(diagnostics[1].rule, Rule::MissingArg);
+            assert_eq!(diagnostics[1].message, "`self` missing from docstring");
         }
     }
 
-    success
-}
+    #[test]
+    fn test_receivers_after_unindented_docstrings() {
+        let source_code = r#"class A:
+    def f(self, x):
+        """Hey.
 
-/// Checks if a given function respects the specified rules.
-#[allow(clippy::too_many_arguments)]
-fn is_function_info_valid(
-    info: &FunctionInfo,
-    path: Option<&Path>,
-    break_on_empty_line: bool,
-    succeed_if_no_docstring: bool,
-    succeed_if_no_args_in_docstring: bool,
-    succeed_if_docstrings_are_not_typed: bool,
-    skip_args_and_kwargs: bool,
-    docstyle: DocstringStyle,
-) -> bool {
-    let path = path.map_or("".to_string(), |x| x.to_string_lossy().to_string() + ": ");
+Args:
+    x: First var.
+        """
 
-    let Some(docstring) = info.docstring else {
-        if !succeed_if_no_docstring {
-            tracing::event!(
-                Level::ERROR,
-                "{}`{}`: Docstring missing",
-                path,
-                info.function_name
-            );
-        }
+    @classmethod
+    def g(cls, y):
+        """Hey.
 
-        return succeed_if_no_docstring;
-    };
+        Args:
+            y: Second var.
+        """
+"#;
 
-    let args_from_docstring = match docstyle {
-        DocstringStyle::Google => {
-            parse_google_docstring(docstring, break_on_empty_line, skip_args_and_kwargs)
-        }
-        DocstringStyle::Numpy => {
-            parse_numpy_docstring(docstring, break_on_empty_line, skip_args_and_kwargs)
-        }
-        DocstringStyle::AutoDetect => {
-            parse_google_docstring(docstring, break_on_empty_line, skip_args_and_kwargs).or(
-                parse_numpy_docstring(docstring, break_on_empty_line, skip_args_and_kwargs),
-            )
-        }
-    };
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics =
+                check_source(source_code, None, checker, &CheckOptions::default(), None).unwrap();
 
-    let Some(args_from_docstring) = args_from_docstring else {
-        if !succeed_if_no_args_in_docstring {
-            tracing::event!(
-                Level::ERROR,
-                "{}`{}`: Args missing from docstring",
-                path,
-                info.function_name
-            );
+            assert!(diagnostics.is_empty(), "{diagnostics:?}");
         }
+    }
 
-        return succeed_if_no_args_in_docstring;
-    };
-
-    if succeed_if_docstrings_are_not_typed {
-        let is_valid = if args_from_docstring.len() == info.params.len() {
-            args_from_docstring
-                .iter()
-                .zip(info.params)
-                .all(|((param1, type1), (param2, type2))| match (type1, type2) {
-                    (Some(type1), Some(type2)) => param1 == param2 && type1 == type2,
-                    (_, _) => param1 == param2,
-                })
-        } else {
-            false
+    #[test]
+    fn test_ignore_params() {
+        let options = CheckOptions {
+            ignore_params: vec!["context".to_string()],
+            ..Default::default()
         };
 
-        if !is_valid {
-            tracing::event!(
-                Level::ERROR,
-                "{}`{}`: Args from function: {:?}. Args from docstring: {:?}",
-                path,
-                info.function_name,
-                info.params,
-                args_from_docstring,
-            );
-        }
+        let source_code = r#"class A:
+    def f(self, context, x):
+        """Hey.
 
-        is_valid
-    } else {
-        let is_valid = args_from_docstring == info.params;
+        Args:
+            x: First var.
+        """
 
-        if !is_valid {
-            tracing::event!(
-                Level::ERROR,
-                "Docstring args not matching at function {}",
-                info.function_name
-            );
+def g(y):
+    """Hey.
+
+    Args:
+        context: Injected by the framework.
+        y: Second var.
+    """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            assert!(check_source(source_code, None, checker, &options, None)
+                .unwrap()
+                .is_empty());
+
+            let diagnostics =
+                check_source(source_code, None, checker, &CheckOptions::default(), None).unwrap();
+
+            assert_eq!(diagnostics.len(), 2);
+            assert_eq!(diagnostics[0].rule, Rule::MissingArg);
+            assert_eq!(diagnostics[1].rule, Rule::ExtraArg);
         }
 
-        is_valid
+        // An ignored param documented twice is not a duplicate, nor hides the other errors.
+        let documented_twice = r#"def h(context, y, z):
+    """Hey.
+
+    Args:
+        context: Injected by the framework.
+        context: Still injected by the framework.
+        y: Second var.
+    """
+"#;
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics =
+                check_source(documented_twice, None, checker, &options, None).unwrap();
+
+            assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+            assert_eq!(diagnostics[0].rule, Rule::MissingArg);
+            assert_eq!(diagnostics[0].message, "`z` missing from docstring");
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use tracing_test::traced_test;
+    #[test]
+    fn test_spans() {
+        let source_code = r#"import os
 
-    use crate::ast_parsing::FunctionLocation;
+def f(x: int,
+      y) -> int:  # type: ignore
+    """Hey.
 
-    use super::*;
+    Args:
+        x: Something.
+    """
+    return x
 
-    fn get_parser() -> Parser {
-        let mut parser = Parser::new();
+def g(a=(1, 2)):
+    '''Hey.
 
-        parser
-            .set_language(&tree_sitter_python::language())
-            .expect("should be able to load Python grammar");
+    Args:
+        b: Something.
+    '''
+"#;
 
-        parser
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let diagnostics =
+                check_source(source_code, None, checker, &CheckOptions::default(), None).unwrap();
+            let text =
+                |range: &Option<std::ops::Range<usize>>| &source_code[range.clone().unwrap()];
+
+            assert!(!diagnostics.is_empty());
+
+            for diagnostic in &diagnostics {
+                let spans = &diagnostic.spans;
+
+                if diagnostic.function_row == Some(2) {
+                    assert_eq!(text(&spans.signature), "def f(x: int,\n      y) -> int:");
+                    assert_eq!(text(&spans.parameters), "(x: int,\n      y)");
+                    assert_eq!(
+                        text(&spans.docstring),
+                        "\"\"\"Hey.\n\n    Args:\n        x: Something.\n    \"\"\""
+                    );
+                } else {
+                    assert_eq!(text(&spans.signature), "def g(a=(1, 2)):");
+                    assert_eq!(text(&spans.parameters), "(a=(1, 2))");
+                    assert_eq!(
+                        text(&spans.docstring),
+                        "'''Hey.\n\n    Args:\n        b: Something.\n    '''"
+                    );
+                }
+            }
+        }
     }
 
     #[test]
-    #[traced_test]
-    fn test_success_no_docstring() {
-        let function_info = FunctionInfo {
-            params: &[("x", Some("int")), ("y", Some("str"))],
-            docstring: None,
-            function_name: FunctionLocation::Name(""),
+    fn test_suppression() {
+        let source_code = r#"# pystaleds: ignore
+def above(x):
+    pass
+
+def same_line(x):  # noqa  # pystaleds: ignore
+    pass
+
+def multiline(  # pystaleds: ignore
+    x,
+):
+    pass
+
+@decorator
+# pystaleds: ignore
+def decorated(x):
+    pass
+
+# pystaleds: ignore
+
+def not_adjacent(x):
+    pass
+
+def other_comment(x):  # pystaleds: ignored
+    pass
+"#;
+
+        let options = CheckOptions {
+            succeed_if_no_docstring: false,
+            ..Default::default()
         };
 
-        assert!(is_function_info_valid(
-            &function_info,
-            None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::AutoDetect
-        ));
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let report = check_source_report(source_code, None, checker, &options, None).unwrap();
 
-        assert!(!is_function_info_valid(
-            &function_info,
-            None,
-            false,
-            false,
-            true,
-            true,
-            true,
-            DocstringStyle::AutoDetect
-        ));
+            assert_eq!(report.suppressed, 4);
+            assert_eq!(report.functions.len(), 2);
+            assert_eq!(report.diagnostics.len(), 2);
+        }
     }
 
     #[test]
-    #[traced_test]
-    fn test_out_of_order() {
-        let function_info = FunctionInfo {
-            params: &[("x", Some("int")), ("y", Some("str"))],
-            docstring: Some(
-                r#"
-                """
-                Hello!
+    fn test_rule_suppression() {
+        let source_code = r#"def f(x: int, y):  # pystaleds: ignore[PSD203]
+    """Hey.
 
-                Args:
-                    y: Nope.
-                    x: Hehehe.
-                """"#,
-            ),
-            function_name: FunctionLocation::Name(""),
-        };
+    Args:
+        x (str): Something.
+    """
 
-        assert!(!is_function_info_valid(
-            &function_info,
-            None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+# pystaleds: ignore[psd2, PSD999]
+def g(x: int, y):
+    """Hey.
 
-        let function_info = FunctionInfo {
-            params: &[("x", Some("int")), ("y", Some("str"))],
-            docstring: Some(
-                r#"
-                """
-                Hello!
+    Args:
+        x (str): Something.
+    """
+"#;
 
-                Args:
-                    x: Hehehe.
-                    y: Nope.
-                """"#,
-            ),
-            function_name: FunctionLocation::Name(""),
-        };
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let report =
+                check_source_report(source_code, None, checker, &CheckOptions::default(), None)
+                    .unwrap();
+            let rules: Vec<_> = report.diagnostics.iter().map(|d| d.rule).collect();
 
-        assert!(is_function_info_valid(
-            &function_info,
-            None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            assert_eq!(rules, [Rule::MissingArg]);
+            assert_eq!(report.suppressed, 0);
+            assert_eq!(report.functions.len(), 2);
+        }
 
-        let function_info = FunctionInfo {
-            params: &[("x", Some("int")), ("y", Some("str"))],
-            docstring: Some(
-                r#"
-                """
-                Hello!
+        assert_eq!(
+            crate::ast_parsing::parse_suppression("# noqa  # pystaleds: ignore[PSD203, PSD999, ]"),
+            Some(Suppression::Rules {
+                selectors: vec!["PSD203".parse().unwrap()],
+                unknown: vec!["PSD999".to_string()],
+            })
+        );
+        assert_eq!(
+            crate::ast_parsing::parse_suppression("# pystaleds: ignored"),
+            None
+        );
+    }
 
-                Parameters
-                ----------
-                y
-                    Nope
-                x
-                    Hehehe
-                """"#,
-            ),
-            function_name: FunctionLocation::Name(""),
+    #[test]
+    fn test_skip_file() {
+        let source_code =
+            "#!/usr/bin/env python\n# noqa  # pystaleds: skip-file\ndef f(x):\n    pass\n";
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            assert!(matches!(
+                check_source(source_code, None, checker, &CheckOptions::default(), None),
+                Err(CheckError::SkippedFile)
+            ));
+
+            let late = format!("{}{source_code}", "\n".repeat(HEADER_LINES));
+            assert!(check_source(&late, None, checker, &CheckOptions::default(), None).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_skip_generated() {
+        let source_code = "# Generated by Django 5.0 on 2024-01-01 00:00\ndef f(x):\n    pass\n";
+        let strict = CheckOptions {
+            succeed_if_no_docstring: false,
+            ..Default::default()
         };
 
-        assert!(!is_function_info_valid(
-            &function_info,
-            None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Numpy
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            assert!(matches!(
+                check_source(source_code, None, checker, &strict, None),
+                Err(CheckError::Generated(marker)) if marker == "Generated by"
+            ));
+
+            let late = format!("{}{source_code}", "\n".repeat(HEADER_LINES));
+            assert_eq!(
+                check_source(&late, None, checker, &strict, None)
+                    .unwrap()
+                    .len(),
+                1
+            );
+
+            let options = CheckOptions {
+                skip_generated: false,
+                ..strict.clone()
+            };
+            assert_eq!(
+                check_source(source_code, None, checker, &options, None)
+                    .unwrap()
+                    .len(),
+                1
+            );
+
+            let options = CheckOptions {
+                generated_markers: vec!["# AUTOGENERATED".to_string()],
+                ..strict.clone()
+            };
+            assert_eq!(
+                check_source(source_code, None, checker, &options, None)
+                    .unwrap()
+                    .len(),
+                1
+            );
+        }
+
+        let header = read_header("# @generated\n".repeat(20).as_bytes()).unwrap();
+        assert_eq!(header.lines().count(), HEADER_LINES);
+        assert!(matches!(
+            check_header(&header, &strict),
+            Err(CheckError::Generated(marker)) if marker == "@generated"
         ));
+    }
 
-        let function_info = FunctionInfo {
-            params: &[("x", Some("int")), ("y", Some("str"))],
-            docstring: Some(
-                r#"
-                """
-                Hello!
+    #[test]
+    fn test_rule_selection() {
+        let source_code = r#"def f(x: int, y):
+    """Hey.
 
-                Parameters
-                ----------
-                x
-                    Hehehe
-                y
-                    Nope.
+    Args:
+        x (str): First var.
+    """
 
-                Returns
-                ------
-                ...
-                """"#,
-            ),
-            function_name: FunctionLocation::Name(""),
-        };
+def g(z):
+    pass
+"#;
 
-        assert!(is_function_info_valid(
-            &function_info,
-            None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Numpy
-        ));
+        let selectors = |codes: &[&str]| codes.iter().map(|code| code.parse().unwrap()).collect();
+
+        for checker in [CompliancyChecker::TreeSitter, CompliancyChecker::Lexer] {
+            let rules = |select: &[&str], ignore: &[&str]| {
+                let options = CheckOptions {
+                    succeed_if_no_docstring: false,
+                    rules: RuleSelection {
+                        select: selectors(select),
+                        ignore: selectors(ignore),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+
+                check_source(source_code, None, checker, &options, None)
+                    .unwrap()
+                    .into_iter()
+                    .map(|diagnostic| diagnostic.rule)
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(
+                rules(&[], &[]),
+                vec![
+                    Rule::ArgTypeMismatch,
+                    Rule::MissingArg,
+                    Rule::MissingDocstring
+                ]
+            );
+            assert_eq!(rules(&["PSD2"], &["PSD203"]), vec![Rule::MissingArg]);
+            assert_eq!(rules(&[], &["PSD101"]).len(), 2);
+            assert!(rules(&["PSD102"], &[]).is_empty());
+        }
     }
 
     #[test]
     #[traced_test]
     fn test_check_function_info() {
         let function_info = FunctionInfo {
-            params: &[("x", Some("int")), ("y", Some("str"))],
+            params: &[
+                Param::new("x", Some("int"), false),
+                Param::new("y", Some("str"), false),
+            ],
             docstring: Some(
                 r#"
                 """
@@ -397,36 +5170,39 @@ mod tests {
                 """"#,
             ),
             function_name: FunctionLocation::Name(""),
+            ..Default::default()
         };
 
-        assert!(is_function_info_valid(
+        assert!(check_function_info(
             &function_info,
-            None,
-            false,
-            false,
-            false,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
-
-        assert!(is_function_info_valid(
+            &CheckOptions {
+                succeed_if_no_docstring: false,
+                succeed_if_no_args_in_docstring: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .is_empty());
+
+        assert!(check_function_info(
             &function_info,
-            None,
-            false,
-            false,
-            false,
-            true,
-            true,
-            DocstringStyle::AutoDetect
-        ));
+            &CheckOptions {
+                succeed_if_no_docstring: false,
+                succeed_if_no_args_in_docstring: false,
+                ..Default::default()
+            },
+        )
+        .is_empty());
     }
 
     #[test]
     #[traced_test]
     fn ignore_edge_case() {
         let function_info = FunctionInfo {
-            params: &[("x", Some("int")), ("y", Some("str"))],
+            params: &[
+                Param::new("x", Some("int"), false),
+                Param::new("y", Some("str"), false),
+            ],
             docstring: Some(
                 r#"
                 """
@@ -442,18 +5218,19 @@ mod tests {
                 """"#,
             ),
             function_name: FunctionLocation::Name(""),
+            ..Default::default()
         };
 
-        assert!(is_function_info_valid(
+        assert!(check_function_info(
             &function_info,
-            None,
-            false,
-            false,
-            false,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                succeed_if_no_docstring: false,
+                succeed_if_no_args_in_docstring: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .is_empty());
     }
 
     #[test]
@@ -477,24 +5254,23 @@ mod tests {
             source_code,
             None,
             None,
-            false,
-            true,
-            true,
-            true,
-            false,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                skip_args_and_kwargs: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(respects_rules_through_lexing(
             source_code,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         let source_code = r#"def sub(x, y):
     """This is a multi-line docstring.
@@ -511,24 +5287,22 @@ mod tests {
             source_code,
             None,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(!respects_rules_through_lexing(
             source_code,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         let source_code = r#"def sub(x, y):
     """This is a multi-line docstring.
@@ -547,24 +5321,22 @@ mod tests {
             source_code,
             None,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(!respects_rules_through_lexing(
             source_code,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
     }
 
     #[test]
@@ -596,24 +5368,26 @@ def other_func(x,y,z):
             source_code,
             None,
             None,
-            false,
-            false,
-            false,
-            true,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                succeed_if_no_docstring: false,
+                succeed_if_no_args_in_docstring: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(!respects_rules_through_lexing(
             source_code,
             None,
-            false,
-            false,
-            false,
-            true,
-            true,
-            DocstringStyle::Google,
-        ))
+            &CheckOptions {
+                succeed_if_no_docstring: false,
+                succeed_if_no_args_in_docstring: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap())
     }
 
     #[test]
@@ -645,24 +5419,24 @@ def other_func(x,y,z):
             source_code,
             None,
             None,
-            false,
-            false,
-            true,
-            true,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                succeed_if_no_docstring: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(respects_rules_through_lexing(
             source_code,
             None,
-            false,
-            false,
-            true,
-            true,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                succeed_if_no_docstring: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
     }
 
     #[test]
@@ -688,24 +5462,24 @@ def other_func(x,y,z):
             source_code,
             None,
             None,
-            false,
-            true,
-            true,
-            false,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                succeed_if_docstrings_are_not_typed: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(!respects_rules_through_lexing(
             source_code,
             None,
-            false,
-            true,
-            true,
-            false,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                succeed_if_docstrings_are_not_typed: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         let source_code = r#"def add(x: int,y):
     """This is a docstring."""
@@ -725,24 +5499,24 @@ def other_func(x,y,z):
             source_code,
             None,
             None,
-            false,
-            true,
-            true,
-            false,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                succeed_if_docstrings_are_not_typed: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(respects_rules_through_lexing(
             source_code,
             None,
-            false,
-            true,
-            true,
-            false,
-            true,
-            DocstringStyle::Google,
-        ));
+            &CheckOptions {
+                succeed_if_docstrings_are_not_typed: false,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
     }
 
     #[test]
@@ -757,24 +5531,22 @@ def other_func(x,y,z):
             &source_code,
             None,
             Some(&path),
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(respects_rules_through_lexing(
             &source_code,
             Some(&path),
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         let path = std::path::PathBuf::from("test_folder/test_cp.py");
         let source_code = std::fs::read_to_string("test_folder/test_cp.py").unwrap();
@@ -784,24 +5556,22 @@ def other_func(x,y,z):
             &source_code,
             None,
             Some(&path),
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(!respects_rules_through_lexing(
             &source_code,
             Some(&path),
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
     }
 
     #[test]
@@ -826,24 +5596,22 @@ def other_func(x,y,z):
             source_code,
             None,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(respects_rules_through_lexing(
             source_code,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
     }
 
     #[test]
@@ -868,48 +5636,46 @@ def other_func(x,y,z):
             source_code,
             None,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(!respects_rules_through_lexing(
             source_code,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(respects_rules(
             &mut parser,
             source_code,
             None,
             None,
-            true,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                break_on_empty_line: true,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(respects_rules_through_lexing(
             source_code,
             None,
-            true,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Google
-        ));
+            &CheckOptions {
+                break_on_empty_line: true,
+                docstyle: DocstringStyle::Google,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         let source_code = r#"def f(a, x=2):  # Comment to try and screw up the lexer.
     """
@@ -932,48 +5698,46 @@ def other_func(x,y,z):
             source_code,
             None,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Numpy
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Numpy,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(!respects_rules_through_lexing(
             source_code,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Numpy
-        ));
+            &CheckOptions {
+                docstyle: DocstringStyle::Numpy,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(respects_rules(
             &mut parser,
             source_code,
             None,
             None,
-            true,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Numpy
-        ));
+            &CheckOptions {
+                break_on_empty_line: true,
+                docstyle: DocstringStyle::Numpy,
+                ..Default::default()
+            },
+        )
+        .unwrap());
 
         assert!(respects_rules_through_lexing(
             source_code,
             None,
-            true,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::Numpy
-        ));
+            &CheckOptions {
+                break_on_empty_line: true,
+                docstyle: DocstringStyle::Numpy,
+                ..Default::default()
+            },
+        )
+        .unwrap());
     }
 
     #[test]
@@ -1105,24 +5869,13 @@ def other_func(x,y,z):
             source_code,
             None,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::AutoDetect
-        ));
+            &CheckOptions::default(),
+        )
+        .unwrap());
 
-        assert!(respects_rules_through_lexing(
-            source_code,
-            None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::AutoDetect
-        ));
+        assert!(
+            respects_rules_through_lexing(source_code, None, &CheckOptions::default(),).unwrap()
+        );
     }
 
     #[test]
@@ -1160,23 +5913,12 @@ def other_func(x,y,z):
             source_code,
             None,
             None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::AutoDetect
-        ));
+            &CheckOptions::default(),
+        )
+        .unwrap());
 
-        assert!(!respects_rules_through_lexing(
-            source_code,
-            None,
-            false,
-            true,
-            true,
-            true,
-            true,
-            DocstringStyle::AutoDetect
-        ));
+        assert!(
+            !respects_rules_through_lexing(source_code, None, &CheckOptions::default(),).unwrap()
+        );
     }
 }
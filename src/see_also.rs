@@ -0,0 +1,348 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use tree_sitter::{Node, Parser};
+
+use crate::diagnostics::{Diagnostic, Rule, Severity, Spans};
+use crate::parsing::parse_see_also_section;
+use crate::rules_checking::walk_rec;
+
+/// Qualified names of the modules, functions and classes defined under the checked
+/// paths, used to resolve the references in "See Also" sections.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    names: HashSet<String>,
+    /// Top-level packages (or modules) in which the names are defined.
+    packages: HashSet<String>,
+}
+
+impl SymbolTable {
+    /// Adds a qualified name to the table.
+    pub fn insert(&mut self, name: String) {
+        let package = name.split('.').next().unwrap_or_default();
+
+        self.packages.insert(package.to_string());
+        self.names.insert(name);
+    }
+
+    /// Merges the names of another table into this one.
+    pub fn extend(&mut self, other: SymbolTable) {
+        self.names.extend(other.names);
+        self.packages.extend(other.packages);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    /// Whether the name refers to something under the checked paths, i.e. if its
+    /// top-level package is one of the collected ones.
+    fn is_local(&self, name: &str) -> bool {
+        let package = name.split('.').next().unwrap_or_default();
+
+        self.packages.contains(package)
+    }
+}
+
+/// Determines the qualified name of the module defined by a file, considering that every
+/// parent folder with an `__init__.py` file is a package.
+///
+/// Returns `None` if the path is not a Python file.
+pub fn module_name(path: &Path) -> Option<String> {
+    if path.extension()? != "py" {
+        return None;
+    }
+
+    let mut components = Vec::new();
+
+    let stem = path.file_stem()?.to_str()?;
+
+    if stem != "__init__" {
+        components.push(stem.to_string());
+    }
+
+    let mut folder = path
+        .canonicalize()
+        .ok()
+        .and_then(|path| path.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+
+    while folder.join("__init__.py").is_file() {
+        let Some(name) = folder.file_name().and_then(|name| name.to_str()) else {
+            break;
+        };
+
+        components.push(name.to_string());
+
+        if !folder.pop() {
+            break;
+        }
+    }
+
+    components.reverse();
+
+    (!components.is_empty()).then(|| components.join("."))
+}
+
+/// Name of a function or class definition.
+fn definition_name<'a>(node: &Node, source_code: &'a str) -> Option<&'a str> {
+    if node.kind() != "function_definition" && node.kind() != "class_definition" {
+        return None;
+    }
+
+    node.child_by_field_name("name")?
+        .utf8_text(source_code.as_bytes())
+        .ok()
+}
+
+/// Qualified name of a function or class definition, e.g. `module.Class.method`.
+fn qualified_name(node: &Node, source_code: &str, module: &str) -> Option<String> {
+    let mut names = vec![definition_name(node, source_code)?];
+
+    let mut parent = node.parent();
+
+    while let Some(node) = parent {
+        if let Some(name) = definition_name(&node, source_code) {
+            names.push(name);
+        }
+
+        parent = node.parent();
+    }
+
+    names.push(module);
+    names.reverse();
+
+    Some(names.join("."))
+}
+
+/// Collects the qualified names of the module, its parent packages, and every function
+/// and class defined in the source code.
+pub fn collect_symbols(parser: &mut Parser, source_code: &str, module: &str) -> SymbolTable {
+    let tree = parser
+        .parse(source_code, None)
+        .expect("parser should be ready to parse");
+
+    let mut symbols = SymbolTable::default();
+
+    let mut package = String::new();
+
+    for component in module.split('.') {
+        if !package.is_empty() {
+            package.push('.');
+        }
+
+        package.push_str(component);
+        symbols.insert(package.clone());
+    }
+
+    walk_rec(&mut tree.walk(), &mut |node| {
+        if let Some(name) = qualified_name(node, source_code, module) {
+            symbols.insert(name);
+        }
+    });
+
+    symbols
+}
+
+/// Checks that the names referenced in the "See Also" sections of the docstrings of the
+/// source code are defined somewhere under the checked paths.
+///
+/// A name is resolved against the scope in which the docstring is and every scope
+/// enclosing it, up to the top-level package. Dotted names whose top-level package is not
+/// under the checked paths (e.g., from the standard library) are skipped, while undotted
+/// names are expected to be defined in some enclosing scope.
+pub fn check_see_also(
+    parser: &mut Parser,
+    source_code: &str,
+    path: Option<&Path>,
+    module: &str,
+    symbols: &SymbolTable,
+) -> Vec<Diagnostic> {
+    let tree = parser
+        .parse(source_code, None)
+        .expect("parser should be ready to parse");
+
+    let mut diagnostics = Vec::new();
+
+    walk_rec(&mut tree.walk(), &mut |node| {
+        let Some(name) = definition_name(node, source_code) else {
+            return;
+        };
+
+        let Some(docstring) = node
+            .child_by_field_name("body")
+            .and_then(|body| body.named_child(0))
+            .filter(|statement| statement.kind() == "expression_statement")
+            .and_then(|statement| statement.named_child(0))
+            .filter(|string| string.kind() == "string")
+        else {
+            return;
+        };
+
+        let Ok(text) = docstring.utf8_text(source_code.as_bytes()) else {
+            return;
+        };
+
+        let scope = qualified_name(node, source_code, module).unwrap_or_default();
+
+        for (line, reference) in parse_see_also_section(text) {
+            if resolves(reference, &scope, symbols) {
+                continue;
+            }
+
+            let (first, _) = reference.split_once('.').unwrap_or((reference, ""));
+
+            let is_checked =
+                first == reference || symbols.is_local(first) || resolves(first, &scope, symbols);
+
+            if is_checked {
+                diagnostics.push(Diagnostic {
+                    rule: Rule::StaleSeeAlso,
+                    path: path.map(Path::to_path_buf),
+                    function: name.to_string(),
+                    row: Some(docstring.start_position().row + line),
+                    column: None,
+                    function_row: None,
+                    message: format!("\"See Also\" references `{reference}`, which is not defined"),
+                    severity: Severity::Error,
+                    spans: Spans::default(),
+                    entries: None,
+                    fix: None,
+                });
+            }
+        }
+    });
+
+    diagnostics
+}
+
+/// Whether the name is defined in the scope or in any scope enclosing it.
+fn resolves(name: &str, scope: &str, symbols: &SymbolTable) -> bool {
+    if symbols.contains(name) {
+        return true;
+    }
+
+    let mut scope = Some(scope);
+
+    while let Some(current) = scope {
+        if symbols.contains(&format!("{current}.{name}")) {
+            return true;
+        }
+
+        scope = current.rsplit_once('.').map(|(parent, _)| parent);
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_parser() -> Parser {
+        let mut parser = Parser::new();
+
+        parser
+            .set_language(&tree_sitter_python::language())
+            .expect("should be able to load Python grammar");
+
+        parser
+    }
+
+    #[test]
+    fn module_names() {
+        let root = std::env::temp_dir().join("pystaleds_module_names");
+        let package = root.join("src").join("pkg").join("sub");
+
+        std::fs::create_dir_all(&package).unwrap();
+        std::fs::write(root.join("src").join("pkg").join("__init__.py"), "").unwrap();
+        std::fs::write(package.join("__init__.py"), "").unwrap();
+        std::fs::write(package.join("module.py"), "").unwrap();
+
+        assert_eq!(
+            module_name(&package.join("module.py")).as_deref(),
+            Some("pkg.sub.module")
+        );
+        assert_eq!(
+            module_name(&package.join("__init__.py")).as_deref(),
+            Some("pkg.sub")
+        );
+        assert_eq!(module_name(&package.join("module.pyi")), None);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn stale_references() {
+        let helpers = r#"def helper(x):
+    """Hey."""
+    return x
+
+
+class Formatter:
+    def format(self, x):
+        """Hey."""
+        return x
+"#;
+
+        let main = r#"import os
+
+
+def run(x):
+    """Runs.
+
+    See Also
+    --------
+    helpers.helper : Helps.
+    helpers.renamed_helper : Used to help.
+    os.path.join, numpy.sum
+    """
+    return x
+
+
+class Runner:
+    """Runs.
+
+    See Also
+    --------
+    run, helpers.Formatter.format
+    stop
+    """
+
+    def go(self):
+        """Goes.
+
+        See Also
+        --------
+        run
+        """
+"#;
+
+        let mut parser = get_parser();
+
+        let mut symbols = SymbolTable::default();
+        symbols.extend(collect_symbols(&mut parser, helpers, "pkg.helpers"));
+        symbols.extend(collect_symbols(&mut parser, main, "pkg.main"));
+
+        assert!(symbols.contains("pkg"));
+        assert!(symbols.contains("pkg.helpers.Formatter.format"));
+        assert!(symbols.contains("pkg.main.Runner.go"));
+
+        let diagnostics = check_see_also(&mut parser, main, None, "pkg.main", &symbols);
+
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(diagnostics[0].rule, Rule::StaleSeeAlso);
+        assert_eq!(diagnostics[0].function, "run");
+        assert_eq!(diagnostics[0].row, Some(9));
+        assert_eq!(
+            diagnostics[0].message,
+            "\"See Also\" references `helpers.renamed_helper`, which is not defined"
+        );
+
+        assert_eq!(diagnostics[1].function, "Runner");
+        assert_eq!(diagnostics[1].row, Some(21));
+
+        assert!(check_see_also(&mut parser, helpers, None, "pkg.helpers", &symbols).is_empty());
+    }
+}
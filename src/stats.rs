@@ -0,0 +1,748 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+use clap::ValueEnum;
+use tree_sitter::{Parser, Tree};
+
+use crate::diagnostics::Rule;
+use crate::rules_checking::SourceReport;
+
+/// Counts of the files and functions checked in a run.
+///
+/// Files without any function definitions (see [`SourceReport::is_trivial`]) are
+/// classified as trivial, and are left out of the counts reported by [`Stats::summary`]
+/// unless asked otherwise, since they have nothing to be checked and would otherwise
+/// inflate the share of clean files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub files: usize,
+    pub files_with_errors: usize,
+    pub trivial_files: usize,
+    pub trivial_files_with_errors: usize,
+    /// Files that were not checked, since they are not Python files, could not be read,
+    /// have a `# pystaleds: skip-file` comment, are generated or are too large.
+    pub skipped_files: usize,
+    /// Skipped files that are generated, which are also counted in `skipped_files`.
+    pub generated_files: usize,
+    /// Skipped files that are larger than the maximum size, which are also counted in
+    /// `skipped_files`.
+    pub too_large_files: usize,
+    pub functions: usize,
+    pub functions_with_errors: usize,
+    /// Functions left unchecked by a `# pystaleds: ignore` comment.
+    pub suppressed_functions: usize,
+    /// Diagnostics that fail the check.
+    pub violations: usize,
+    /// Diagnostics reported as warnings, which do not fail the check.
+    pub warnings: usize,
+}
+
+impl Stats {
+    /// Records the outcome of checking a file.
+    pub fn record(&mut self, trivial: bool, success: bool) {
+        self.files += 1;
+
+        if !success {
+            self.files_with_errors += 1;
+        }
+
+        if trivial {
+            self.trivial_files += 1;
+
+            if !success {
+                self.trivial_files_with_errors += 1;
+            }
+        }
+    }
+
+    /// Records the functions checked in a file and the violations and warnings found in it.
+    pub fn record_functions(&mut self, report: &SourceReport) {
+        let errors: Vec<_> = report.diagnostics.iter().filter(|d| d.is_error()).collect();
+
+        self.functions += report.functions.len();
+        self.suppressed_functions += report.suppressed;
+        self.functions_with_errors += report
+            .functions
+            .iter()
+            .filter(|function| errors.iter().any(|d| function.matches(d)))
+            .count();
+        self.violations += errors.len();
+        self.warnings += report.diagnostics.len() - errors.len();
+    }
+
+    /// Summary of the counts of files and functions checked (excluding the trivial files
+    /// unless `count_trivial_files`) and of those with errors, along with the skipped
+    /// files, the violations, the warnings and the suppressed functions, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pystaleds::stats::Stats;
+    ///
+    /// let mut stats = Stats {
+    ///     skipped_files: 1,
+    ///     functions: 10,
+    ///     functions_with_errors: 2,
+    ///     violations: 3,
+    ///     ..Default::default()
+    /// };
+    /// stats.record(false, true);
+    /// stats.record(false, false);
+    /// stats.record(true, true);
+    ///
+    /// assert_eq!(
+    ///     stats.summary(false),
+    ///     "Checked 2 files, 1 with errors (1 trivial file not counted), and 10 functions, \
+    ///      2 with errors; skipped 1 file; found 3 violations"
+    /// );
+    /// assert!(stats.summary(true).starts_with("Checked 3 files, 1 with errors, and"));
+    /// ```
+    pub fn summary(&self, count_trivial_files: bool) -> String {
+        let (files, files_with_errors, trivial) = if count_trivial_files || self.trivial_files == 0
+        {
+            (self.files, self.files_with_errors, String::new())
+        } else {
+            (
+                self.files - self.trivial_files,
+                self.files_with_errors - self.trivial_files_with_errors,
+                format!(
+                    " ({} not counted)",
+                    pluralize(self.trivial_files, "trivial file")
+                ),
+            )
+        };
+
+        let suppressed = if self.suppressed_functions == 0 {
+            String::new()
+        } else {
+            format!(" ({} suppressed by comments)", self.suppressed_functions)
+        };
+
+        let reasons: Vec<_> = [
+            (self.generated_files, "generated"),
+            (self.too_large_files, "too large"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, reason)| format!("{count} {reason}"))
+        .collect();
+        let reasons = if reasons.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", reasons.join(", "))
+        };
+
+        let warnings = if self.warnings == 0 {
+            String::new()
+        } else {
+            format!(" and {}", pluralize(self.warnings, "warning"))
+        };
+
+        format!(
+            "Checked {}, {files_with_errors} with errors{trivial}, and {}, {} with \
+             errors{suppressed}; skipped {}{reasons}; found {}{warnings}",
+            pluralize(files, "file"),
+            pluralize(self.functions, "function"),
+            self.functions_with_errors,
+            pluralize(self.skipped_files, "file"),
+            pluralize(self.violations, "violation"),
+        )
+    }
+}
+
+/// Writes a count followed by a noun, in the plural unless the count is one.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::stats::pluralize;
+///
+/// assert_eq!(pluralize(1, "file"), "1 file");
+/// assert_eq!(pluralize(0, "file"), "0 files");
+/// assert_eq!(pluralize(2, "stale entry"), "2 stale entries");
+/// ```
+pub fn pluralize(count: usize, noun: &str) -> String {
+    if count == 1 {
+        return format!("1 {noun}");
+    }
+
+    match noun.strip_suffix('y') {
+        Some(stem) => format!("{count} {stem}ies"),
+        None => format!("{count} {noun}s"),
+    }
+}
+
+/// How the files are grouped in the aggregated stats of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatsGrouping {
+    /// A row per directory, up to a depth.
+    #[value(name = "dir", alias = "directory")]
+    Directory,
+}
+
+/// Counts of the files, functions and violations in a directory, as aggregated by
+/// [`directory_stats`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryStats {
+    pub directory: String,
+    pub files: usize,
+    pub functions: usize,
+    /// Violations of each rule, sorted by count in descending order and then by code.
+    pub rules: Vec<(Rule, usize)>,
+}
+
+impl DirectoryStats {
+    /// Number of violations of any rule.
+    pub fn violations(&self) -> usize {
+        self.rules.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// Aggregates the checked files, their functions and their violations by the directory
+/// of each file up to `depth` levels, such as `src/pkg` for `src/pkg/sub/module.py` at
+/// depth 2, with `.` for the files that are not in any. The directories are sorted by
+/// their number of violations in descending order, and then by name.
+///
+/// Only the errors count as violations, as in [`Stats`].
+pub fn directory_stats(reports: &[SourceReport], depth: usize) -> Vec<DirectoryStats> {
+    let mut directories: BTreeMap<String, DirectoryStats> = BTreeMap::new();
+
+    for report in reports {
+        let name = directory_prefix(report.path.as_deref(), depth);
+        let directory = directories
+            .entry(name.clone())
+            .or_insert_with(|| DirectoryStats {
+                directory: name,
+                files: 0,
+                functions: 0,
+                rules: Vec::new(),
+            });
+
+        directory.files += 1;
+        directory.functions += report.functions.len();
+
+        for diagnostic in report.diagnostics.iter().filter(|d| d.is_error()) {
+            match directory
+                .rules
+                .iter_mut()
+                .find(|(rule, _)| *rule == diagnostic.rule)
+            {
+                Some((_, count)) => *count += 1,
+                None => directory.rules.push((diagnostic.rule, 1)),
+            }
+        }
+    }
+
+    let mut directories: Vec<DirectoryStats> = directories.into_values().collect();
+
+    for directory in &mut directories {
+        directory
+            .rules
+            .sort_by_key(|(rule, count)| (std::cmp::Reverse(*count), rule.code()));
+    }
+
+    // The sort is stable, so the ones with the same count keep the order of their names.
+    directories.sort_by_key(|directory| std::cmp::Reverse(directory.violations()));
+
+    directories
+}
+
+/// Renders the stats of the directories as a table with a header and a row per
+/// directory, whose violations are broken down by rule, as in `PSD201 (10), PSD202 (5)`.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::diagnostics::Rule;
+/// use pystaleds::stats::{directory_table, DirectoryStats};
+///
+/// let directories = [
+///     DirectoryStats {
+///         directory: "src".to_string(),
+///         files: 12,
+///         functions: 80,
+///         rules: vec![(Rule::MissingArg, 10), (Rule::ExtraArg, 5)],
+///     },
+///     DirectoryStats {
+///         directory: "tests".to_string(),
+///         files: 3,
+///         functions: 9,
+///         rules: Vec::new(),
+///     },
+/// ];
+///
+/// assert_eq!(
+///     directory_table(&directories),
+///     "Directory  Files  Functions  Violations  Rules\n\
+///      src           12         80          15  PSD201 (10), PSD202 (5)\n\
+///      tests          3          9           0"
+/// );
+/// ```
+pub fn directory_table(directories: &[DirectoryStats]) -> String {
+    let header = "Directory";
+    let width = directories
+        .iter()
+        .map(|directory| directory.directory.len())
+        .chain([header.len()])
+        .max()
+        .unwrap_or_default();
+
+    let rows = directories.iter().map(|directory| {
+        let rules: Vec<_> = directory
+            .rules
+            .iter()
+            .map(|(rule, count)| format!("{} ({count})", rule.code()))
+            .collect();
+        let row = format!(
+            "{:width$}  {:>5}  {:>9}  {:>10}  {}",
+            directory.directory,
+            directory.files,
+            directory.functions,
+            directory.violations(),
+            rules.join(", ")
+        );
+
+        row.trim_end().to_string()
+    });
+
+    std::iter::once(format!(
+        "{header:width$}  Files  Functions  Violations  Rules"
+    ))
+    .chain(rows)
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// How the violations are grouped in the statistics of a run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatisticsGrouping {
+    /// A row per rule.
+    #[default]
+    Rule,
+    /// A row per top-level directory and rule.
+    Directory,
+}
+
+/// Number of violations of a rule, in a top-level directory if grouped by them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleCount {
+    pub directory: Option<String>,
+    pub rule: Rule,
+    pub count: usize,
+}
+
+/// Counts the violations of each rule in the reports, sorted by count in descending order
+/// and then by directory and code, so that they are the same across runs.
+///
+/// With [`StatisticsGrouping::Directory`], the violations are counted separately for each
+/// top-level directory, with `.` for the files that are not in any.
+pub fn rule_counts(reports: &[SourceReport], grouping: StatisticsGrouping) -> Vec<RuleCount> {
+    let mut counts: BTreeMap<(Option<String>, &str), (Rule, usize)> = BTreeMap::new();
+
+    for report in reports {
+        let directory = match grouping {
+            StatisticsGrouping::Rule => None,
+            StatisticsGrouping::Directory => Some(directory_prefix(report.path.as_deref(), 1)),
+        };
+
+        for diagnostic in &report.diagnostics {
+            counts
+                .entry((directory.clone(), diagnostic.rule.code()))
+                .or_insert((diagnostic.rule, 0))
+                .1 += 1;
+        }
+    }
+
+    let mut counts: Vec<RuleCount> = counts
+        .into_iter()
+        .map(|((directory, _), (rule, count))| RuleCount {
+            directory,
+            rule,
+            count,
+        })
+        .collect();
+
+    // The sort is stable, so the ones with the same count keep the order of the map.
+    counts.sort_by_key(|count| std::cmp::Reverse(count.count));
+
+    counts
+}
+
+/// Renders the counts as a table with a row per count, as in
+/// `    12  src  PSD201  missing-arg`.
+///
+/// # Examples
+///
+/// ```rust
+/// use pystaleds::diagnostics::Rule;
+/// use pystaleds::stats::{statistics_table, RuleCount};
+///
+/// let counts = [
+///     RuleCount { directory: None, rule: Rule::MissingArg, count: 12 },
+///     RuleCount { directory: None, rule: Rule::MissingDocstring, count: 3 },
+/// ];
+///
+/// assert_eq!(
+///     statistics_table(&counts),
+///     "12  PSD201  missing-arg\n 3  PSD101  missing-docstring"
+/// );
+/// ```
+pub fn statistics_table(counts: &[RuleCount]) -> String {
+    let count_width = counts
+        .iter()
+        .map(|count| count.count.to_string().len())
+        .max()
+        .unwrap_or_default();
+    let directory_width = counts
+        .iter()
+        .filter_map(|count| count.directory.as_ref().map(String::len))
+        .max();
+
+    counts
+        .iter()
+        .map(|count| {
+            let directory = match (&count.directory, directory_width) {
+                (Some(directory), Some(width)) => format!("{directory:width$}  "),
+                _ => String::new(),
+            };
+
+            format!(
+                "{:>count_width$}  {directory}{}  {}",
+                count.count,
+                count.rule.code(),
+                count.rule.id()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// First `depth` directories of the path of a file, or fewer if it is not as deep, or `.`
+/// if it is not in any.
+fn directory_prefix(path: Option<&Path>, depth: usize) -> String {
+    let Some(directory) = path.and_then(Path::parent) else {
+        return ".".to_string();
+    };
+
+    let components: Vec<_> = directory
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir | Component::RootDir))
+        .take(depth)
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+
+    if components.is_empty() {
+        ".".to_string()
+    } else {
+        components.join("/")
+    }
+}
+
+/// Determines if a file is an `__init__.py` whose only statements are imports,
+/// assignments (such as `__all__`) and a docstring, given its tree if it was already
+/// parsed. The source code is only parsed otherwise, and only for `__init__.py` files.
+pub fn is_trivial_init(path: &Path, source_code: &str, tree: Option<&Tree>) -> bool {
+    if path.file_name().and_then(|name| name.to_str()) != Some("__init__.py") {
+        return false;
+    }
+
+    let parsed;
+    let tree = match tree {
+        Some(tree) => tree,
+        None => {
+            parsed = python_parser()
+                .parse(source_code, None)
+                .expect("parser should be ready to parse");
+            &parsed
+        }
+    };
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+
+    let is_trivial = root
+        .named_children(&mut cursor)
+        .all(|statement| match statement.kind() {
+            "import_statement"
+            | "import_from_statement"
+            | "future_import_statement"
+            | "comment" => true,
+            "expression_statement" => statement.named_child(0).is_some_and(|expression| {
+                matches!(
+                    expression.kind(),
+                    "assignment" | "augmented_assignment" | "string"
+                )
+            }),
+            _ => false,
+        });
+
+    is_trivial
+}
+
+fn python_parser() -> Parser {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_python::language())
+        .expect("should be able to load Python grammar");
+
+    parser
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules_checking::{check_source_report, CompliancyChecker};
+
+    use super::*;
+
+    const SHIM_INIT: &str = r#""""Package."""
+
+from __future__ import annotations
+
+from .a import f  # Re-exported.
+import pkg.b as b
+
+__all__ = ["f", "b"]
+__all__ += ["g"]
+"#;
+
+    const SUBSTANTIVE_INIT: &str = r#"from .a import f
+
+__all__ = ["f"]
+
+
+def g(x):
+    """Hey.
+
+    Args:
+        x: Something.
+    """
+    return f(x)
+"#;
+
+    fn is_trivial(source_code: &str, checker: CompliancyChecker) -> bool {
+        check_source_report(source_code, None, checker, &Default::default(), None)
+            .unwrap()
+            .is_trivial()
+    }
+
+    #[test]
+    fn trivial_files() {
+        for checker in [CompliancyChecker::Lexer, CompliancyChecker::TreeSitter] {
+            assert!(is_trivial(SHIM_INIT, checker));
+            assert!(!is_trivial(SUBSTANTIVE_INIT, checker));
+            // Functions that are not checked are still defined.
+            assert!(!is_trivial(
+                "def f(x):  # pystaleds: ignore\n    pass\n",
+                checker
+            ));
+        }
+
+        let init = Path::new("pkg/__init__.py");
+        let tree = python_parser().parse(SHIM_INIT, None).unwrap();
+
+        assert!(is_trivial_init(init, SHIM_INIT, None));
+        assert!(is_trivial_init(init, SHIM_INIT, Some(&tree)));
+        assert!(!is_trivial_init(init, SUBSTANTIVE_INIT, None));
+        assert!(!is_trivial_init(
+            init,
+            "import os\n\nif os.name:\n    x = 1\n",
+            None
+        ));
+        assert!(!is_trivial_init(Path::new("pkg/shim.py"), SHIM_INIT, None));
+    }
+
+    #[test]
+    fn mixed_package() {
+        let files = [
+            ("pkg/__init__.py", SHIM_INIT, true),
+            ("pkg/sub/__init__.py", SUBSTANTIVE_INIT, true),
+            ("pkg/sub/module.py", SUBSTANTIVE_INIT, false),
+            ("pkg/constants.py", "X = 1\n", true),
+        ];
+
+        let mut stats = Stats::default();
+
+        for (_, source_code, success) in files {
+            stats.record(is_trivial(source_code, CompliancyChecker::Lexer), success);
+        }
+
+        assert_eq!(
+            stats,
+            Stats {
+                files: 4,
+                files_with_errors: 1,
+                trivial_files: 2,
+                trivial_files_with_errors: 0,
+                ..Default::default()
+            }
+        );
+
+        assert!(stats
+            .summary(false)
+            .starts_with("Checked 2 files, 1 with errors (2 trivial files not counted), and"));
+        assert!(stats
+            .summary(true)
+            .starts_with("Checked 4 files, 1 with errors, and"));
+    }
+
+    #[test]
+    fn statistics() {
+        use std::path::PathBuf;
+
+        use crate::diagnostics::{Diagnostic, Severity, Spans};
+
+        let diagnostic = |path: &str, rule| Diagnostic {
+            rule,
+            path: Some(PathBuf::from(path)),
+            function: "f".to_string(),
+            row: None,
+            column: None,
+            function_row: None,
+            message: String::new(),
+            severity: Severity::Error,
+            spans: Spans::default(),
+            entries: None,
+            fix: None,
+        };
+
+        let report = |path: &str, rules: &[Rule]| SourceReport {
+            path: Some(PathBuf::from(path)),
+            diagnostics: rules.iter().map(|rule| diagnostic(path, *rule)).collect(),
+            functions: Vec::new(),
+            suppressed: 0,
+            definitions: 0,
+        };
+
+        let reports = [
+            report("setup.py", &[Rule::MissingDocstring]),
+            report(
+                "src/pkg/a.py",
+                &[Rule::MissingArg, Rule::ExtraArg, Rule::MissingArg],
+            ),
+            report("tests/test_a.py", &[Rule::MissingArg, Rule::ExtraArg]),
+        ];
+
+        assert_eq!(
+            statistics_table(&rule_counts(&reports, StatisticsGrouping::Rule)),
+            "3  PSD201  missing-arg\n\
+             2  PSD202  extra-arg\n\
+             1  PSD101  missing-docstring"
+        );
+        assert_eq!(
+            statistics_table(&rule_counts(&reports, StatisticsGrouping::Directory)),
+            "2  src    PSD201  missing-arg\n\
+             1  .      PSD101  missing-docstring\n\
+             1  src    PSD202  extra-arg\n\
+             1  tests  PSD201  missing-arg\n\
+             1  tests  PSD202  extra-arg"
+        );
+
+        assert_eq!(
+            directory_table(&directory_stats(&reports, 1)),
+            "Directory  Files  Functions  Violations  Rules\n\
+             src            1          0           3  PSD201 (2), PSD202 (1)\n\
+             tests          1          0           2  PSD201 (1), PSD202 (1)\n\
+             .              1          0           1  PSD101 (1)"
+        );
+
+        let directories: Vec<_> = directory_stats(&reports, 2)
+            .into_iter()
+            .map(|directory| directory.directory)
+            .collect();
+
+        assert_eq!(directories, ["src/pkg", "tests", "."]);
+    }
+
+    #[test]
+    fn functions() {
+        let source_code = r#"def f(x, y):
+    """Hey.
+
+    Args:
+        z: Something.
+    """
+
+def g(x):
+    """Hey.
+
+    Args:
+        x: Something.
+    """
+"#;
+
+        let report = crate::rules_checking::check_source_report(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &Default::default(),
+            None,
+        )
+        .unwrap();
+
+        let mut stats = Stats::default();
+        stats.record_functions(&report);
+
+        assert_eq!(stats.functions, 2);
+        assert_eq!(stats.functions_with_errors, 1);
+        assert_eq!(stats.violations, 3);
+        assert_eq!(stats.warnings, 0);
+
+        let mut options = crate::rules_checking::CheckOptions::default();
+        options.rules.warn = vec!["PSD2".parse().unwrap()];
+
+        let report = crate::rules_checking::check_source_report(
+            source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &options,
+            None,
+        )
+        .unwrap();
+
+        let mut stats = Stats::default();
+        stats.record_functions(&report);
+
+        assert_eq!(stats.functions_with_errors, 0);
+        assert_eq!(stats.violations, 0);
+        assert_eq!(stats.warnings, 3);
+
+        let source_code = source_code.replace("def g(x):", "def g(x):  # pystaleds: ignore");
+
+        let report = crate::rules_checking::check_source_report(
+            &source_code,
+            None,
+            CompliancyChecker::TreeSitter,
+            &Default::default(),
+            None,
+        )
+        .unwrap();
+
+        let mut stats = Stats::default();
+        stats.record_functions(&report);
+
+        assert_eq!(stats.functions, 1);
+        assert_eq!(stats.suppressed_functions, 1);
+        assert!(stats
+            .summary(false)
+            .contains("and 1 function, 1 with errors (1 suppressed by comments);"));
+    }
+
+    #[test]
+    fn skipped_files() {
+        let mut stats = Stats {
+            skipped_files: 4,
+            too_large_files: 1,
+            ..Default::default()
+        };
+
+        assert!(stats
+            .summary(false)
+            .contains("; skipped 4 files (1 too large);"));
+
+        stats.generated_files = 2;
+
+        assert!(stats
+            .summary(false)
+            .contains("; skipped 4 files (2 generated, 1 too large);"));
+    }
+}
@@ -0,0 +1,1301 @@
+use assert_cmd::Command;
+
+fn pystaleds() -> Command {
+    Command::cargo_bin("pystaleds").unwrap()
+}
+
+#[test]
+fn clean() {
+    pystaleds()
+        .arg("tests/fixtures/clean.py")
+        .assert()
+        .success();
+}
+
+#[test]
+fn errors_found() {
+    pystaleds().arg("tests/fixtures/stale.py").assert().code(1);
+
+    pystaleds()
+        .arg("tests/fixtures")
+        .assert()
+        .code(1)
+        .stderr("Error: found 2 errors in 1 file\n");
+
+    // The progress is not shown when piped, with or without `--no-progress`.
+    pystaleds()
+        .args(["tests/fixtures", "--no-progress"])
+        .assert()
+        .code(1)
+        .stderr("Error: found 2 errors in 1 file\n");
+}
+
+#[test]
+fn exit_zero() {
+    let assert = pystaleds()
+        .args(["tests/fixtures/stale.py", "--exit-zero", "--color", "never"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("PSD201 `z` missing from docstring"));
+
+    pystaleds()
+        .args(["tests/fixtures/missing.py", "--exit-zero"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn max_errors() {
+    pystaleds()
+        .args(["tests/fixtures", "--max-errors", "2"])
+        .assert()
+        .success();
+
+    pystaleds()
+        .args(["tests/fixtures", "--max-errors", "1"])
+        .assert()
+        .code(1)
+        .stderr("Error: found 2 errors in 1 file, more than the maximum of 1\n");
+
+    pystaleds()
+        .args(["tests/fixtures", "--max-errors", "0"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn warn() {
+    pystaleds()
+        .args(["tests/fixtures", "--warn", "PSD201"])
+        .assert()
+        .code(1)
+        .stderr("Error: found 1 error in 1 file\n");
+
+    let assert = pystaleds()
+        .args(["tests/fixtures", "--warn", "PSD2", "--format", "json"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains(r#""severity":"warning""#));
+    assert!(!stdout.contains(r#""severity":"error""#));
+}
+
+#[test]
+fn config() {
+    for path in [
+        "tests/config/discovered",
+        "tests/config/discovered/undocumented.py",
+        "tests/config/pyproject",
+    ] {
+        pystaleds().arg(path).assert().code(1);
+    }
+
+    pystaleds()
+        .args([
+            "tests/fixtures/clean.py",
+            "--config",
+            "tests/config/discovered/pystaleds.toml",
+        ])
+        .assert()
+        .success();
+
+    let assert = pystaleds()
+        .args([
+            "tests/config/discovered",
+            "--config",
+            "tests/config/invalid.toml",
+        ])
+        .assert()
+        .code(2);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    assert!(stderr.starts_with("Error: could not parse tests/config/invalid.toml: "));
+    assert!(stderr.contains("line 2, column 11"));
+
+    let assert = pystaleds()
+        .args([
+            "tests/config/discovered",
+            "--config",
+            "tests/config/typo.toml",
+        ])
+        .assert()
+        .code(2);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    assert_eq!(
+        stderr,
+        "Error: unknown key `docstlye` in tests/config/typo.toml at line 2, did you mean `docstyle`?\n"
+    );
+
+    let assert = pystaleds()
+        .args([
+            "tests/config/discovered",
+            "--config",
+            "tests/config/missing.toml",
+        ])
+        .assert()
+        .code(2);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    assert!(stderr.starts_with("Error: could not read tests/config/missing.toml: "));
+}
+
+#[test]
+fn nested_config() {
+    let assert = pystaleds()
+        .args(["tests/config/nested", "--color", "never"])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+    let failed: Vec<_> = stdout
+        .lines()
+        .filter(|line| line.ends_with(".py"))
+        .collect();
+
+    assert_eq!(
+        failed,
+        [
+            "tests/config/nested/strict/private.py",
+            "tests/config/nested/undocumented.py",
+        ]
+    );
+
+    // An explicit configuration replaces the discovered ones.
+    let assert = pystaleds()
+        .args([
+            "tests/config/nested",
+            "--color",
+            "never",
+            "--config",
+            "tests/config/discovered/pystaleds.toml",
+        ])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert_eq!(
+        stdout.lines().filter(|line| line.ends_with(".py")).count(),
+        4
+    );
+}
+
+#[test]
+fn run_not_completed() {
+    pystaleds()
+        .arg("tests/fixtures/missing.py")
+        .assert()
+        .code(2);
+
+    pystaleds()
+        .args(["tests/fixtures", "--glob", "[*.py"])
+        .assert()
+        .code(2);
+
+    pystaleds()
+        .args(["tests/fixtures", "--parser", "lexer", "--check-raises"])
+        .assert()
+        .code(2);
+
+    pystaleds()
+        .args([
+            "tests/fixtures",
+            "--output",
+            "tests/fixtures/missing/out.json",
+        ])
+        .assert()
+        .code(2);
+
+    pystaleds().arg("--no-such-flag").assert().code(2);
+}
+
+#[test]
+fn skip_file() {
+    for parser in ["tree-sitter", "lexer"] {
+        let assert = pystaleds()
+            .args([
+                "tests/skip_file",
+                "--color",
+                "never",
+                "--forbid-no-docstring",
+                "--parser",
+                parser,
+            ])
+            .assert()
+            .success();
+        let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+        assert!(stdout.contains("and 1 function, 0 with errors; skipped 1 file;"));
+    }
+}
+
+#[test]
+fn baseline() {
+    let path = std::env::temp_dir().join(format!("pystaleds-baseline-{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    pystaleds()
+        .args(["tests/fixtures", "--baseline", path, "--write-baseline"])
+        .assert()
+        .success();
+
+    pystaleds()
+        .args(["tests/fixtures", "--baseline", path])
+        .assert()
+        .success();
+
+    std::fs::remove_file(path).unwrap();
+
+    // Entries that no longer match anything are reported, but do not fail the check.
+    let assert = pystaleds()
+        .args([
+            "tests/fixtures/stale.py",
+            "--baseline",
+            "tests/baseline/stale.json",
+        ])
+        .assert()
+        .success();
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    assert!(stderr.contains(
+        "stale entry: 1 PSD202 violation in `add` of tests/fixtures/stale.py is no longer found"
+    ));
+
+    // Entries of files that no longer exist are stale, even if other files are checked.
+    std::fs::write(
+        path,
+        r#"{"entries":[{"path":"tests/fixtures/deleted.py","function":"f","rule":"PSD201","count":2}]}"#,
+    )
+    .unwrap();
+
+    let assert = pystaleds()
+        .args(["tests/fixtures/stale.py", "--baseline", path])
+        .assert()
+        .code(1);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stderr.contains(
+        "stale entry: 2 PSD201 violations in `f` of tests/fixtures/deleted.py are no longer found"
+    ));
+    assert!(stdout.contains("which has 1 stale entry"));
+
+    std::fs::remove_file(path).unwrap();
+
+    pystaleds()
+        .args(["tests/fixtures", "--write-baseline"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn include() {
+    let failed = |args: &[&str]| {
+        let assert = pystaleds()
+            .args(["tests/include", "--color", "never", "--forbid-no-docstring"])
+            .args(args)
+            .assert()
+            .code(1);
+        let stdout = String::from_utf8_lossy(&assert.get_output().stdout).to_string();
+
+        stdout
+            .lines()
+            .filter(|line| line.ends_with(".py"))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        failed(&["--include", "src/**", "--include", "tools/**"]),
+        ["tests/include/src/nested/a.py", "tests/include/tools/b.py"]
+    );
+
+    // `*` does not match across folders.
+    assert_eq!(
+        failed(&["--include", "src/*.py", "--include", "tools/*.py"]),
+        ["tests/include/tools/b.py"]
+    );
+
+    // The matches of `--glob` are filtered by the includes.
+    assert_eq!(
+        failed(&["--glob", "*/*.py", "--include", "tools/**"]),
+        ["tests/include/tools/b.py"]
+    );
+
+    pystaleds()
+        .args(["tests/include", "--include", "["])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn globs() {
+    let assert = pystaleds()
+        .args([
+            "tests/include",
+            "--color",
+            "never",
+            "--forbid-no-docstring",
+            "--glob",
+            "src/**/*.py,tools/*.py",
+            "--glob",
+            "**/b.py",
+        ])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    // `tools/b.py` matches two globs, but is checked once.
+    assert!(stdout.contains("Checked 2 files, 2 with errors"));
+    assert!(!stdout.contains("other/c.py"));
+
+    let failed = |globs: &[&str]| {
+        let mut command = pystaleds();
+        command.args(["tests/globs", "--color", "never", "--forbid-no-docstring"]);
+
+        for glob in globs {
+            command.args(["--glob", glob]);
+        }
+
+        let assert = command.assert().code(1);
+        let stdout = String::from_utf8_lossy(&assert.get_output().stdout).to_string();
+
+        stdout
+            .lines()
+            .filter(|line| line.ends_with(".py"))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        failed(&["src/**/*.py", "!src/generated/**"]),
+        ["tests/globs/src/a.py"]
+    );
+
+    // Later globs override earlier ones.
+    assert_eq!(
+        failed(&["src/**/*.py", "!src/generated/**", "src/generated/keep.py"]),
+        ["tests/globs/src/a.py", "tests/globs/src/generated/keep.py"]
+    );
+    assert_eq!(
+        failed(&["!src/generated/**", "src/**/*.py"]),
+        [
+            "tests/globs/src/a.py",
+            "tests/globs/src/generated/drop.py",
+            "tests/globs/src/generated/keep.py"
+        ]
+    );
+
+    let assert = pystaleds()
+        .args(["tests/include", "--glob", "*.py", "--glob", "["])
+        .assert()
+        .code(2);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    assert!(stderr.contains("invalid glob `[`"));
+}
+
+#[test]
+fn hidden() {
+    for glob in [None, Some("**/*.py")] {
+        let run = |allow_hidden: bool| {
+            let mut command = pystaleds();
+            command.args(["tests/hidden", "--forbid-no-docstring"]);
+            command.args(glob.map(|glob| ["--glob", glob]).into_iter().flatten());
+
+            if allow_hidden {
+                command.arg("--allow-hidden");
+            }
+
+            command.assert()
+        };
+
+        run(false).success();
+        run(true).code(1);
+    }
+}
+
+#[test]
+fn env() {
+    pystaleds()
+        .arg("tests/include/other/c.py")
+        .assert()
+        .success();
+
+    pystaleds()
+        .arg("tests/include/other/c.py")
+        .env("PYSTALEDS_FORBID_NO_DOCSTRING", "1")
+        .assert()
+        .code(1);
+
+    // The environment takes precedence over the configuration files.
+    pystaleds()
+        .arg("tests/config/discovered")
+        .env("PYSTALEDS_FORBID_NO_DOCSTRING", "false")
+        .assert()
+        .success();
+
+    // The command line takes precedence over the environment.
+    pystaleds()
+        .args(["tests/config/discovered", "--select", "PSD101"])
+        .env("PYSTALEDS_SELECT", "PSD2")
+        .assert()
+        .code(1);
+
+    let assert = pystaleds()
+        .arg("tests/config/discovered")
+        .env("PYSTALEDS_FORBID_NO_DOCSTRING", "maybe")
+        .assert()
+        .code(2);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    assert!(stderr.contains("environment variable PYSTALEDS_FORBID_NO_DOCSTRING"));
+}
+
+#[test]
+fn show_config() {
+    let assert = pystaleds()
+        .args(["tests/config/nested/lenient", "--show-config"])
+        .args(["--parser", "tree-sitter"])
+        .env("PYSTALEDS_IGNORE_DUNDER", "true")
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+    let line = |key: &str| {
+        stdout
+            .lines()
+            .find(|line| line.starts_with(&format!("{key} =")))
+            .unwrap()
+            .to_string()
+    };
+
+    // The deepest configuration file takes precedence over the ones of its parents.
+    assert!(line("forbid_no_docstring").starts_with("forbid_no_docstring = false  # "));
+    assert!(line("forbid_no_docstring").ends_with("lenient/pystaleds.toml"));
+    assert!(line("ignore_private").ends_with("nested/pystaleds.toml"));
+    assert_eq!(line("parser"), r#"parser = "tree-sitter"  # command line"#);
+    assert_eq!(
+        line("ignore_dunder"),
+        "ignore_dunder = true  # environment (PYSTALEDS_IGNORE_DUNDER)"
+    );
+    assert_eq!(line("check_raises"), "check_raises = false  # default");
+
+    let assert = pystaleds()
+        .args([
+            "tests/config/nested/lenient/undocumented.py",
+            "--show-config",
+        ])
+        .args(["--format", "json"])
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+
+    assert_eq!(json["forbid_no_docstring"]["value"], false);
+    assert_eq!(json["ignore_private"]["value"], true);
+    assert_eq!(json["check_raises"]["source"], "default");
+}
+
+#[test]
+fn per_file() {
+    // The same violation fails in `src` but passes in `tests`, unless in `tests/strict`.
+    pystaleds().arg("tests/per_file/src").assert().code(1);
+    pystaleds()
+        .arg("tests/per_file/tests/undocumented.py")
+        .assert()
+        .success();
+    pystaleds()
+        .arg("tests/per_file/tests/strict")
+        .assert()
+        .code(1);
+
+    // `src/__init__.py` would fail as well if it was not skipped.
+    let assert = pystaleds().arg("tests/per_file").assert().code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("src/undocumented.py"));
+    assert!(stdout.contains("strict/undocumented.py"));
+    assert!(!stdout.contains("__init__.py"));
+    assert!(stdout.contains("Checked 3 files, 2 with errors"));
+
+    pystaleds()
+        .arg("tests/per_file/src/__init__.py")
+        .assert()
+        .success();
+}
+
+#[test]
+fn default_excludes() {
+    pystaleds()
+        .args(["tests/default_excludes", "--forbid-no-docstring"])
+        .assert()
+        .success();
+
+    pystaleds()
+        .args(["tests/default_excludes", "--forbid-no-docstring"])
+        .args(["--glob", "**/*.py"])
+        .assert()
+        .success();
+
+    let assert = pystaleds()
+        .args(["tests/default_excludes", "--forbid-no-docstring"])
+        .arg("--verbose")
+        .assert()
+        .success();
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    assert!(stderr.contains("tests/default_excludes/build: skipped: folder excluded by default"));
+    assert!(stderr.contains("tests/default_excludes/venv: skipped"));
+    assert!(stderr.contains("tests/default_excludes/src/__pycache__: skipped"));
+
+    let assert = pystaleds()
+        .args(["tests/default_excludes", "--forbid-no-docstring"])
+        .arg("--no-default-excludes")
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("Checked 4 files, 3 with errors"));
+
+    // A path given explicitly is checked even if it would be excluded.
+    pystaleds()
+        .args(["tests/default_excludes/build", "--forbid-no-docstring"])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn skip_generated() {
+    let assert = pystaleds()
+        .args(["tests/generated", "--forbid-no-docstring"])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("handwritten.py"));
+    assert!(!stdout.contains("messages_pb2.py"));
+    assert!(stdout.contains("skipped 2 files (2 generated)"));
+
+    let assert = pystaleds()
+        .args(["tests/generated", "--forbid-no-docstring"])
+        .arg("--skip-generated=false")
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("Checked 3 files, 3 with errors"));
+
+    let assert = pystaleds()
+        .args(["tests/generated", "--forbid-no-docstring"])
+        .args(["--generated-markers", "protocol buffer compiler"])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("0001_initial.py"));
+    assert!(stdout.contains("skipped 1 file (1 generated)"));
+
+    pystaleds()
+        .args(["tests/generated/messages_pb2.py", "--forbid-no-docstring"])
+        .assert()
+        .success();
+
+    pystaleds()
+        .args(["tests/generated/messages_pb2.py", "--forbid-no-docstring"])
+        .env("PYSTALEDS_SKIP_GENERATED", "0")
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn max_file_size() {
+    let assert = pystaleds()
+        .args(["tests/max_file_size", "--forbid-no-docstring"])
+        .args(["--max-file-size", "1000"])
+        .assert()
+        .success();
+    let output = assert.get_output();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(stdout.contains("skipped 1 file (1 too large)"));
+    assert!(stderr.contains("large.py: skipped: has 2765 bytes"));
+
+    pystaleds()
+        .args(["tests/max_file_size", "--forbid-no-docstring"])
+        .args(["--max-file-size", "1000", "--glob", "*.py"])
+        .assert()
+        .success();
+
+    // Both the default and disabling the limit let it through.
+    for size in ["1048576", "0"] {
+        pystaleds()
+            .args(["tests/max_file_size", "--forbid-no-docstring"])
+            .args(["--max-file-size", size])
+            .assert()
+            .code(1);
+    }
+}
+
+#[test]
+fn files_from() {
+    let assert = pystaleds()
+        .args(["--files-from", "tests/files_from/list.txt", "--nd"])
+        .assert()
+        .code(1);
+    let output = assert.get_output();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(stdout.contains("tests/generated/handwritten.py"));
+    assert!(stdout.contains("Checked 2 files, 1 with errors"));
+    assert!(stderr.contains("tests/files_from/deleted.py: listed in"));
+
+    pystaleds()
+        .args(["--files-from", "-", "--nd"])
+        .write_stdin("tests/fixtures/clean.py\nREADME.md\n\ntests/files_from/deleted.py\n")
+        .assert()
+        .success();
+
+    pystaleds()
+        .args(["--files-from", "-", "--nd", "--include", "src/**"])
+        .write_stdin("tests/generated/handwritten.py\n")
+        .assert()
+        .success();
+
+    pystaleds()
+        .args(["--files-from", "tests/files_from/missing.txt"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn files_from_exclusions() {
+    let listed = "tests/default_excludes/src/module.py\n\
+                  tests/default_excludes/build/generated.py\n\
+                  tests/default_excludes/venv/lib/python3.12/site-packages/vendored.py\n\
+                  tests/hidden/.hidden/failing.py\n";
+
+    let run = |args: &[&str]| {
+        let assert = pystaleds()
+            .args(["--files-from", "-", "--forbid-no-docstring"])
+            .args(args)
+            .write_stdin(listed)
+            .assert();
+        let stdout = String::from_utf8_lossy(&assert.get_output().stdout).to_string();
+
+        (assert, stdout)
+    };
+
+    // Listed files are left out as the ones found when walking through the folder.
+    let (assert, stdout) = run(&[]);
+    assert.success();
+    assert!(stdout.contains("Checked 1 file, 0 with errors"));
+
+    let (assert, stdout) = run(&["--no-default-excludes"]);
+    assert.code(1);
+    assert!(stdout.contains("Checked 3 files, 2 with errors"));
+
+    let (assert, stdout) = run(&["--allow-hidden", "--no-default-excludes"]);
+    assert.code(1);
+    assert!(stdout.contains("Checked 4 files, 3 with errors"));
+
+    let (assert, stdout) = run(&["--no-default-excludes", "--glob", "!**/build/**"]);
+    assert.code(1);
+    assert!(stdout.contains("Checked 2 files, 1 with errors"));
+
+    let (assert, stdout) = run(&["--no-default-excludes", "--glob", "**/src/**"]);
+    assert.success();
+    assert!(stdout.contains("Checked 1 file, 0 with errors"));
+}
+
+#[test]
+fn stdin() {
+    let source_code = std::fs::read_to_string("tests/generated/handwritten.py").unwrap();
+
+    let assert = pystaleds()
+        .args(["--stdin", "--stdin-filename", "src/foo.py", "--nd"])
+        .write_stdin(source_code.clone())
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.starts_with("src/foo.py\n"));
+    assert!(stdout.contains("1:1 `add` PSD101 Docstring missing"));
+
+    pystaleds()
+        .args(["--stdin", "--stdin-filename", "src/foo.py"])
+        .write_stdin(source_code.clone())
+        .assert()
+        .success();
+
+    // The configuration is resolved with the filename, even if it does not exist.
+    pystaleds()
+        .args(["--stdin", "--stdin-filename", "tests/per_file/src/new.py"])
+        .write_stdin(source_code.clone())
+        .assert()
+        .code(1);
+    pystaleds()
+        .args(["--stdin", "--stdin-filename", "tests/per_file/tests/new.py"])
+        .write_stdin(source_code.clone())
+        .assert()
+        .success();
+    pystaleds()
+        .args([
+            "--stdin",
+            "--stdin-filename",
+            "tests/per_file/src/__init__.py",
+        ])
+        .write_stdin(source_code.clone())
+        .assert()
+        .success();
+
+    let assert = pystaleds()
+        .args(["--stdin", "--nd", "--format", "json"])
+        .write_stdin(source_code)
+        .assert()
+        .code(1);
+    let json: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+
+    assert_eq!(json[0]["file"], "-");
+
+    pystaleds()
+        .args(["--stdin-filename", "src/foo.py", "tests/fixtures/clean.py"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn fix() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("stale_args.py");
+    std::fs::copy("tests/fix/stale_args.py", &path).unwrap();
+
+    // The numpy docstring is not fixed, so it is still reported.
+    let assert = pystaleds()
+        .args([path.to_str().unwrap(), "--fix", "--color", "never"])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("`subtract` PSD201 `b` missing from docstring"));
+    assert!(!stdout.contains("`add`"));
+    assert!(stdout.contains("Fixed 1 function in 1 file"));
+
+    let fixed = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        fixed,
+        std::fs::read_to_string("tests/fix/expected.py").unwrap()
+    );
+
+    pystaleds()
+        .args(["--stdin", "--fix"])
+        .write_stdin(fixed)
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn diff() {
+    let source_code = std::fs::read_to_string("tests/fix/renamed.py").unwrap();
+
+    let assert = pystaleds()
+        .args(["tests/fix/renamed.py", "--diff"])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    assert_eq!(
+        stdout,
+        std::fs::read_to_string("tests/fix/renamed.diff").unwrap()
+    );
+    assert!(stderr.contains("1 function would be fixed in 1 file"));
+
+    // The file is left untouched.
+    assert_eq!(
+        std::fs::read_to_string("tests/fix/renamed.py").unwrap(),
+        source_code
+    );
+
+    let assert = pystaleds()
+        .args(["tests/fix/expected.py", "--diff"])
+        .assert()
+        .success();
+
+    assert!(assert.get_output().stdout.is_empty());
+}
+
+#[test]
+fn json_fixes() {
+    let mut source_code = std::fs::read_to_string("tests/fix/renamed.py").unwrap();
+
+    let assert = pystaleds()
+        .args(["tests/fix/renamed.py", "--format", "json"])
+        .assert()
+        .code(1);
+    let json: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+
+    // Both the missing and the extra arg are fixed by the same edits.
+    let fixes: Vec<_> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|diagnostic| &diagnostic["fix"])
+        .collect();
+
+    assert_eq!(fixes.len(), 2);
+    assert_eq!(fixes[0], fixes[1]);
+    assert_eq!(fixes[0]["title"], "update Args section to match signature");
+
+    let mut edits = fixes[0]["edits"].as_array().unwrap().clone();
+    edits.sort_by_key(|edit| edit["start_byte"].as_u64());
+
+    for edit in edits.iter().rev() {
+        let start = edit["start_byte"].as_u64().unwrap() as usize;
+        let end = edit["end_byte"].as_u64().unwrap() as usize;
+
+        source_code.replace_range(start..end, edit["replacement"].as_str().unwrap());
+    }
+
+    pystaleds()
+        .args(["--stdin", "--stdin-filename", "tests/fix/renamed.py"])
+        .write_stdin(source_code)
+        .assert()
+        .success();
+}
+
+#[test]
+fn fix_output() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("changes.patch");
+
+    let assert = pystaleds()
+        .args(["tests/fix", "--fix-output", path.to_str().unwrap()])
+        .assert()
+        .code(1);
+
+    assert!(assert.get_output().stdout.is_empty());
+
+    let patch = std::fs::read_to_string(&path).unwrap();
+
+    assert!(patch.starts_with("--- a/tests/fix/renamed.py\n+++ b/tests/fix/renamed.py\n"));
+    assert!(patch.contains("\n--- a/tests/fix/stale_args.py\n+++ b/tests/fix/stale_args.py\n"));
+
+    // The patch holds the same changes as the diff.
+    let assert = pystaleds().args(["tests/fix", "--diff"]).assert().code(1);
+
+    assert_eq!(String::from_utf8_lossy(&assert.get_output().stdout), patch);
+
+    pystaleds()
+        .args([
+            "tests/fix/expected.py",
+            "--fix-output",
+            path.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    assert!(std::fs::read_to_string(&path).unwrap().is_empty());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn fix_only() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("renamed.py");
+    std::fs::copy("tests/fix/renamed.py", &path).unwrap();
+
+    // The extra arg is removed even if it is not reported, but the missing one is not
+    // added, so it is still reported.
+    let assert = pystaleds()
+        .args([path.to_str().unwrap(), "--fix", "--fix-only", "PSD202"])
+        .args(["--ignore", "PSD202", "--color", "never"])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("PSD201 `name` missing from docstring"));
+    assert!(stdout.contains("Fixed 1 function in 1 file"));
+
+    let fixed = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(!fixed.contains("person"));
+    assert!(!fixed.contains("_description_"));
+    assert!(fixed.contains("    Args:\n        greeting (str): How they are greeted."));
+}
+
+#[test]
+fn watch() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("watch");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy("tests/fixtures/clean.py", dir.join("module.py")).unwrap();
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_pystaleds"))
+        .args([dir.to_str().unwrap(), "--watch", "--color", "never"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // The files are only watched once the initial run is done.
+    let mut stderr = BufReader::new(child.stderr.take().unwrap()).lines();
+    assert!(stderr.any(|line| line.unwrap().contains("watching for changes")));
+
+    let (sender, receiver) = mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let wait_for = |text: &str| loop {
+        let line = receiver
+            .recv_timeout(Duration::from_secs(10))
+            .unwrap_or_else(|_| panic!("`{text}` should be printed"));
+
+        if line.contains(text) {
+            break;
+        }
+    };
+
+    wait_for("✅ Success!");
+
+    std::fs::copy("tests/fixtures/stale.py", dir.join("module.py")).unwrap();
+    wait_for("Checked 1 file, 1 with errors");
+
+    std::fs::remove_file(dir.join("module.py")).unwrap();
+    wait_for("module.py ✅ Resolved");
+    wait_for("Checked 0 files, 0 with errors");
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn diff_against() {
+    let repo = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("diff_against");
+    let _ = std::fs::remove_dir_all(&repo);
+    std::fs::create_dir_all(&repo).unwrap();
+
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args([
+                "-c",
+                "user.name=pystaleds",
+                "-c",
+                "user.email=pystaleds@example.com",
+            ])
+            .args(args)
+            .current_dir(&repo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+
+    let legacy = std::fs::read_to_string("tests/fixtures/stale.py").unwrap();
+    let touched = legacy.replace("add", "total");
+    std::fs::write(repo.join("legacy.py"), &legacy).unwrap();
+    std::fs::write(repo.join("touched.py"), &touched).unwrap();
+
+    git(&["init", "--quiet"]);
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "-m", "Initial commit"]);
+
+    std::fs::write(
+        repo.join("touched.py"),
+        touched.replace("return x + y + z", "return z + y + x"),
+    )
+    .unwrap();
+    std::fs::write(repo.join("new.py"), legacy.replace("add", "new")).unwrap();
+
+    let assert = pystaleds()
+        .current_dir(&repo)
+        .args([".", "--diff-against", "HEAD", "--color", "never"])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("`total` PSD201"));
+    assert!(stdout.contains("`new` PSD201"));
+    assert!(!stdout.contains("`add`"));
+    assert!(stdout.contains("Left out 2 violations in functions not touched since HEAD"));
+
+    // The legacy violations are reported, but do not fail the check.
+    let assert = pystaleds()
+        .current_dir(&repo)
+        .args(["legacy.py", "--diff-against", "HEAD", "--report-untouched"])
+        .args(["--color", "never"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("`add` PSD201"));
+    assert!(stdout.contains("Reported 2 violations in functions not touched since HEAD"));
+
+    pystaleds()
+        .current_dir(&repo)
+        .args([".", "--diff-against", "no-such-ref"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn subcommands() {
+    // Checking is the default, so the options can be given with or without `check`.
+    pystaleds()
+        .args(["check", "tests/fixtures"])
+        .assert()
+        .code(1)
+        .stderr("Error: found 2 errors in 1 file\n");
+
+    pystaleds()
+        .args(["check", "--stdin", "--stdin-filename", "module.py"])
+        .write_stdin(std::fs::read_to_string("tests/fixtures/clean.py").unwrap())
+        .assert()
+        .success();
+
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("fix_command.py");
+    std::fs::copy("tests/fix/stale_args.py", &path).unwrap();
+
+    let assert = pystaleds()
+        .args(["fix", path.to_str().unwrap(), "--color", "never"])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+
+    assert!(stdout.contains("Fixed 1 function in 1 file"));
+
+    let fixed = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        fixed,
+        std::fs::read_to_string("tests/fix/expected.py").unwrap()
+    );
+
+    let assert = pystaleds()
+        .args(["fix", "tests/fix/renamed.py", "--diff"])
+        .assert()
+        .code(1);
+
+    assert_eq!(
+        String::from_utf8_lossy(&assert.get_output().stdout),
+        std::fs::read_to_string("tests/fix/renamed.diff").unwrap()
+    );
+
+    // The other commands print the same as the flags they stand for.
+    for (command, flag) in [("rules", "--list-rules"), ("show-config", "--show-config")] {
+        let output = |args: &[&str]| {
+            let assert = pystaleds()
+                .args(args)
+                .args(["tests/config/nested/lenient", "--format", "json"])
+                .env("PYSTALEDS_IGNORE_DUNDER", "true")
+                .assert()
+                .success();
+
+            assert.get_output().stdout.clone()
+        };
+
+        assert_eq!(output(&[command]), output(&[flag]));
+    }
+
+    pystaleds()
+        .args(["rules", "tests/fixtures", "--glob", "*.py"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn list_files() {
+    let assert = pystaleds()
+        .args(["tests/per_file", "--list-files", "-v"])
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+    // Sorted, and without the files configured with `skip = true`.
+    assert_eq!(
+        stdout,
+        "tests/per_file/src/undocumented.py\n\
+         tests/per_file/tests/strict/undocumented.py\n\
+         tests/per_file/tests/undocumented.py\n"
+    );
+    assert!(
+        stderr.contains("tests/per_file/src/__init__.py: skipped: configured with `skip = true`")
+    );
+    assert!(stderr.contains("tests/per_file/pystaleds.toml: skipped: not a Python file"));
+
+    let assert = pystaleds()
+        .args(["tests/default_excludes", "--list-files"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        String::from_utf8_lossy(&assert.get_output().stdout),
+        "tests/default_excludes/src/module.py\n"
+    );
+
+    let assert = pystaleds()
+        .args(["tests/hidden", "--list-files", "--glob", "**/*.py", "-v"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        String::from_utf8_lossy(&assert.get_output().stdout),
+        "tests/hidden/visible.py\n"
+    );
+    assert!(String::from_utf8_lossy(&assert.get_output().stderr)
+        .contains("tests/hidden/.hidden/failing.py: skipped: hidden, or in a hidden folder"));
+}
+
+#[test]
+fn stats_by() {
+    let assert = pystaleds()
+        .args(["tests/config", "--stats-by", "dir", "--depth", "3"])
+        .args(["--forbid-no-docstring", "--color", "never"])
+        .assert()
+        .code(1);
+    let stdout = String::from_utf8_lossy(&assert.get_output().stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+
+    // The folders nested deeper are counted along with their parents.
+    assert_eq!(
+        lines[1],
+        "tests/config/nested          4          3           3  PSD101 (3)"
+    );
+    assert!(!stdout.contains("missing-docstring"));
+
+    let assert = pystaleds()
+        .args(["tests/config", "--stats-by", "dir", "--depth", "3"])
+        .args(["--forbid-no-docstring", "--format", "json"])
+        .assert()
+        .code(1);
+    let json: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+
+    assert_eq!(json[0]["directory"], "tests/config/nested");
+    assert_eq!(json[0]["rules"][0]["code"], "PSD101");
+
+    pystaleds()
+        .args(["tests/config", "--stats-by", "dir", "--format", "sarif"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn quickfix() {
+    let expected = "tests/fixtures/stale.py:4:5: PSD201 `z` missing from docstring\n\
+                    tests/fixtures/stale.py:6:9: PSD203 `y`: signature says `int`, docstring says `str`\n";
+
+    // Both parsers know the line and column of every error.
+    for parser in ["lexer", "tree-sitter"] {
+        pystaleds()
+            .args(["tests/fixtures", "--format", "quickfix", "--parser", parser])
+            .args(["--color", "always"])
+            .assert()
+            .code(1)
+            .stdout(expected);
+    }
+
+    pystaleds()
+        .args(["tests/fixtures/clean.py", "--format", "quickfix"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn positions() {
+    for parser in ["tree-sitter", "lexer"] {
+        let output = |format: &str| {
+            let assert = pystaleds()
+                .args([
+                    "tests/fixtures/stale.py",
+                    "--parser",
+                    parser,
+                    "--format",
+                    format,
+                ])
+                .assert()
+                .code(1);
+
+            serde_json::from_slice::<serde_json::Value>(&assert.get_output().stdout).unwrap()
+        };
+
+        // The missing arg points at the heading of the args, and the wrong type at its entry.
+        let json = output("json");
+        let positions: Vec<_> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| {
+                (
+                    error["code"].clone(),
+                    error["line"].clone(),
+                    error["column"].clone(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            positions,
+            [
+                ("PSD201".into(), 4.into(), 5.into()),
+                ("PSD203".into(), 6.into(), 9.into()),
+            ]
+        );
+
+        let sarif = output("sarif");
+        let regions: Vec<_> = sarif["runs"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| result["locations"][0]["physicalLocation"]["region"].clone())
+            .collect();
+
+        assert_eq!(
+            regions,
+            [
+                serde_json::json!({ "startLine": 4, "startColumn": 5 }),
+                serde_json::json!({ "startLine": 6, "startColumn": 9 }),
+            ]
+        );
+    }
+}
+
+#[test]
+fn rdjson() {
+    let assert = pystaleds()
+        .args(["tests/fixtures/stale.py", "--format", "rdjson"])
+        .assert()
+        .code(1);
+    let rdjson: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+
+    // The missing arg is fixed by rewriting the section, but the wrong type is not fixed.
+    let golden = std::fs::read_to_string("tests/fixtures/stale.rdjson").unwrap();
+    assert_eq!(
+        rdjson,
+        serde_json::from_str::<serde_json::Value>(&golden).unwrap()
+    );
+
+    // Vendored from reviewdog's proto/rdf/jsonschema, which reviewdog reads rdjson with.
+    let schema = std::fs::read_to_string("tests/schemas/DiagnosticResult.jsonschema").unwrap();
+    let validator = jsonschema::validator_for(&serde_json::from_str(&schema).unwrap()).unwrap();
+    let errors: Vec<_> = validator
+        .iter_errors(&rdjson)
+        .map(|error| error.to_string())
+        .collect();
+
+    assert!(errors.is_empty(), "{errors:?}");
+}
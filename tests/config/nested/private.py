@@ -0,0 +1,2 @@
+def _helper(x):
+    return x
@@ -0,0 +1,11 @@
+def add(x: int, y: int) -> int:
+    """Adds two numbers.
+
+    Args:
+        x (int): First number.
+        y (int): Second number.
+
+    Returns:
+        int: The sum.
+    """
+    return x + y
@@ -0,0 +1,24 @@
+def add(a: int, b: int, c: int) -> int:
+    """Adds the numbers.
+
+    Args:
+        a (int): First number,
+            which is added first.
+        b (int): Second number.
+        c (int): _description_
+
+    Returns:
+        int: The sum.
+    """
+    return a + b + c
+
+
+def subtract(a: int, b: int) -> int:
+    """Subtracts the numbers.
+
+    Parameters
+    ----------
+    a : int
+        First number.
+    """
+    return a - b
@@ -0,0 +1,14 @@
+"""Utilities for greeting people."""
+
+
+def greet(name: str, greeting: str = "Hello") -> str:
+    """Greets someone.
+
+    Args:
+        person (str): Who is greeted.
+        greeting (str): How they are greeted. Defaults to "Hello".
+
+    Returns:
+        str: The greeting.
+    """
+    return f"{greeting}, {name}!"
@@ -0,0 +1,24 @@
+def add(a: int, b: int, c: int) -> int:
+    """Adds the numbers.
+
+    Args:
+        b (int): Second number.
+        a (int): First number,
+            which is added first.
+        d (int): Removed number.
+
+    Returns:
+        int: The sum.
+    """
+    return a + b + c
+
+
+def subtract(a: int, b: int) -> int:
+    """Subtracts the numbers.
+
+    Parameters
+    ----------
+    a : int
+        First number.
+    """
+    return a - b
@@ -0,0 +1,11 @@
+def add(x: int, y: int, z: int) -> int:
+    """Adds three numbers.
+
+    Args:
+        x (int): First number.
+        y (str): Second number.
+
+    Returns:
+        int: The sum.
+    """
+    return x + y + z
@@ -0,0 +1,5 @@
+# Generated by Django 5.0 on 2024-01-01 00:00
+
+
+def forwards(apps, schema_editor):
+    pass
@@ -0,0 +1,7 @@
+# -*- coding: utf-8 -*-
+# Generated by the protocol buffer compiler.  DO NOT EDIT!
+# source: messages.proto
+
+
+def add(x, y):
+    return x + y
@@ -0,0 +1,6 @@
+def f(x):
+    """Hey.
+
+    Args:
+        x: Something.
+    """
@@ -0,0 +1,5 @@
+# Generated by protoc. DO NOT EDIT!
+# pystaleds: skip-file
+
+def f(x):
+    pass